@@ -0,0 +1,60 @@
+// src/rust_data_collector/benches/smard_parse.rs
+//
+// Parse + filter cost of a large SMARD `index_hour.json`, the hot path when backfilling.
+// Filtering during deserialization avoids materializing the full series, and reading from
+// the response stream avoids holding the raw body at all.
+// Run with `cargo bench --bench smard_parse`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use rust_data_collector::{parse_smard_reader, parse_smard_response, SmardApiResponse};
+use std::io::BufReader;
+
+const HOUR_MS: i64 = 3_600_000;
+const START_MS: i64 = 1_420_070_400_000; // 2015-01-01T00:00:00Z
+
+// Ten years of hourly points in SMARD's shape, with the nulls SMARD uses for
+// unpublished hours sprinkled in: roughly 4 MB of JSON.
+fn large_index_fixture() -> String {
+    let points: Vec<String> = (0..10 * 365 * 24)
+        .map(|i| {
+            let value = if i % 97 == 0 {
+                "null".to_string()
+            } else {
+                format!("{:.2}", 40.0 + ((i % 24) as f64 - 12.0) * 3.7 + (i % 13) as f64 * 0.31)
+            };
+            format!("{{\"timestamp\":{},\"value\":{}}}", START_MS + i as i64 * HOUR_MS, value)
+        })
+        .collect();
+    format!("{{\"data\":[{}]}}", points.join(","))
+}
+
+fn bench_parse_and_filter(c: &mut Criterion) {
+    let body = large_index_fixture();
+    // The typical query: the last 48 hours of the series.
+    let end_ms = START_MS + (10 * 365 * 24 - 1) * HOUR_MS;
+    let start_ms = end_ms - 48 * HOUR_MS;
+
+    let mut group = c.benchmark_group("smard_index_hour");
+    group.throughput(Throughput::Bytes(body.len() as u64));
+    // The original approach: materialize the whole series, then filter it.
+    group.bench_function("from_str_then_filter", |b| {
+        b.iter(|| {
+            let parsed: SmardApiResponse = serde_json::from_str(black_box(&body)).unwrap();
+            parsed.data.into_iter()
+                .filter(|dp| dp.timestamp >= start_ms && dp.timestamp <= end_ms)
+                .count()
+        })
+    });
+    // Filtering while deserializing, over an in-memory body.
+    group.bench_function("windowed_from_str", |b| {
+        b.iter(|| parse_smard_response(black_box(&body), start_ms, end_ms).unwrap())
+    });
+    // What the fetch path does now: filtering while reading from a (buffered) stream.
+    group.bench_function("windowed_from_reader", |b| {
+        b.iter(|| parse_smard_reader(BufReader::new(black_box(body.as_bytes())), start_ms, end_ms).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse_and_filter);
+criterion_main!(benches);
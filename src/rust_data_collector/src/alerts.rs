@@ -0,0 +1,215 @@
+// src/rust_data_collector/src/alerts.rs
+
+use chrono::{DateTime, Utc};
+use pyo3::prelude::*;
+use serde::Deserialize;
+
+use crate::merge::HourlyPoint;
+use crate::prices::local_hour;
+use crate::timestamp::{hour_start, to_unix_seconds};
+use crate::CollectorError;
+
+// --- Weather Alert Recommendations ---
+
+// Turns active weather alerts into something the optimizer can do about them. Rules
+// match keywords against the alert's event name and tags; the first matching rule decides
+// the action, so more specific keywords go first. National services word their alerts in
+// their own language (DWD sends "STARKE HITZE"), hence the German keywords.
+
+// One entry of One Call's `alerts` block. `start`/`end` are Unix seconds.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WeatherAlert {
+    #[serde(default)]
+    pub sender_name: String,
+    pub event: String,
+    pub start: i64,
+    pub end: i64,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertAction {
+    /// Cool the building in the cheapest hour before the dearest hour of the alert.
+    PreCool,
+    /// Heat ahead in the cheapest hour before the dearest hour of the alert.
+    PreHeat,
+    /// Fill the battery before the alert starts, in case the grid goes down.
+    ChargeBattery,
+}
+
+impl AlertAction {
+    pub fn parse(action: &str) -> Option<Self> {
+        match action {
+            "pre_cool" => Some(AlertAction::PreCool),
+            "pre_heat" => Some(AlertAction::PreHeat),
+            "charge_battery" => Some(AlertAction::ChargeBattery),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AlertAction::PreCool => "pre_cool",
+            AlertAction::PreHeat => "pre_heat",
+            AlertAction::ChargeBattery => "charge_battery",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AlertRule {
+    pub keyword: String, // Lower case
+    pub action: AlertAction,
+}
+
+const DEFAULT_RULES: &[(&str, AlertAction)] = &[
+    ("thunderstorm", AlertAction::ChargeBattery),
+    ("gewitter", AlertAction::ChargeBattery),
+    ("storm", AlertAction::ChargeBattery),
+    ("sturm", AlertAction::ChargeBattery),
+    ("orkan", AlertAction::ChargeBattery),
+    ("wind", AlertAction::ChargeBattery),
+    ("snow", AlertAction::ChargeBattery),
+    ("schnee", AlertAction::ChargeBattery),
+    ("black ice", AlertAction::ChargeBattery),
+    ("freezing rain", AlertAction::ChargeBattery),
+    ("glätte", AlertAction::ChargeBattery),
+    ("heat", AlertAction::PreCool),
+    ("hitze", AlertAction::PreCool),
+    ("high temperature", AlertAction::PreCool),
+    ("frost", AlertAction::PreHeat),
+    ("cold", AlertAction::PreHeat),
+    ("kälte", AlertAction::PreHeat),
+    ("low temperature", AlertAction::PreHeat),
+];
+
+pub fn default_rules() -> Vec<AlertRule> {
+    DEFAULT_RULES.iter()
+        .map(|&(keyword, action)| AlertRule { keyword: keyword.to_string(), action })
+        .collect()
+}
+
+/// Rules from `(keyword, action)` pairs, action being "pre_cool", "pre_heat" or
+/// "charge_battery".
+pub fn parse_rules(pairs: &[(String, String)]) -> Result<Vec<AlertRule>, CollectorError> {
+    pairs.iter()
+        .map(|(keyword, action)| {
+            let action = AlertAction::parse(action).ok_or_else(|| CollectorError::InvalidInput(format!(
+                "Unknown alert action {:?}, expected \"pre_cool\", \"pre_heat\" or \"charge_battery\"",
+                action
+            )))?;
+            Ok(AlertRule { keyword: keyword.to_lowercase(), action })
+        })
+        .collect()
+}
+
+/// Alerts from One Call's `alerts` block. Entries that don't parse are skipped.
+pub fn parse_alerts(alerts: &[serde_json::Value]) -> Vec<WeatherAlert> {
+    alerts.iter()
+        .filter_map(|value| match WeatherAlert::deserialize(value) {
+            Ok(alert) => Some(alert),
+            Err(e) => {
+                log!("WARNING (Rust): Skipping unreadable weather alert: {}", e);
+                None
+            }
+        })
+        .collect()
+}
+
+#[pyclass(get_all)]
+#[derive(Debug, Clone)]
+pub struct Recommendation {
+    pub action: &'static str, // "pre_cool", "pre_heat" or "charge_battery"
+    pub event: String,        // The alert that triggered it, as the sender named it
+    pub act_at: i64,          // Unix seconds, start of the hour to act in
+    pub alert_start: i64,     // Unix seconds
+    pub alert_end: i64,       // Unix seconds
+    pub reason: String,
+}
+
+#[pymethods]
+impl Recommendation {
+    fn __repr__(&self) -> String {
+        format!(
+            "Recommendation(action={:?}, event={:?}, act_at={}, alert_start={}, alert_end={}, reason={:?})",
+            self.action, self.event, self.act_at, self.alert_start, self.alert_end, self.reason
+        )
+    }
+}
+
+fn matching_action(alert: &WeatherAlert, rules: &[AlertRule]) -> Option<AlertAction> {
+    let texts: Vec<String> = std::iter::once(&alert.event)
+        .chain(alert.tags.iter())
+        .map(|text| text.to_lowercase())
+        .collect();
+    rules.iter()
+        .find(|rule| texts.iter().any(|text| text.contains(&rule.keyword)))
+        .map(|rule| rule.action)
+}
+
+// "14:00" in German local time.
+fn clock_time(ts: i64) -> String {
+    local_hour(ts).map_or_else(|| ts.to_string(), |(_, hour, _)| format!("{:02}:00", hour))
+}
+
+// Cheapest priced hour in `[from, until)`, earliest on a tie.
+fn cheapest_hour(priced: &[(i64, f64)], from: i64, until: i64) -> Option<(i64, f64)> {
+    priced.iter()
+        .copied()
+        .filter(|&(ts, _)| ts >= from && ts < until)
+        .reduce(|best, h| if h.1 < best.1 { h } else { best })
+}
+
+/// One recommendation per alert still active at `now` that a rule matches, in alert order.
+/// `merged` supplies the prices (hourly, Unix seconds); without a cheaper hour to move to,
+/// the recommendation is to act in the current hour.
+pub fn alert_recommendations(
+    alerts: &[WeatherAlert],
+    merged: &[HourlyPoint],
+    rules: &[AlertRule],
+    now: DateTime<Utc>,
+) -> Vec<Recommendation> {
+    let now_hour = hour_start(now.timestamp());
+    let priced: Vec<(i64, f64)> = merged.iter()
+        .filter_map(|p| Some((hour_start(p.timestamp), p.price?)))
+        .filter(|&(ts, _)| ts >= now_hour)
+        .collect();
+
+    alerts.iter()
+        .filter(|alert| to_unix_seconds(alert.end) > now.timestamp())
+        .filter_map(|alert| {
+            let action = matching_action(alert, rules)?;
+            let (start, end) = (to_unix_seconds(alert.start), to_unix_seconds(alert.end));
+            let (act_at, reason) = match action {
+                AlertAction::PreCool | AlertAction::PreHeat => {
+                    let verb = if action == AlertAction::PreCool { "Pre-cool" } else { "Pre-heat" };
+                    let dearest = priced.iter()
+                        .copied()
+                        .filter(|&(ts, _)| ts + 3600 > start && ts < end)
+                        .reduce(|best, h| if h.1 > best.1 { h } else { best });
+                    match dearest.and_then(|(peak, peak_price)| Some((peak, peak_price, cheapest_hour(&priced, now_hour, peak)?))) {
+                        Some((peak, peak_price, (cheap, cheap_price))) => (cheap, format!(
+                            "{} at {} ({:.1} EUR/MWh) ahead of the {:.1} EUR/MWh peak at {} during \"{}\"",
+                            verb, clock_time(cheap), cheap_price, peak_price, clock_time(peak), alert.event
+                        )),
+                        None => (now_hour, format!("{} now ahead of \"{}\"; no cheaper hour is known", verb, alert.event)),
+                    }
+                }
+                AlertAction::ChargeBattery => match cheapest_hour(&priced, now_hour, start.max(now_hour + 3600)) {
+                    Some((cheap, cheap_price)) => (cheap, format!(
+                        "Top up the battery at {} ({:.1} EUR/MWh) before \"{}\" in case of a grid outage",
+                        clock_time(cheap), cheap_price, alert.event
+                    )),
+                    None => (now_hour, format!(
+                        "Top up the battery before \"{}\" in case of a grid outage",
+                        alert.event
+                    )),
+                },
+            };
+            Some(Recommendation { action: action.as_str(), event: alert.event.clone(), act_at, alert_start: start, alert_end: end, reason })
+        })
+        .collect()
+}
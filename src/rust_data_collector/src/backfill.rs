@@ -0,0 +1,224 @@
+// src/rust_data_collector/src/backfill.rs
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use crate::smard::SmardSeriesKind;
+use crate::timestamp::to_unix_millis;
+use crate::{circuit, http, validate_time_range, CollectorError, SmardApiResponse, SmardDataPoint};
+
+// --- SMARD Historical Backfill ---
+
+// For history SMARD publishes the series in fixed-length segments: `index_<res>.json` lists
+// the segment start times, and each segment is its own file. A long backfill therefore
+// means many requests, which are fetched in parallel here.
+
+pub const DEFAULT_SEGMENT_CONCURRENCY: usize = 4;
+
+// Each segment gets this many tries on transient failures (5xx, 429, network), waiting
+// `SEGMENT_RETRY_DELAY` times the attempt number in between.
+const SEGMENT_ATTEMPTS: u32 = 3;
+const SEGMENT_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Deserialize)]
+struct SmardIndex {
+    timestamps: Vec<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SmardSegment {
+    series: Vec<SmardDataPoint>, // [[timestamp_ms, value], ...]
+}
+
+fn get_json<T: serde::de::DeserializeOwned>(url: &str) -> Result<T, CollectorError> {
+    circuit::SMARD.call(|| {
+        let response = http::client()?.get(url).send()?;
+        let status = response.status();
+        let content_type = http::content_type(&response);
+        let body = http::read_body(response)?;
+        if !status.is_success() {
+            log!("ERROR (Rust): SMARD returned non-success status {} for {}", status, url);
+            return Err(CollectorError::Status { status: status.as_u16(), body });
+        }
+        http::expect_json(content_type.as_deref(), &body)?;
+        serde_json::from_str(&body).map_err(|e| CollectorError::Parse { source: e, body })
+    })
+}
+
+// Retries happen on the worker that owns the segment, so one flaky segment doesn't hold up
+// the others. An open circuit already means "stop asking" and is not retried.
+fn get_segment(url: &str) -> Result<SmardSegment, CollectorError> {
+    let retryable = |e: &CollectorError| e.is_transient() && !matches!(e, CollectorError::CircuitOpen { .. });
+    let mut attempt = 1;
+    loop {
+        match get_json(url) {
+            Err(e) if attempt < SEGMENT_ATTEMPTS && retryable(&e) => {
+                let delay = SEGMENT_RETRY_DELAY * attempt;
+                // Don't sleep into a deadline that would stop the retry anyway.
+                if http::check_deadline()?.is_some_and(|remaining| remaining <= delay) {
+                    return Err(http::deadline_error_or(e));
+                }
+                log!("WARNING (Rust): SMARD segment {} failed (attempt {} of {}), retrying: {}", url, attempt, SEGMENT_ATTEMPTS, e);
+                thread::sleep(delay);
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Segment starts whose segment overlaps `[start_ms, end_ms]`. A segment runs until the
+/// next one starts; the last one is open-ended. Starts are returned as the index lists
+/// them, since they are part of the segment URL, even if it lists them in seconds.
+fn overlapping_segments(timestamps: &[i64], start_ms: i64, end_ms: i64) -> Vec<i64> {
+    let mut sorted = timestamps.to_vec();
+    sorted.sort_unstable();
+    sorted.iter().enumerate()
+        .filter(|&(i, &segment_start)| {
+            let segment_end = sorted.get(i + 1).map_or(i64::MAX, |&next| to_unix_millis(next));
+            to_unix_millis(segment_start) <= end_ms && segment_end > start_ms
+        })
+        .map(|(_, &segment_start)| segment_start)
+        .collect()
+}
+
+/// Every point of a SMARD series inside `[start_ms, end_ms]`, assembled in timestamp order
+/// from however many segments that spans, each timestamp once. Up to `segment_concurrency`
+/// segments are in flight at once; `progress(done, total)` is called on the calling thread
+/// after each one finishes. Transient failures are retried per segment; a segment that
+/// still fails aborts the backfill.
+#[allow(clippy::too_many_arguments)]
+pub fn get_smard_historical(
+    base_url: &str,
+    filter: &str,
+    kind: SmardSeriesKind,
+    region: &str,
+    resolution: &str,
+    start_ms: i64,
+    end_ms: i64,
+    segment_concurrency: usize,
+    progress: &mut dyn FnMut(usize, usize),
+) -> Result<SmardApiResponse, CollectorError> {
+    kind.validate_filter(filter)?;
+    validate_time_range(start_ms, end_ms)?;
+
+    let index_url = format!("{}/{}/{}/index_{}.json", base_url, filter, region, resolution);
+    let index: SmardIndex = get_json(&index_url)?;
+    let segments = overlapping_segments(&index.timestamps, start_ms, end_ms);
+    let total = segments.len();
+    log!("DEBUG (Rust): SMARD backfill needs {} segments.", total);
+
+    let next = AtomicUsize::new(0);
+    let deadline = http::current_deadline();
+    let mut points: BTreeMap<i64, Option<f64>> = BTreeMap::new();
+    thread::scope(|scope| -> Result<(), CollectorError> {
+        let (tx, rx) = mpsc::channel();
+        // A fixed set of workers pulling segment indices keeps at most
+        // `segment_concurrency` requests open.
+        for _ in 0..segment_concurrency.clamp(1, total.max(1)) {
+            let tx = tx.clone();
+            let (next, segments) = (&next, &segments);
+            scope.spawn(move || {
+                let _deadline = http::with_deadline(deadline);
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(&segment_start) = segments.get(i) else {
+                        break;
+                    };
+                    let url = format!(
+                        "{}/{}/{}/{}_{}_{}_{}.json",
+                        base_url, filter, region, filter, region, resolution, segment_start
+                    );
+                    if tx.send(get_segment(&url)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(tx);
+
+        for (done, result) in rx.iter().enumerate() {
+            let segment = match result {
+                Ok(segment) => segment,
+                Err(e) => {
+                    // Let the workers run dry instead of fetching the rest for nothing.
+                    next.store(total, Ordering::Relaxed);
+                    return Err(e);
+                }
+            };
+            for dp in segment.series {
+                let timestamp = to_unix_millis(dp.timestamp);
+                if timestamp >= start_ms && timestamp <= end_ms {
+                    points.insert(timestamp, dp.value);
+                }
+            }
+            progress(done + 1, total);
+        }
+        Ok(())
+    })?;
+
+    Ok(SmardApiResponse {
+        data: points.into_iter().map(|(timestamp, value)| SmardDataPoint { timestamp, value }).collect(),
+        source_unit: None,
+        config_hash: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loopback;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    const HOUR_MS: i64 = 3_600_000;
+    const START_MS: i64 = 1_704_067_200_000; // 2024-01-01 00:00 UTC
+
+    fn hour_ms(hour: i64) -> i64 {
+        START_MS + hour * HOUR_MS
+    }
+
+    // Three segments starting at hours 0, 10 and 20, each repeating the first hour of the
+    // next, as SMARD's do at the seams. The middle one answers 503 the first time.
+    #[test]
+    fn flaky_segment_is_retried_on_its_own() {
+        let requests: Arc<Mutex<HashMap<String, usize>>> = Arc::default();
+        let seen = Arc::clone(&requests);
+        let base_url = loopback::serve(move |path| {
+            let mut seen = seen.lock().unwrap();
+            let count = seen.entry(path.to_string()).or_default();
+            *count += 1;
+            let json = |body: String| ("200 OK", "Content-Type: application/json\r\n".to_string(), body.into_bytes());
+            if path.ends_with("index_hour.json") {
+                return json(format!("{{\"timestamps\":[{},{},{}]}}", hour_ms(20), hour_ms(0), hour_ms(10)));
+            }
+            let start: i64 = path.trim_end_matches(".json").rsplit('_').next().unwrap().parse().unwrap();
+            if start == hour_ms(10) && *count == 1 {
+                return ("503 Service Unavailable", String::new(), Vec::new());
+            }
+            let first = (start - START_MS) / HOUR_MS;
+            let series: Vec<String> = (first..=(first + 10).min(25)).map(|h| format!("[{},{}.5]", hour_ms(h), h)).collect();
+            json(format!("{{\"series\":[{}]}}", series.join(",")))
+        });
+
+        let mut progress = Vec::new();
+        let response = get_smard_historical(
+            &base_url, "4169", SmardSeriesKind::Price, "DE", "hour", hour_ms(0), hour_ms(25), 2,
+            &mut |done, total| progress.push((done, total)),
+        )
+        .unwrap();
+
+        let expected: Vec<(i64, Option<f64>)> = (0..=25).map(|h| (hour_ms(h), Some(h as f64 + 0.5))).collect();
+        let points: Vec<(i64, Option<f64>)> = response.data.iter().map(|dp| (dp.timestamp, dp.value)).collect();
+        assert_eq!(points, expected);
+        assert_eq!(progress, vec![(1, 3), (2, 3), (3, 3)]);
+
+        let requests = requests.lock().unwrap();
+        let segment = |hour: i64| requests.get(&format!("/4169/DE/4169_DE_hour_{}.json", hour_ms(hour))).copied();
+        assert_eq!((segment(0), segment(10), segment(20)), (Some(1), Some(2), Some(1)));
+    }
+}
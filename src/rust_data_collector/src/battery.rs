@@ -0,0 +1,242 @@
+// src/rust_data_collector/src/battery.rs
+
+use pyo3::prelude::*;
+
+use crate::{CollectorError, SmardDataPoint};
+
+// --- Battery Schedule ---
+
+// A greedy arbitrage plan that a real battery can follow. Energy is moved in pairs of
+// hours: charge in one, discharge in another (either order), always the pair with the
+// best margin after losses first, and as much as the power limit and the state of charge
+// in between allow. Each pair is energy-neutral inside the battery, so the plan ends at
+// the initial state of charge and never leaves `[min_soc_kwh, capacity_kwh]`.
+//
+// Losses are split evenly between charging and discharging: with a round-trip efficiency
+// of 0.81, 1 kWh from the grid stores 0.9 kWh, and 0.9 kWh stored delivers 0.81 kWh.
+// Cell wear is charged per kWh delivered; a pair whose spread doesn't cover losses and
+// wear (`cycle_break_even`) is never traded.
+
+#[derive(Debug, Clone, Copy)]
+pub struct BatterySpec {
+    pub capacity_kwh: f64,
+    pub max_power_kw: f64, // Grid-side limit, for charging and discharging alike
+    pub min_soc_kwh: f64,
+    pub initial_soc_kwh: f64,
+    pub round_trip_efficiency: f64, // 0 < x <= 1
+    pub cycle_cost_eur_per_kwh: f64, // Wear per kWh discharged, >= 0
+}
+
+impl BatterySpec {
+    fn validate(&self) -> Result<(), CollectorError> {
+        let invalid = |msg: String| Err(CollectorError::InvalidInput(msg));
+        if !(self.capacity_kwh.is_finite() && self.capacity_kwh > 0.0) {
+            return invalid(format!("capacity_kwh must be positive, got {}", self.capacity_kwh));
+        }
+        if !(self.max_power_kw.is_finite() && self.max_power_kw > 0.0) {
+            return invalid(format!("max_power_kw must be positive, got {}", self.max_power_kw));
+        }
+        if !(self.round_trip_efficiency > 0.0 && self.round_trip_efficiency <= 1.0) {
+            return invalid(format!("round_trip_efficiency must be in (0, 1], got {}", self.round_trip_efficiency));
+        }
+        if !(self.cycle_cost_eur_per_kwh.is_finite() && self.cycle_cost_eur_per_kwh >= 0.0) {
+            return invalid(format!("cycle_cost_eur_per_kwh must be zero or positive, got {}", self.cycle_cost_eur_per_kwh));
+        }
+        if !(0.0 <= self.min_soc_kwh && self.min_soc_kwh <= self.initial_soc_kwh && self.initial_soc_kwh <= self.capacity_kwh) {
+            return invalid(format!(
+                "need 0 <= min_soc_kwh ({}) <= initial_soc_kwh ({}) <= capacity_kwh ({})",
+                self.min_soc_kwh, self.initial_soc_kwh, self.capacity_kwh
+            ));
+        }
+        Ok(())
+    }
+}
+
+// What the battery does in one hour, seen from the grid.
+#[pyclass(get_all)]
+#[derive(Debug, Clone)]
+pub struct BatteryStep {
+    pub timestamp: i64,       // Milliseconds since epoch, start of the hour
+    pub action: &'static str, // "charge", "discharge" or "idle"
+    pub grid_kwh: f64,        // Drawn from the grid (positive) or fed in (negative)
+    pub price: f64,           // EUR/MWh
+}
+
+#[pymethods]
+impl BatteryStep {
+    fn __repr__(&self) -> String {
+        format!(
+            "BatteryStep(timestamp={}, action={:?}, grid_kwh={:.3}, price={})",
+            self.timestamp, self.action, self.grid_kwh, self.price
+        )
+    }
+}
+
+#[pyclass(get_all)]
+#[derive(Debug, Clone)]
+pub struct BatterySchedule {
+    pub steps: Vec<BatteryStep>,
+    // State of charge in kWh at the start of each step, plus one after the last.
+    pub soc_kwh: Vec<f64>,
+    pub profit: f64,    // EUR: export revenue minus import cost
+    pub wear_cost: f64, // EUR: cycle cost of the energy discharged, not in `profit`
+}
+
+#[pymethods]
+impl BatterySchedule {
+    fn __repr__(&self) -> String {
+        format!("BatterySchedule(steps={}, profit={:.4}, wear_cost={:.4})", self.steps.len(), self.profit, self.wear_cost)
+    }
+}
+
+/// Smallest spread (EUR/kWh, selling minus buying price) at which storing energy bought at
+/// `charge_price_eur_per_kwh` and selling it later pays off: the wear of the delivered kWh
+/// plus the price of what is lost on the way. Losses cost more the dearer the charge
+/// price, and nothing at a price of zero.
+pub fn cycle_break_even(round_trip_efficiency: f64, cycle_cost_eur_per_kwh: f64, charge_price_eur_per_kwh: f64) -> f64 {
+    cycle_cost_eur_per_kwh + charge_price_eur_per_kwh * (1.0 / round_trip_efficiency - 1.0)
+}
+
+// Amounts below this are rounding noise and end the search.
+const MIN_TRANSFER_KWH: f64 = 1e-9;
+
+/// Schedule over the priced points, one step per point in timestamp order; unpriced points
+/// are left out.
+pub fn optimize_battery_schedule(points: &[SmardDataPoint], spec: &BatterySpec) -> Result<BatterySchedule, CollectorError> {
+    spec.validate()?;
+    let mut hours: Vec<(i64, f64)> = points.iter().filter_map(|dp| Some((dp.timestamp, dp.value?))).collect();
+    hours.sort_by_key(|(ts, _)| *ts);
+    hours.dedup_by_key(|(ts, _)| *ts);
+
+    let eta = spec.round_trip_efficiency.sqrt(); // Each way
+    let n = hours.len();
+    let mut charged = vec![0.0; n]; // Grid kWh drawn per hour
+    let mut delivered = vec![0.0; n]; // Grid kWh fed in per hour
+    let mut soc = vec![spec.initial_soc_kwh; n + 1];
+
+    loop {
+        // (EUR/MWh gained per unit stored, charge hour, discharge hour, stored kWh)
+        let mut best: Option<(f64, usize, usize, f64)> = None;
+        for c in 0..n {
+            if delivered[c] > 0.0 {
+                continue;
+            }
+            for d in 0..n {
+                if d == c || charged[d] > 0.0 {
+                    continue;
+                }
+                let (buy, sell) = (hours[c].1, hours[d].1);
+                if (sell - buy) / 1000.0 <= cycle_break_even(spec.round_trip_efficiency, spec.cycle_cost_eur_per_kwh, buy / 1000.0) {
+                    continue;
+                }
+                let margin = eta * sell - buy / eta - eta * spec.cycle_cost_eur_per_kwh * 1000.0;
+                if best.is_some_and(|(m, ..)| margin <= m) {
+                    continue;
+                }
+                // SoC changes between the two hours: up if charging first, down otherwise.
+                let soc_room = if c < d {
+                    spec.capacity_kwh - soc[c + 1..=d].iter().copied().fold(f64::NEG_INFINITY, f64::max)
+                } else {
+                    soc[d + 1..=c].iter().copied().fold(f64::INFINITY, f64::min) - spec.min_soc_kwh
+                };
+                let stored = ((spec.max_power_kw - charged[c]) * eta)
+                    .min((spec.max_power_kw - delivered[d]) / eta)
+                    .min(soc_room);
+                if stored > MIN_TRANSFER_KWH {
+                    best = Some((margin, c, d, stored));
+                }
+            }
+        }
+        let Some((_, c, d, stored)) = best else {
+            break;
+        };
+        charged[c] += stored / eta;
+        delivered[d] += stored * eta;
+        if c < d {
+            soc[c + 1..=d].iter_mut().for_each(|s| *s += stored);
+        } else {
+            soc[d + 1..=c].iter_mut().for_each(|s| *s -= stored);
+        }
+    }
+
+    let mut profit = 0.0;
+    let steps = hours.iter().enumerate()
+        .map(|(i, &(timestamp, price))| {
+            let grid_kwh = charged[i] - delivered[i];
+            profit -= grid_kwh * price / 1000.0;
+            let action = if charged[i] > 0.0 {
+                "charge"
+            } else if delivered[i] > 0.0 {
+                "discharge"
+            } else {
+                "idle"
+            };
+            BatteryStep { timestamp, action, grid_kwh, price }
+        })
+        .collect();
+    let wear_cost = delivered.iter().sum::<f64>() * spec.cycle_cost_eur_per_kwh;
+    Ok(BatterySchedule { steps, soc_kwh: soc, profit, wear_cost })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR_MS: i64 = 3_600_000;
+
+    // 10 kWh, 5 kW, starting empty; 81% round trip is 90% each way.
+    const SPEC: BatterySpec = BatterySpec {
+        capacity_kwh: 10.0,
+        max_power_kw: 5.0,
+        min_soc_kwh: 0.0,
+        initial_soc_kwh: 0.0,
+        round_trip_efficiency: 0.81,
+        cycle_cost_eur_per_kwh: 0.01,
+    };
+
+    fn two_hours(first: f64, second: f64) -> Vec<SmardDataPoint> {
+        vec![
+            SmardDataPoint { timestamp: 0, value: Some(first) },
+            SmardDataPoint { timestamp: HOUR_MS, value: Some(second) },
+        ]
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn two_hour_arbitrage_matches_hand_calculation() {
+        // Charge at 50 EUR/MWh, sell at 250. The 5 kW limit on charging binds: 5 kWh
+        // drawn stores 4.5 kWh, which delivers 4.05 kWh.
+        let schedule = optimize_battery_schedule(&two_hours(50.0, 250.0), &SPEC).unwrap();
+        let actions: Vec<_> = schedule.steps.iter().map(|s| s.action).collect();
+        assert_eq!(actions, vec!["charge", "discharge"]);
+        assert_close(schedule.steps[0].grid_kwh, 5.0);
+        assert_close(schedule.steps[1].grid_kwh, -4.05);
+        assert_eq!(schedule.soc_kwh.len(), 3);
+        assert_close(schedule.soc_kwh[1], 4.5);
+        assert_close(schedule.soc_kwh[2], 0.0);
+        // 4.05 kWh * 0.25 EUR - 5 kWh * 0.05 EUR
+        assert_close(schedule.profit, 0.7625);
+        assert_close(schedule.wear_cost, 0.0405);
+    }
+
+    #[test]
+    fn spread_below_break_even_stays_idle() {
+        // Break-even at 0.05 EUR/kWh: 0.01 wear + 0.05 * (1/0.81 - 1) ≈ 0.0217 EUR/kWh.
+        assert_close(cycle_break_even(0.81, 0.01, 0.05), 0.01 + 0.05 * (1.0 / 0.81 - 1.0));
+        assert_close(cycle_break_even(1.0, 0.0, 0.05), 0.0);
+        let schedule = optimize_battery_schedule(&two_hours(50.0, 70.0), &SPEC).unwrap();
+        assert!(schedule.steps.iter().all(|s| s.action == "idle"));
+        assert_eq!((schedule.profit, schedule.wear_cost), (0.0, 0.0));
+    }
+
+    #[test]
+    fn invalid_spec_is_rejected() {
+        let spec = BatterySpec { initial_soc_kwh: 12.0, ..SPEC };
+        assert!(matches!(optimize_battery_schedule(&two_hours(50.0, 250.0), &spec), Err(CollectorError::InvalidInput(_))));
+        let spec = BatterySpec { round_trip_efficiency: 1.2, ..SPEC };
+        assert!(optimize_battery_schedule(&[], &spec).is_err());
+    }
+}
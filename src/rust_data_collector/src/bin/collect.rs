@@ -0,0 +1,137 @@
+// src/rust_data_collector/src/bin/collect.rs
+//
+// Day-ahead price collection from the shell, for cron jobs that don't want a Python
+// interpreter. Writes `smard_prices.json` into the data directory, in the same format as
+// `fetch_and_save_data`. With `--since-last`, only prices newer than the last run's are
+// requested and merged into the existing file; the newest stored timestamp is kept in the
+// same state file `fetch_and_save_data(incremental=True)` uses, so both can take turns.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use rust_data_collector::{clock, lookback_window_ms, pipeline, prices, smard_price_series, storage, CollectorError};
+
+const USAGE: &str = "\
+Usage: collect [--data-dir DIR] [--region REGION] [--since-last] [--state-file PATH] [--compact]
+
+  --data-dir DIR     Where smard_prices.json is written (default: data)
+  --region REGION    SMARD market region (default: DE)
+  --since-last       Only fetch prices newer than the last run's, merged into the saved file;
+                     the first run, with no state yet, fetches the last 48 hours
+  --state-file PATH  Where the last stored timestamp is kept (default: DIR/.fetch_state.json)
+  --compact          Write the JSON on one line";
+
+#[derive(Debug, PartialEq)]
+struct Options {
+    data_dir: String,
+    region: String,
+    since_last: bool,
+    state_file: Option<PathBuf>,
+    pretty: bool,
+}
+
+// `None` when help was asked for.
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        data_dir: "data".to_string(),
+        region: "DE".to_string(),
+        since_last: false,
+        state_file: None,
+        pretty: true,
+    };
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = |flag: &str| args.next().ok_or_else(|| format!("{} needs a value", flag));
+        match arg.as_str() {
+            "--data-dir" => options.data_dir = value("--data-dir")?,
+            "--region" => options.region = value("--region")?,
+            "--since-last" => options.since_last = true,
+            "--state-file" => options.state_file = Some(PathBuf::from(value("--state-file")?)),
+            "--compact" => options.pretty = false,
+            "-h" | "--help" => return Ok(None),
+            other => return Err(format!("unknown argument {:?}", other)),
+        }
+    }
+    Ok(Some(options))
+}
+
+fn collect(options: &Options) -> Result<(), CollectorError> {
+    let now = clock::now();
+    let series = smard_price_series(&options.region);
+    let state_file = options.state_file.clone().unwrap_or_else(|| storage::default_fetch_state_path(&options.data_dir));
+    let last_stored_ms = if options.since_last { storage::last_stored_timestamp(&state_file, &series) } else { None };
+    let (start_ms, end_ms) = lookback_window_ms(now, last_stored_ms);
+    let out_dir = storage::output_dir(&options.data_dir, false, now)?;
+    let smard_path = out_dir.join("smard_prices.json");
+
+    let mut collector = pipeline::Pipeline::new()
+        .source(pipeline::SmardSource { region: options.region.clone(), start_ms, end_ms })
+        .transform(pipeline::RoundPrices(prices::DEFAULT_PRICE_DECIMALS));
+    if options.since_last {
+        collector = collector.transform(pipeline::MergeStoredPrices(smard_path.clone()));
+    }
+    let data = collector
+        .sink(pipeline::PricesJsonSink { target: smard_path.into(), pretty: options.pretty })
+        .run()
+        .map_err(|e| e.error)?;
+
+    // Only once the prices are saved, so a failed run is simply repeated next time.
+    let newest_ms = data.prices.iter().flat_map(|p| p.data.iter()).filter(|dp| dp.value.is_some()).map(|dp| dp.timestamp).max();
+    if let Some(newest_ms) = newest_ms {
+        storage::save_last_stored_timestamp(&state_file, &series, newest_ms)?;
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("collect: {}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    match collect(&options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("collect: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn defaults_fetch_the_lookback_window() {
+        let options = parse_args(args("")).unwrap().unwrap();
+        assert_eq!((options.data_dir.as_str(), options.region.as_str()), ("data", "DE"));
+        assert!(!options.since_last && options.pretty);
+        assert_eq!(options.state_file, None);
+    }
+
+    #[test]
+    fn since_last_with_a_state_file() {
+        let options = parse_args(args("--since-last --state-file /var/lib/collector/state.json --data-dir /srv/energy --compact")).unwrap().unwrap();
+        assert!(options.since_last && !options.pretty);
+        assert_eq!(options.state_file, Some(PathBuf::from("/var/lib/collector/state.json")));
+        assert_eq!(options.data_dir, "/srv/energy");
+    }
+
+    #[test]
+    fn bad_arguments_are_reported() {
+        assert_eq!(parse_args(args("--state-file")), Err("--state-file needs a value".to_string()));
+        assert!(parse_args(args("--since")).is_err());
+        assert_eq!(parse_args(args("--help")), Ok(None));
+    }
+}
@@ -0,0 +1,155 @@
+// src/rust_data_collector/src/briefing.rs
+
+use chrono::NaiveDate;
+
+use crate::merge::HourlyPoint;
+use crate::prices::local_hour;
+
+// --- Daily Briefing ---
+
+// A short plain-language summary of one German local day for notifications. Every
+// sentence comes from a fixed template filled with values read off the series, so the
+// same input always yields the same text.
+
+// Chance of precipitation from which an hour counts as rainy.
+const RAIN_POP_THRESHOLD: f64 = 0.5;
+
+const PARTS_OF_DAY: [(&str, u32, u32); 4] = [
+    ("night", 0, 6),
+    ("morning", 6, 12),
+    ("afternoon", 12, 18),
+    ("evening", 18, 24),
+];
+
+fn ct_per_kwh(eur_per_mwh: f64) -> f64 {
+    eur_per_mwh / 10.0
+}
+
+fn part_of_day(hour: u32) -> &'static str {
+    PARTS_OF_DAY.iter()
+        .find(|(_, from, to)| (*from..*to).contains(&hour))
+        .map(|(name, _, _)| *name)
+        .unwrap_or("night")
+}
+
+// "a", "a and b", "a, b and c"
+fn join_words(words: &[&str]) -> String {
+    match words {
+        [] => String::new(),
+        [only] => only.to_string(),
+        [rest @ .., last] => format!("{} and {}", rest.join(", "), last),
+    }
+}
+
+// First (hour, value) with the smallest value, then the first with the largest.
+fn extremes(hours: &[(u32, f64)]) -> Option<((u32, f64), (u32, f64))> {
+    let cheapest = hours.iter().copied().reduce(|best, h| if h.1 < best.1 { h } else { best })?;
+    let dearest = hours.iter().copied().reduce(|best, h| if h.1 > best.1 { h } else { best })?;
+    Some((cheapest, dearest))
+}
+
+/// Briefing for the Berlin local `date`: the cheapest and dearest priced hours, the hour of
+/// lowest carbon intensity and when rain is expected. `carbon` is `(timestamp, gCO2/kWh)` in
+/// either seconds or milliseconds and may be empty; weather is taken from `merged`. `today`
+/// only decides whether the text says "today" or names the date.
+pub fn summarize_day(merged: &[HourlyPoint], carbon: &[(i64, Option<f64>)], date: NaiveDate, today: NaiveDate) -> String {
+    let on_day = |ts: i64| local_hour(ts).filter(|(d, _, _)| *d == date).map(|(_, hour, _)| hour);
+    let day = if date == today { "today".to_string() } else { format!("on {}", date) };
+    let mut sentences = Vec::new();
+
+    let priced: Vec<(u32, f64)> = merged.iter()
+        .filter_map(|p| Some((on_day(p.timestamp)?, p.price?)))
+        .collect();
+    match extremes(&priced) {
+        Some(((cheap_hour, cheap), (dear_hour, dear))) => sentences.push(format!(
+            "Cheapest hour {} is {:02}:00 at {:.1} ct/kWh; most expensive is {:02}:00 at {:.1} ct/kWh.",
+            day, cheap_hour, ct_per_kwh(cheap), dear_hour, ct_per_kwh(dear)
+        )),
+        None => sentences.push(format!("No prices are available {}.", day)),
+    }
+
+    let intensities: Vec<(u32, f64)> = carbon.iter()
+        .filter_map(|&(ts, value)| Some((on_day(ts)?, value?)))
+        .collect();
+    if let Some(((green_hour, green), _)) = extremes(&intensities) {
+        sentences.push(format!(
+            "The grid is greenest around {:02}:00 at {:.0} gCO2/kWh.",
+            green_hour, green
+        ));
+    }
+
+    let weather_hours: Vec<(u32, &HourlyPoint)> = merged.iter()
+        .filter_map(|p| Some((on_day(p.timestamp)?, p)))
+        .collect();
+    if !weather_hours.is_empty() {
+        let rainy = |p: &HourlyPoint| p.pop >= RAIN_POP_THRESHOLD || p.rain_1h.is_some_and(|mm| mm > 0.0);
+        let rainy_parts: Vec<&str> = PARTS_OF_DAY.iter()
+            .map(|(name, _, _)| *name)
+            .filter(|part| weather_hours.iter().any(|(hour, p)| part_of_day(*hour) == *part && rainy(p)))
+            .collect();
+        if rainy_parts.is_empty() {
+            sentences.push("No rain is expected.".to_string());
+        } else {
+            let daylight = rainy_parts.iter().any(|part| matches!(*part, "morning" | "afternoon"));
+            sentences.push(format!(
+                "Expect rain in the {}{}.",
+                join_words(&rainy_parts),
+                if daylight { ", reducing solar output" } else { "" }
+            ));
+        }
+    }
+
+    sentences.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-06-01 00:00 in Berlin (UTC+2).
+    const LOCAL_MIDNIGHT: i64 = 1_717_192_800;
+
+    fn june_first() -> Vec<HourlyPoint> {
+        (0..24)
+            .map(|hour| HourlyPoint {
+                timestamp: LOCAL_MIDNIGHT + hour * 3600,
+                temp: 18.0,
+                clouds: 20,
+                pop: if hour == 15 { 0.6 } else { 0.0 },
+                rain_1h: None,
+                snow_1h: None,
+                pressure: None,
+                dew_point: None,
+                visibility: None,
+                description: "few clouds".to_string(),
+                ghi: None,
+                price: Some(match hour {
+                    13 => 20.0,
+                    19 => 150.0,
+                    _ => 80.0,
+                }),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn briefing_is_filled_from_the_series() {
+        let date = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+        let carbon: Vec<(i64, Option<f64>)> = (0..24)
+            .map(|hour| ((LOCAL_MIDNIGHT + hour * 3600) * 1000, Some(if hour == 12 { 180.0 } else { 320.0 })))
+            .collect();
+        assert_eq!(
+            summarize_day(&june_first(), &carbon, date, date),
+            "Cheapest hour today is 13:00 at 2.0 ct/kWh; most expensive is 19:00 at 15.0 ct/kWh. \
+             The grid is greenest around 12:00 at 180 gCO2/kWh. \
+             Expect rain in the afternoon, reducing solar output."
+        );
+    }
+
+    #[test]
+    fn briefing_for_another_day_without_data() {
+        let date = NaiveDate::from_ymd_opt(2024, 6, 2).unwrap();
+        let today = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+        assert_eq!(summarize_day(&june_first(), &[], date, today), "No prices are available on 2024-06-02.");
+    }
+}
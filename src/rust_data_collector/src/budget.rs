@@ -0,0 +1,96 @@
+// src/rust_data_collector/src/budget.rs
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::{clock, CollectorError};
+
+// --- OpenWeatherMap Daily Call Budget ---
+
+// One Call 3.0 includes 1000 free calls per day and bills every call beyond that, so a
+// buggy loop can get expensive. Calls are counted in a small state file (so the count
+// survives restarts and is shared by processes using the same file) and the count resets
+// at UTC midnight, like OpenWeather's own.
+pub const DEFAULT_DAILY_CALL_BUDGET: u32 = 1000;
+
+#[derive(Debug, Clone)]
+struct BudgetConfig {
+    daily_call_budget: u32,
+    state_file: Option<PathBuf>,
+    // Refuse calls over budget; when false, only warn.
+    enforce: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BudgetState {
+    date: Option<NaiveDate>,
+    calls: u32,
+}
+
+static CONFIG: Mutex<BudgetConfig> = Mutex::new(BudgetConfig {
+    daily_call_budget: DEFAULT_DAILY_CALL_BUDGET,
+    state_file: None,
+    enforce: true,
+});
+
+pub fn configure(daily_call_budget: u32, state_file: Option<PathBuf>, enforce: bool) {
+    *CONFIG.lock().unwrap() = BudgetConfig { daily_call_budget, state_file, enforce };
+}
+
+fn state_path(config: &BudgetConfig) -> PathBuf {
+    config.state_file.clone()
+        .unwrap_or_else(|| std::env::temp_dir().join("rust_data_collector_openweather_calls.json"))
+}
+
+// A missing or unreadable state file counts as a fresh day rather than blocking fetches.
+fn load_today(path: &PathBuf, today: NaiveDate) -> BudgetState {
+    let state: BudgetState = fs::read_to_string(path).ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+    if state.date == Some(today) {
+        state
+    } else {
+        BudgetState { date: Some(today), calls: 0 }
+    }
+}
+
+fn save(path: &PathBuf, state: &BudgetState) -> Result<(), CollectorError> {
+    // Write-then-rename so a crash mid-write can't leave a truncated state file.
+    let tmp = path.with_extension("json.tmp");
+    let json = serde_json::to_string(state)
+        .map_err(|e| CollectorError::Parse { source: e, body: String::new() })?;
+    fs::write(&tmp, json)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+/// Counts one OpenWeatherMap call against today's budget, before it is sent. Over budget
+/// this returns `BudgetExceeded` (or just warns when enforcement is off).
+pub fn record_openweather_call() -> Result<u32, CollectorError> {
+    let config = CONFIG.lock().unwrap();
+    let path = state_path(&config);
+    let mut state = load_today(&path, clock::now().date_naive());
+
+    if state.calls >= config.daily_call_budget {
+        if config.enforce {
+            return Err(CollectorError::BudgetExceeded { used: state.calls, budget: config.daily_call_budget });
+        }
+        log!(
+            "WARNING (Rust): OpenWeatherMap daily call budget exceeded ({} of {} used); calls may be billed.",
+            state.calls, config.daily_call_budget
+        );
+    }
+    state.calls += 1;
+    if let Err(e) = save(&path, &state) {
+        log!("WARNING (Rust): Could not persist OpenWeatherMap call count to {:?}: {}", path, e);
+    }
+    Ok(state.calls)
+}
+
+pub fn calls_used_today() -> u32 {
+    let config = CONFIG.lock().unwrap();
+    load_today(&state_path(&config), clock::now().date_naive()).calls
+}
@@ -0,0 +1,169 @@
+// src/rust_data_collector/src/cache.rs
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::SmardDataPoint;
+
+// --- In-Memory TTL Cache ---
+
+pub const DEFAULT_TTL: Duration = Duration::from_secs(600);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheStats {
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+// Keyed by the request (minus credentials). Expired entries are treated as misses and
+// replaced on the next insert. All access goes through the one lock, so clearing is safe
+// while fetches are running.
+#[derive(Debug)]
+pub struct TtlCache<V> {
+    entries: Mutex<HashMap<String, (Instant, V)>>,
+    ttl: Mutex<Duration>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl<V: Clone> TtlCache<V> {
+    pub fn new(ttl: Duration) -> Self {
+        TtlCache {
+            entries: Mutex::new(HashMap::new()),
+            ttl: Mutex::new(ttl),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<V> {
+        self.get_with(key, |value| Some(value.clone()))
+    }
+
+    /// What `usable` makes of the live entry for `key`. Counts as a hit only if it returns
+    /// `Some`, so an entry that exists but can't answer the request is a miss.
+    pub fn get_with<T>(&self, key: &str, usable: impl FnOnce(&V) -> Option<T>) -> Option<T> {
+        let ttl = *self.ttl.lock().unwrap();
+        let entries = self.entries.lock().unwrap();
+        let found = entries.get(key)
+            .filter(|(stored_at, _)| stored_at.elapsed() < ttl)
+            .and_then(|(_, value)| usable(value));
+        let counter = if found.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        found
+    }
+
+    pub fn insert(&self, key: String, value: V) {
+        self.entries.lock().unwrap().insert(key, (Instant::now(), value));
+    }
+
+    /// Restarts the TTL of an existing entry, so it's served for another full TTL. `false`
+    /// when there's no entry for `key`.
+    pub fn renew(&self, key: &str) -> bool {
+        match self.entries.lock().unwrap().get_mut(key) {
+            Some((stored_at, _)) => {
+                *stored_at = Instant::now();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn ttl(&self) -> Duration {
+        *self.ttl.lock().unwrap()
+    }
+
+    pub fn set_ttl(&self, ttl: Duration) {
+        *self.ttl.lock().unwrap() = ttl;
+    }
+
+    /// Drops all entries and resets the hit/miss counters.
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            entries: self.entries.lock().unwrap().len(),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+// Raw One Call bodies. OpenWeather is the quota-metered provider, so that's what is cached;
+// storing the body rather than the parsed struct keeps the cache independent of the types.
+pub fn weather() -> &'static TtlCache<String> {
+    static WEATHER: OnceLock<TtlCache<String>> = OnceLock::new();
+    WEATHER.get_or_init(|| TtlCache::new(DEFAULT_TTL))
+}
+
+// SMARD points already filtered to the window they were fetched for. A later request can
+// reuse them only if its window lies inside that one; `warmup` fetches a wide window so
+// the usual windows all fit.
+#[derive(Debug, Clone)]
+pub struct CachedPrices {
+    pub start_ms: i64,
+    pub end_ms: i64,
+    pub data: Vec<SmardDataPoint>,
+}
+
+impl CachedPrices {
+    /// The cached points inside `[start_ms, end_ms]`, if the cached window covers it.
+    pub fn window(&self, start_ms: i64, end_ms: i64) -> Option<Vec<SmardDataPoint>> {
+        if start_ms < self.start_ms || end_ms > self.end_ms {
+            return None;
+        }
+        Some(self.data.iter()
+            .filter(|dp| dp.timestamp >= start_ms && dp.timestamp <= end_ms)
+            .cloned()
+            .collect())
+    }
+}
+
+// Parsed SMARD series keyed by request URL.
+pub fn smard() -> &'static TtlCache<CachedPrices> {
+    static SMARD: OnceLock<TtlCache<CachedPrices>> = OnceLock::new();
+    SMARD.get_or_init(|| TtlCache::new(DEFAULT_TTL))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cached_window() -> TtlCache<CachedPrices> {
+        let cache = TtlCache::new(DEFAULT_TTL);
+        let data = (0..4).map(|h| SmardDataPoint { timestamp: h * 3_600_000, value: Some(80.0) }).collect();
+        cache.insert("smard".to_string(), CachedPrices { start_ms: 0, end_ms: 3 * 3_600_000, data });
+        cache
+    }
+
+    #[test]
+    fn window_inside_the_cached_one_is_a_hit() {
+        let cache = cached_window();
+        let data = cache.get_with("smard", |c| c.window(3_600_000, 2 * 3_600_000)).unwrap();
+        assert_eq!(data.len(), 2);
+        assert_eq!(cache.stats(), CacheStats { entries: 1, hits: 1, misses: 0 });
+    }
+
+    #[test]
+    fn window_outside_the_cached_one_is_a_miss() {
+        let cache = cached_window();
+        assert!(cache.get_with("smard", |c| c.window(0, 10 * 3_600_000)).is_none());
+        assert!(cache.get_with("other", |c| c.window(0, 3_600_000)).is_none());
+        assert_eq!(cache.stats(), CacheStats { entries: 1, hits: 0, misses: 2 });
+    }
+
+    #[test]
+    fn expired_entries_are_misses() {
+        let cache = cached_window();
+        cache.set_ttl(Duration::ZERO);
+        assert!(cache.get("smard").is_none());
+        assert_eq!(cache.stats().misses, 1);
+    }
+}
@@ -0,0 +1,224 @@
+// src/rust_data_collector/src/carbon.rs
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::thread;
+
+use crate::smard::SmardSeriesKind;
+use crate::quality::Warning;
+use crate::{get_smard_series, http, validate_time_range, CollectorError, SmardApiResponse, SMARD_BASE_URL};
+
+// --- Grid Carbon Intensity ---
+
+// SMARD generation filters by the source name used in emission-factor tables.
+pub const GENERATION_SOURCES: [(&str, &str); 12] = [
+    ("1223", "lignite"),
+    ("1224", "nuclear"),
+    ("1225", "wind_offshore"),
+    ("1226", "hydro"),
+    ("1227", "other_conventional"),
+    ("1228", "other_renewable"),
+    ("4066", "biomass"),
+    ("4067", "wind_onshore"),
+    ("4068", "solar"),
+    ("4069", "hard_coal"),
+    ("4070", "pumped_storage"),
+    ("4071", "natural_gas"),
+];
+
+// Lifecycle gCO2eq/kWh, mostly IPCC AR5 medians. Lignite isn't split out there, so it
+// uses a typical German estimate above hard coal; pumped storage only shifts other
+// generation and counts as zero.
+const DEFAULT_EMISSION_FACTORS: [(&str, f64); 12] = [
+    ("lignite", 1150.0),
+    ("nuclear", 12.0),
+    ("wind_offshore", 12.0),
+    ("hydro", 24.0),
+    ("other_conventional", 700.0),
+    ("other_renewable", 38.0),
+    ("biomass", 230.0),
+    ("wind_onshore", 11.0),
+    ("solar", 41.0),
+    ("hard_coal", 820.0),
+    ("pumped_storage", 0.0),
+    ("natural_gas", 490.0),
+];
+
+// Replaces the defaults once a table is loaded; methodologies differ too much to mix them.
+static LOADED_FACTORS: Mutex<Option<HashMap<String, f64>>> = Mutex::new(None);
+
+pub fn default_emission_factors() -> HashMap<String, f64> {
+    DEFAULT_EMISSION_FACTORS.iter().map(|&(name, factor)| (name.to_string(), factor)).collect()
+}
+
+/// The table `fetch_carbon_intensity` uses: the last loaded one, else the defaults.
+pub fn emission_factors() -> HashMap<String, f64> {
+    LOADED_FACTORS.lock().unwrap().clone().unwrap_or_else(default_emission_factors)
+}
+
+/// Reads a flat JSON object or TOML table of `source = gCO2/kWh` and makes it the active
+/// table. Factors must be finite and non-negative; unknown source names are kept but
+/// reported, since they're usually typos.
+pub fn load_emission_factors(path: &str) -> Result<HashMap<String, f64>, CollectorError> {
+    let content = fs::read_to_string(path)?;
+    let is_toml = Path::new(path).extension().is_some_and(|ext| ext == "toml");
+    let factors: HashMap<String, f64> = if is_toml {
+        toml::from_str(&content).map_err(|e| {
+            CollectorError::InvalidInput(format!("Emission factor file {} is not a flat TOML table of numbers: {}", path, e))
+        })?
+    } else {
+        serde_json::from_str(&content).map_err(|e| {
+            CollectorError::InvalidInput(format!("Emission factor file {} is not a flat JSON object of numbers: {}", path, e))
+        })?
+    };
+
+    if let Some((name, factor)) = factors.iter().find(|(_, f)| !f.is_finite() || **f < 0.0) {
+        return Err(CollectorError::InvalidInput(format!(
+            "Emission factor for {} must be a non-negative number, got {}",
+            name, factor
+        )));
+    }
+    for name in factors.keys() {
+        if !GENERATION_SOURCES.iter().any(|(_, source)| source == name) {
+            log!("WARNING (Rust): Emission factor for unknown source {:?} will not be used.", name);
+        }
+    }
+    *LOADED_FACTORS.lock().unwrap() = Some(factors.clone());
+    Ok(factors)
+}
+
+// Intensity points and the warnings about what was left out of them.
+pub type CarbonIntensity = (Vec<(i64, Option<f64>)>, Vec<Warning>);
+
+/// Generation-weighted gCO2/kWh per timestamp, `(timestamp_ms, intensity)`, and what was
+/// left out of it. All sources are fetched in parallel; a source that fails or has no factor
+/// is skipped with a `Warning` ("carbon_source_skipped", "missing_emission_factor"), since
+/// the intensity then understates or overstates the real mix. A timestamp with no counted
+/// generation gets `None`. Only when no source at all can be fetched is it an error.
+pub fn fetch_carbon_intensity(
+    region: &str,
+    resolution: &str,
+    start_ms: i64,
+    end_ms: i64,
+) -> Result<CarbonIntensity, CollectorError> {
+    validate_time_range(start_ms, end_ms)?;
+
+    let deadline = http::current_deadline();
+    let fetched: Vec<(&str, Result<_, CollectorError>)> = thread::scope(|scope| {
+        let handles: Vec<_> = GENERATION_SOURCES.iter()
+            .map(|&(filter, name)| {
+                (name, scope.spawn(move || {
+                    let _deadline = http::with_deadline(deadline);
+                    get_smard_series(SMARD_BASE_URL, filter, SmardSeriesKind::Generation, region, resolution, start_ms, end_ms)
+                }))
+            })
+            .collect();
+        handles.into_iter()
+            .map(|(name, h)| (name, h.join().expect("SMARD fetch thread panicked")))
+            .collect()
+    });
+    weigh_generation(fetched, &emission_factors())
+}
+
+// The weighting itself, separate from the fetching so it can be tested offline.
+fn weigh_generation(
+    fetched: Vec<(&str, Result<SmardApiResponse, CollectorError>)>,
+    factors: &HashMap<String, f64>,
+) -> Result<CarbonIntensity, CollectorError> {
+    let mut weighted: BTreeMap<i64, (f64, f64)> = BTreeMap::new(); // ts -> (gCO2-weighted sum, generation)
+    let mut warnings = Vec::new();
+    let mut missing_factors = Vec::new();
+    let mut last_error = None;
+    let mut fetched_any = false;
+    for (name, result) in fetched {
+        let response = match result {
+            Ok(response) => response,
+            Err(e) => {
+                // Pumped storage counts as zero either way, so missing it changes nothing.
+                if factors.get(name).is_none_or(|&factor| factor > 0.0) {
+                    warnings.push(Warning::new(
+                        "carbon_source_skipped",
+                        format!("{} generation is left out of the carbon intensity: {}", name, e),
+                    ));
+                }
+                last_error = Some(e);
+                continue;
+            }
+        };
+        fetched_any = true;
+        let generated = response.data.iter().any(|dp| dp.value.is_some_and(|v| v > 0.0));
+        let Some(&factor) = factors.get(name) else {
+            if generated {
+                missing_factors.push(name);
+            }
+            continue;
+        };
+        for dp in &response.data {
+            let entry = weighted.entry(dp.timestamp).or_insert((0.0, 0.0));
+            if let Some(generation) = dp.value.filter(|v| *v > 0.0) {
+                entry.0 += generation * factor;
+                entry.1 += generation;
+            }
+        }
+    }
+    if !fetched_any {
+        return Err(last_error.unwrap_or_else(|| CollectorError::InvalidInput("No generation sources".to_string())));
+    }
+    if !missing_factors.is_empty() {
+        warnings.push(Warning::new(
+            "missing_emission_factor",
+            format!("No emission factor for {}; their generation is left out of the carbon intensity", missing_factors.join(", ")),
+        ));
+    }
+    for warning in &warnings {
+        log!("WARNING (Rust): {}", warning);
+    }
+
+    let points = weighted.into_iter()
+        .map(|(ts, (emissions, generation))| (ts, (generation > 0.0).then(|| emissions / generation)))
+        .collect();
+    Ok((points, warnings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SmardDataPoint;
+
+    fn generation(values: &[(i64, Option<f64>)]) -> Result<SmardApiResponse, CollectorError> {
+        Ok(SmardApiResponse {
+            data: values.iter().map(|&(timestamp, value)| SmardDataPoint { timestamp, value }).collect(),
+            source_unit: None,
+            config_hash: None,
+        })
+    }
+
+    fn unavailable() -> Result<SmardApiResponse, CollectorError> {
+        Err(CollectorError::InvalidInput("unavailable".to_string()))
+    }
+
+    #[test]
+    fn skipped_sources_are_reported() {
+        let fetched = vec![
+            ("solar", generation(&[(0, Some(300.0)), (1, None)])),
+            ("lignite", generation(&[(0, Some(100.0)), (1, None)])),
+            ("hard_coal", unavailable()),
+            ("pumped_storage", unavailable()),
+            ("fusion", generation(&[(0, Some(50.0))])),
+        ];
+        let (points, warnings) = weigh_generation(fetched, &default_emission_factors()).unwrap();
+        assert_eq!(points, vec![(0, Some((300.0 * 41.0 + 100.0 * 1150.0) / 400.0)), (1, None)]);
+        let codes: Vec<_> = warnings.iter().map(|w| w.code).collect();
+        assert_eq!(codes, vec!["carbon_source_skipped", "missing_emission_factor"]);
+        assert!(warnings[0].message.starts_with("hard_coal"));
+        assert!(warnings[1].message.contains("fusion"));
+    }
+
+    #[test]
+    fn no_source_at_all_is_an_error() {
+        let fetched = vec![("solar", unavailable()), ("lignite", unavailable())];
+        assert!(matches!(weigh_generation(fetched, &default_emission_factors()), Err(CollectorError::InvalidInput(_))));
+    }
+}
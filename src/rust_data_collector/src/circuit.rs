@@ -0,0 +1,280 @@
+// src/rust_data_collector/src/circuit.rs
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::{http, CollectorError};
+
+// --- Per-Provider Circuit Breaker ---
+
+pub const DEFAULT_FAILURE_THRESHOLD: u32 = 3;
+pub const DEFAULT_COOLDOWN: Duration = Duration::from_secs(300);
+
+// SMARD has no published rate limit but blocks bursts, such as a backfill firing all its
+// segments at once.
+pub const DEFAULT_SMARD_MIN_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy)]
+enum CircuitState {
+    Closed { consecutive_failures: u32 },
+    Open { until: Instant },
+    // Cooldown is over and a single trial request is in flight.
+    HalfOpen,
+}
+
+#[derive(Debug)]
+struct BreakerInner {
+    state: CircuitState,
+    failure_threshold: u32,
+    cooldown: Duration,
+}
+
+// After `failure_threshold` consecutive transient failures the circuit opens and calls
+// fail fast with `CircuitOpen` for `cooldown`, instead of burning quota on a provider
+// that is down. The first call after the cooldown is let through as a trial: success
+// closes the circuit again, failure re-opens it.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    provider: &'static str,
+    inner: Mutex<BreakerInner>,
+    spacing: Mutex<Spacing>,
+}
+
+pub static OPENWEATHER: CircuitBreaker = CircuitBreaker::new("OpenWeatherMap", Duration::ZERO);
+pub static SMARD: CircuitBreaker = CircuitBreaker::new("SMARD", DEFAULT_SMARD_MIN_INTERVAL);
+pub static OPEN_METEO: CircuitBreaker = CircuitBreaker::new("Open-Meteo", Duration::ZERO);
+
+/// The breaker for a provider as named in `fetch_weather_with_fallback`'s list, plus
+/// "smard".
+pub fn by_name(provider: &str) -> Option<&'static CircuitBreaker> {
+    match provider {
+        "openweather" => Some(&OPENWEATHER),
+        "smard" => Some(&SMARD),
+        "open-meteo" => Some(&OPEN_METEO),
+        _ => None,
+    }
+}
+
+impl CircuitBreaker {
+    pub const fn new(provider: &'static str, min_interval: Duration) -> Self {
+        CircuitBreaker {
+            provider,
+            inner: Mutex::new(BreakerInner {
+                state: CircuitState::Closed { consecutive_failures: 0 },
+                failure_threshold: DEFAULT_FAILURE_THRESHOLD,
+                cooldown: DEFAULT_COOLDOWN,
+            }),
+            spacing: Mutex::new(Spacing { min_interval, next_slot: None }),
+        }
+    }
+
+    pub fn configure(&self, failure_threshold: u32, cooldown: Duration) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.failure_threshold = failure_threshold.max(1);
+        inner.cooldown = cooldown;
+    }
+
+    /// Puts the circuit back into the closed state, e.g. after fixing the configuration.
+    pub fn reset(&self) {
+        self.inner.lock().unwrap().state = CircuitState::Closed { consecutive_failures: 0 };
+    }
+
+    pub fn call<T>(&self, request: impl FnOnce() -> Result<T, CollectorError>) -> Result<T, CollectorError> {
+        {
+            let mut inner = self.inner.lock().unwrap();
+            match inner.state {
+                CircuitState::Open { until } if Instant::now() < until => {
+                    return Err(self.open_error(until));
+                }
+                CircuitState::Open { .. } => {
+                    log!("DEBUG (Rust): {} circuit half-open, sending trial request.", self.provider);
+                    inner.state = CircuitState::HalfOpen;
+                }
+                // Another caller's trial request is still running.
+                CircuitState::HalfOpen => return Err(self.open_error(Instant::now())),
+                CircuitState::Closed { .. } => {}
+            }
+        }
+
+        // A request cut short by the caller's deadline fails as `DeadlineExceeded`, not as
+        // a provider failure.
+        let result = self.wait_for_slot().and_then(|_| request()).map_err(http::deadline_error_or);
+
+        let mut inner = self.inner.lock().unwrap();
+        match &result {
+            Ok(_) => inner.state = CircuitState::Closed { consecutive_failures: 0 },
+            Err(e) if e.is_transient() => {
+                let failures = match inner.state {
+                    CircuitState::Closed { consecutive_failures } => consecutive_failures + 1,
+                    // A failed trial re-opens immediately.
+                    _ => inner.failure_threshold,
+                };
+                if failures >= inner.failure_threshold {
+                    log!(
+                        "WARNING (Rust): {} failed {} times in a row, opening circuit for {}s.",
+                        self.provider, failures, inner.cooldown.as_secs()
+                    );
+                    inner.state = CircuitState::Open { until: Instant::now() + inner.cooldown };
+                } else {
+                    inner.state = CircuitState::Closed { consecutive_failures: failures };
+                }
+            }
+            // No verdict on the provider; the next call gets to send the trial instead.
+            Err(CollectorError::DeadlineExceeded { .. }) => {
+                if let CircuitState::HalfOpen = inner.state {
+                    inner.state = CircuitState::Open { until: Instant::now() };
+                }
+            }
+            // Bad input or bad credentials say nothing about provider health.
+            Err(_) => {
+                if let CircuitState::HalfOpen = inner.state {
+                    inner.state = CircuitState::Closed { consecutive_failures: 0 };
+                }
+            }
+        }
+        result
+    }
+
+    fn open_error(&self, until: Instant) -> CollectorError {
+        CollectorError::CircuitOpen {
+            provider: self.provider.to_string(),
+            retry_in_secs: until.saturating_duration_since(Instant::now()).as_secs(),
+        }
+    }
+}
+
+// --- Request Spacing ---
+
+// Consecutive requests to a provider start at least `min_interval` apart. Each caller
+// reserves the next free start time and sleeps until it, outside the lock, so concurrent
+// callers (backfill workers) queue up evenly instead of all waking at once.
+
+#[derive(Debug)]
+struct Spacing {
+    min_interval: Duration,
+    next_slot: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    /// Minimum time between the starts of two requests to this provider; zero disables it.
+    pub fn set_min_interval(&self, min_interval: Duration) {
+        let mut spacing = self.spacing.lock().unwrap();
+        spacing.min_interval = min_interval;
+        spacing.next_slot = None;
+    }
+
+    // Fails with `DeadlineExceeded` instead of sleeping past the caller's deadline.
+    fn wait_for_slot(&self) -> Result<(), CollectorError> {
+        let wait = {
+            let mut spacing = self.spacing.lock().unwrap();
+            if spacing.min_interval.is_zero() {
+                return Ok(());
+            }
+            let now = Instant::now();
+            let slot = spacing.next_slot.map_or(now, |next| next.max(now));
+            spacing.next_slot = Some(slot + spacing.min_interval);
+            slot - now
+        };
+        if wait.is_zero() {
+            return Ok(());
+        }
+        if let Some(deadline) = http::current_deadline() {
+            if Instant::now() + wait >= deadline.at {
+                return Err(CollectorError::DeadlineExceeded { limit_secs: deadline.limit.as_secs_f64() });
+            }
+        }
+        log!("DEBUG (Rust): Spacing {} requests, waiting {} ms.", self.provider, wait.as_millis());
+        std::thread::sleep(wait);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outage() -> Result<(), CollectorError> {
+        Err(CollectorError::Status { status: 503, body: "Service Unavailable".to_string() })
+    }
+
+    #[test]
+    fn opens_after_threshold_and_fails_fast() {
+        let breaker = CircuitBreaker::new("test", Duration::ZERO);
+        breaker.configure(2, Duration::from_secs(60));
+        assert!(matches!(breaker.call(outage), Err(CollectorError::Status { .. })));
+        assert!(matches!(breaker.call(outage), Err(CollectorError::Status { .. })));
+        let mut called = false;
+        let result = breaker.call(|| {
+            called = true;
+            Ok(())
+        });
+        assert!(matches!(result, Err(CollectorError::CircuitOpen { .. })));
+        assert!(!called, "open circuit must not reach the provider");
+    }
+
+    #[test]
+    fn non_transient_errors_keep_it_closed() {
+        let breaker = CircuitBreaker::new("test", Duration::ZERO);
+        breaker.configure(1, Duration::from_secs(60));
+        let rejected = breaker.call(|| Err::<(), _>(CollectorError::Status { status: 401, body: String::new() }));
+        assert!(matches!(rejected, Err(CollectorError::Status { status: 401, .. })));
+        assert!(breaker.call(|| Ok(())).is_ok());
+    }
+
+    #[test]
+    fn trial_after_cooldown_closes_or_reopens() {
+        let breaker = CircuitBreaker::new("test", Duration::ZERO);
+        breaker.configure(1, Duration::ZERO);
+        let _ = breaker.call(outage);
+        // Cooldown over: the trial goes through, and its failure re-opens the circuit.
+        assert!(matches!(breaker.call(outage), Err(CollectorError::Status { .. })));
+        assert!(breaker.call(|| Ok(())).is_ok());
+        assert!(breaker.call(|| Ok(())).is_ok());
+    }
+
+    // Calls `breaker` three times from one thread and returns when each request started.
+    fn request_starts(breaker: &CircuitBreaker) -> Vec<Instant> {
+        (0..3).map(|_| breaker.call(|| Ok(Instant::now())).unwrap()).collect()
+    }
+
+    #[test]
+    fn requests_are_spaced_by_min_interval() {
+        let interval = Duration::from_millis(50);
+        let breaker = CircuitBreaker::new("test", interval);
+        let starts = request_starts(&breaker);
+        // Slots are reserved before the clock is read in the request, so a start can come
+        // a hair early relative to the previous one.
+        for pair in starts.windows(2) {
+            assert!(pair[1] - pair[0] >= interval - Duration::from_millis(2), "requests {:?} apart", pair[1] - pair[0]);
+        }
+    }
+
+    #[test]
+    fn concurrent_callers_queue_up() {
+        let interval = Duration::from_millis(50);
+        let breaker = CircuitBreaker::new("test", interval);
+        let mut starts: Vec<Instant> = std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..3).map(|_| scope.spawn(|| breaker.call(|| Ok(Instant::now())).unwrap())).collect();
+            workers.into_iter().map(|w| w.join().unwrap()).collect()
+        });
+        starts.sort();
+        assert!(starts[2] - starts[0] >= 2 * interval - Duration::from_millis(2));
+    }
+
+    #[test]
+    fn zero_interval_does_not_wait() {
+        let breaker = CircuitBreaker::new("test", Duration::from_secs(10));
+        breaker.set_min_interval(Duration::ZERO);
+        let starts = request_starts(&breaker);
+        assert!(starts[2] - starts[0] < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn spacing_wait_respects_deadline() {
+        let breaker = CircuitBreaker::new("test", Duration::from_secs(10));
+        assert!(breaker.call(|| Ok(())).is_ok());
+        let _deadline = http::with_deadline(Some(http::Deadline::after(Duration::from_millis(100))));
+        assert!(matches!(breaker.call(|| Ok(())), Err(CollectorError::DeadlineExceeded { .. })));
+    }
+
+}
@@ -0,0 +1,87 @@
+// src/rust_data_collector/src/clock.rs
+
+use chrono::{DateTime, Utc};
+use std::sync::{Arc, RwLock};
+
+// --- Clock ---
+
+// Where "now" comes from for everything that doesn't take it as a parameter: the call
+// budget's day, forecast confidence, store eviction, the default windows. Swapping in a
+// `FixedClock` makes those deterministic. Durations measured with `Instant` (cache TTLs,
+// circuit cooldowns) stay on the monotonic clock.
+
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+// Always the same instant, e.g. to replay a past run.
+pub struct FixedClock(pub DateTime<Utc>);
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+// `None` means `SystemClock`.
+static CLOCK: RwLock<Option<Arc<dyn Clock>>> = RwLock::new(None);
+
+/// Replaces the process-wide clock; `None` goes back to the system clock.
+pub fn set_clock(clock: Option<Arc<dyn Clock>>) {
+    *CLOCK.write().unwrap() = clock;
+}
+
+pub fn now() -> DateTime<Utc> {
+    match CLOCK.read().unwrap().as_ref() {
+        Some(clock) => clock.now(),
+        None => Utc::now(),
+    }
+}
+
+// Tests run in parallel but share the clock, so the ones that pin it take turns; the clock
+// goes back to the system one when the guard drops.
+#[cfg(test)]
+pub(crate) struct PinnedClock {
+    _turn: std::sync::MutexGuard<'static, ()>,
+}
+
+#[cfg(test)]
+pub(crate) fn pin(at: DateTime<Utc>) -> PinnedClock {
+    static PINNED: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    let guard = PINNED.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    set_clock(Some(Arc::new(FixedClock(at))));
+    PinnedClock { _turn: guard }
+}
+
+#[cfg(test)]
+impl Drop for PinnedClock {
+    fn drop(&mut self) {
+        set_clock(None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn fixed_clock_replaces_and_restores_now() {
+        let pinned = Utc.with_ymd_and_hms(2024, 3, 31, 1, 30, 0).unwrap();
+        assert_eq!(FixedClock(pinned).now(), pinned);
+
+        let guard = pin(pinned);
+        let seen = (now(), now());
+        drop(guard);
+        assert_eq!(seen, (pinned, pinned));
+        assert!(now() > pinned);
+    }
+}
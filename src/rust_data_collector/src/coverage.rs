@@ -0,0 +1,53 @@
+// src/rust_data_collector/src/coverage.rs
+
+use crate::{OpenWeatherHourlyForecast, SmardDataPoint};
+
+// --- Data Coverage ---
+
+// What a fetch actually got, which is often less than was asked for: day-ahead prices end
+// where the last auction did, and a forecast ends at the provider's horizon. Planning
+// past `end_ts` means planning on data that isn't there.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Coverage {
+    pub start_ts: i64, // Milliseconds since epoch, first point with a value
+    pub end_ts: i64,   // Milliseconds since epoch, last point with a value
+    pub count: usize,  // Points with a value in start_ts..=end_ts
+    // Points a gapless series over the same span would have, at the series' own step.
+    pub expected_count: usize,
+}
+
+impl Coverage {
+    pub fn as_tuple(&self) -> (i64, i64, usize, usize) {
+        (self.start_ts, self.end_ts, self.count, self.expected_count)
+    }
+}
+
+/// Span of the points that carry a value, or `None` if none do. The step is the shortest
+/// gap between any two points (unpriced ones included), so a series with a single point
+/// expects exactly one.
+pub fn coverage(points: &[SmardDataPoint]) -> Option<Coverage> {
+    let mut all: Vec<i64> = points.iter().map(|dp| dp.timestamp).collect();
+    all.sort_unstable();
+    all.dedup();
+    let step = all.windows(2).map(|w| w[1] - w[0]).min();
+
+    let mut valued: Vec<i64> = points.iter().filter(|dp| dp.value.is_some()).map(|dp| dp.timestamp).collect();
+    valued.sort_unstable();
+    valued.dedup();
+    let (&start_ts, &end_ts) = (valued.first()?, valued.last()?);
+
+    let expected_count = match step {
+        Some(step) => ((end_ts - start_ts) / step) as usize + 1,
+        None => 1,
+    };
+    Some(Coverage { start_ts, end_ts, count: valued.len(), expected_count })
+}
+
+/// `coverage` of an hourly forecast, in milliseconds like the price series.
+pub fn weather_coverage(hourly: &[OpenWeatherHourlyForecast]) -> Option<Coverage> {
+    let points: Vec<SmardDataPoint> = hourly.iter()
+        .map(|hour| SmardDataPoint { timestamp: hour.dt * 1000, value: Some(hour.temp) })
+        .collect();
+    coverage(&points)
+}
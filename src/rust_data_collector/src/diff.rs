@@ -0,0 +1,180 @@
+// src/rust_data_collector/src/diff.rs
+
+use chrono::{DateTime, Utc};
+use pyo3::prelude::*;
+use std::collections::BTreeMap;
+use std::fs;
+
+use crate::merge::HourlyPoint;
+use crate::timestamp::hour_start;
+use crate::CollectorError;
+
+// --- Snapshot Diffs ---
+
+// One hour present in both snapshots.
+#[pyclass(get_all)]
+#[derive(Debug, Clone)]
+pub struct HourDelta {
+    pub timestamp_ms: i64, // Start of the hour
+    pub old: Option<f64>,
+    pub new: Option<f64>,
+    pub delta: Option<f64>, // new - old, when both have a value
+}
+
+#[pymethods]
+impl HourDelta {
+    fn __repr__(&self) -> String {
+        format!("HourDelta(timestamp_ms={}, old={:?}, new={:?}, delta={:?})", self.timestamp_ms, self.old, self.new, self.delta)
+    }
+}
+
+// What moved between two saved files of the same kind.
+#[pyclass(get_all)]
+#[derive(Debug, Clone)]
+pub struct SnapshotDiff {
+    pub kind: String,             // "weather" (temperature, °C) or "prices" (EUR/MWh)
+    pub changed: Vec<HourDelta>,  // Hours in both files whose value differs
+    pub unchanged_hours: usize,
+    pub added: Vec<i64>,          // Hour starts (ms) only in the new file
+    pub removed: Vec<i64>,        // Hour starts (ms) only in the old file
+}
+
+#[pymethods]
+impl SnapshotDiff {
+    fn __repr__(&self) -> String {
+        format!(
+            "SnapshotDiff(kind={:?}, changed={}, unchanged_hours={}, added={}, removed={})",
+            self.kind, self.changed.len(), self.unchanged_hours, self.added.len(), self.removed.len()
+        )
+    }
+}
+
+// Hour start (ms) -> the value compared for that hour.
+type Snapshot = BTreeMap<i64, Option<f64>>;
+
+// Tells `weather_data.json` (One Call, `hourly`) from `smard_prices.json` (`data`) by shape.
+fn load_snapshot(path: &str) -> Result<(&'static str, Snapshot), CollectorError> {
+    let body = fs::read_to_string(path)?;
+    let json: serde_json::Value = serde_json::from_str(&body)
+        .map_err(|e| CollectorError::Parse { source: e, body: String::new() })?;
+
+    let hours = |items: &serde_json::Value, ts_key: &str, value: &dyn Fn(&serde_json::Value) -> Option<f64>| -> Snapshot {
+        items.as_array().into_iter().flatten()
+            .filter_map(|item| {
+                // SMARD points may be `{timestamp, value}` objects or `[timestamp, value]` pairs.
+                let ts = item.get(ts_key).or_else(|| item.get(0))?.as_i64()?;
+                Some((hour_start(ts) * 1000, value(item)))
+            })
+            .collect()
+    };
+    if let Some(hourly) = json.get("hourly") {
+        Ok(("weather", hours(hourly, "dt", &|item| item.get("temp").and_then(|v| v.as_f64()))))
+    } else if let Some(data) = json.get("data") {
+        Ok(("prices", hours(data, "timestamp", &|item| item.get("value").or_else(|| item.get(1)).and_then(|v| v.as_f64()))))
+    } else {
+        Err(CollectorError::InvalidInput(format!(
+            "{} is neither a saved weather file (\"hourly\") nor a saved price file (\"data\")",
+            path
+        )))
+    }
+}
+
+/// Aligns two saved snapshots of the same kind by hour and reports what changed.
+pub fn diff_snapshots(old_path: &str, new_path: &str) -> Result<SnapshotDiff, CollectorError> {
+    let (old_kind, old) = load_snapshot(old_path)?;
+    let (new_kind, new) = load_snapshot(new_path)?;
+    if old_kind != new_kind {
+        return Err(CollectorError::InvalidInput(format!(
+            "Cannot diff a {} file against a {} file",
+            old_kind, new_kind
+        )));
+    }
+
+    let mut diff = SnapshotDiff {
+        kind: new_kind.to_string(),
+        changed: Vec::new(),
+        unchanged_hours: 0,
+        added: new.keys().filter(|ts| !old.contains_key(ts)).copied().collect(),
+        removed: old.keys().filter(|ts| !new.contains_key(ts)).copied().collect(),
+    };
+    for (&timestamp_ms, &old_value) in &old {
+        let Some(&new_value) = new.get(&timestamp_ms) else {
+            continue;
+        };
+        if old_value == new_value {
+            diff.unchanged_hours += 1;
+        } else {
+            diff.changed.push(HourDelta {
+                timestamp_ms,
+                old: old_value,
+                new: new_value,
+                delta: old_value.zip(new_value).map(|(o, n)| n - o),
+            });
+        }
+    }
+    Ok(diff)
+}
+
+// --- Forecast Delta Alerts ---
+
+// An hour whose forecast moved by more than the caller tolerates since the previous fetch.
+#[pyclass(get_all)]
+#[derive(Debug, Clone)]
+pub struct ForecastDeltaAlert {
+    pub timestamp: i64,       // Unix seconds, start of the hour
+    pub metric: &'static str, // "price" (EUR/MWh) or "temp" (°C)
+    pub old: f64,
+    pub new: f64,
+    pub delta: f64, // new - old
+}
+
+#[pymethods]
+impl ForecastDeltaAlert {
+    fn __repr__(&self) -> String {
+        format!(
+            "ForecastDeltaAlert(timestamp={}, metric={:?}, old={}, new={}, delta={:+})",
+            self.timestamp, self.metric, self.old, self.new, self.delta
+        )
+    }
+}
+
+/// Hours from `now` on whose price or temperature differs between the `old` and `new`
+/// merged series by more than `price_threshold` (EUR/MWh) or `temp_threshold` (°C), in
+/// time order, price before temperature within an hour. Hours missing from either series,
+/// or without a price in either, are not compared.
+pub fn forecast_delta_alert(
+    old: &[HourlyPoint],
+    new: &[HourlyPoint],
+    price_threshold: f64,
+    temp_threshold: f64,
+    now: DateTime<Utc>,
+) -> Result<Vec<ForecastDeltaAlert>, CollectorError> {
+    for (name, threshold) in [("price_threshold", price_threshold), ("temp_threshold", temp_threshold)] {
+        if threshold.is_nan() || threshold < 0.0 {
+            return Err(CollectorError::InvalidInput(format!("{} must be zero or positive, got {}", name, threshold)));
+        }
+    }
+    let old: BTreeMap<i64, &HourlyPoint> = old.iter().map(|p| (hour_start(p.timestamp), p)).collect();
+    let current_hour = hour_start(now.timestamp());
+    let mut alerts = Vec::new();
+    for point in new {
+        let timestamp = hour_start(point.timestamp);
+        let Some(before) = old.get(&timestamp).filter(|_| timestamp >= current_hour) else {
+            continue;
+        };
+        let compared = [
+            ("price", before.price, point.price, price_threshold),
+            ("temp", Some(before.temp), Some(point.temp), temp_threshold),
+        ];
+        for (metric, old_value, new_value, threshold) in compared {
+            if let (Some(old_value), Some(new_value)) = (old_value, new_value) {
+                let delta = new_value - old_value;
+                if delta.abs() > threshold {
+                    alerts.push(ForecastDeltaAlert { timestamp, metric, old: old_value, new: new_value, delta });
+                }
+            }
+        }
+    }
+    alerts.sort_by_key(|a| a.timestamp);
+    Ok(alerts)
+}
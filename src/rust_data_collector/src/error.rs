@@ -0,0 +1,190 @@
+// src/rust_data_collector/src/error.rs
+
+use pyo3::PyErr;
+use std::fmt;
+
+// --- Python Exception Classes ---
+
+// Raised to Python instead of the generic ValueError/IOError, so the UI can tell a bad
+// key from an outage and only retry what is retryable. All derive from `CollectorError`.
+pub mod exceptions {
+    use pyo3::create_exception;
+    use pyo3::exceptions::PyException;
+
+    create_exception!(rust_data_collector, CollectorError, PyException, "Base class for all collector failures.");
+    create_exception!(rust_data_collector, ApiKeyError, CollectorError, "API key missing, invalid, or not authorized.");
+    create_exception!(rust_data_collector, PlanRestrictedError, ApiKeyError, "API key is valid but its plan does not include this endpoint.");
+    create_exception!(rust_data_collector, NetworkError, CollectorError, "Provider unreachable or failing; retrying later may help.");
+    create_exception!(rust_data_collector, RateLimitError, NetworkError, "Provider rate limit or quota hit; back off before retrying.");
+    create_exception!(rust_data_collector, ParseError, CollectorError, "Provider response did not have the expected shape.");
+}
+
+// --- Collector Error Type ---
+
+// Single error type for everything the collector can fail on, so the fetch functions
+// don't have to squeeze non-HTTP failures (bad JSON, bad input) into a reqwest::Error.
+#[derive(Debug)]
+pub enum CollectorError {
+    /// The request could not be sent or the body could not be read.
+    Http(reqwest::Error),
+    /// The API answered with a non-success status code.
+    Status { status: u16, body: String },
+    /// The API answered 200 but the body is an error object (`{"cod": 401, "message": ...}`).
+    Api { code: u16, message: String },
+    /// The body was received but did not match the expected JSON shape.
+    Parse { source: serde_json::Error, body: String },
+    /// The requested time window is empty, reversed, or in the wrong unit.
+    InvalidTimeRange(String),
+    /// Local I/O failed (files, sockets).
+    Io(std::io::Error),
+    /// A required API key/token (named here) is not configured.
+    MissingApiKey(String),
+    /// User-supplied data (a file, a parameter) could not be used; the message says why.
+    InvalidInput(String),
+    /// The key works, but its subscription plan does not cover `feature` (e.g. "One Call 3.0").
+    PlanRestricted { feature: String, body: String },
+    /// The response body was larger than the configured limit and was not read.
+    ResponseTooLarge { limit: u64 },
+    /// The configured daily OpenWeatherMap call budget is used up (resets at UTC midnight).
+    BudgetExceeded { used: u32, budget: u32 },
+    /// The provider failed repeatedly and requests are short-circuited for a while.
+    CircuitOpen { provider: String, retry_in_secs: u64 },
+    /// The caller's deadline for the whole operation passed before it finished.
+    DeadlineExceeded { limit_secs: f64 },
+    /// A successful response that isn't JSON, typically a captive portal or proxy page.
+    UnexpectedContentType { content_type: String, body_snippet: String },
+    /// More forecast hours were asked for than the provider's documented maximum.
+    ForecastHorizon { provider: String, max_hours: u32, requested: u32 },
+}
+
+impl fmt::Display for CollectorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CollectorError::Http(e) => write!(f, "HTTP request failed: {}", e),
+            CollectorError::Status { status, body } => {
+                write!(f, "API returned status {}: {}", status, body)
+            }
+            CollectorError::Api { code, message } => write!(f, "API returned error {}: {}", code, message),
+            CollectorError::Parse { source, .. } => write!(f, "Failed to parse response: {}", source),
+            CollectorError::InvalidTimeRange(msg) => write!(f, "Invalid time range: {}", msg),
+            CollectorError::Io(e) => write!(f, "I/O error: {}", e),
+            CollectorError::MissingApiKey(name) => write!(f, "{} not set", name),
+            CollectorError::InvalidInput(msg) => write!(f, "Invalid input: {}", msg),
+            CollectorError::PlanRestricted { feature, body } => write!(
+                f,
+                "The API key is valid, but its plan does not include {}; upgrade the subscription to use it ({})",
+                feature, body
+            ),
+            CollectorError::ResponseTooLarge { limit } => {
+                write!(f, "Response body is larger than the {} byte limit", limit)
+            }
+            CollectorError::BudgetExceeded { used, budget } => write!(
+                f,
+                "Daily OpenWeatherMap call budget reached ({} of {} calls used); resets at UTC midnight",
+                used, budget
+            ),
+            CollectorError::CircuitOpen { provider, retry_in_secs } => write!(
+                f,
+                "{} circuit is open after repeated failures; not retrying for another {}s",
+                provider, retry_in_secs
+            ),
+            CollectorError::DeadlineExceeded { limit_secs } => {
+                write!(f, "Gave up after the {}s deadline", limit_secs)
+            }
+            CollectorError::UnexpectedContentType { content_type, body_snippet } => {
+                let hint = if content_type.to_ascii_lowercase().contains("html") {
+                    " (are you behind a captive portal or proxy?)"
+                } else {
+                    ""
+                };
+                write!(f, "Expected a JSON response but got {}{}: {}", content_type, hint, body_snippet)
+            }
+            CollectorError::ForecastHorizon { provider, max_hours, requested } => write!(
+                f,
+                "{} forecasts at most {} hours, but {} were requested",
+                provider, max_hours, requested
+            ),
+        }
+    }
+}
+
+impl CollectorError {
+    /// Whether retrying later (or elsewhere) could succeed: network failures, 5xx, 429 and
+    /// open circuits. Bad input, bad credentials and malformed responses are not transient,
+    /// and neither is a passed deadline, which says nothing about the provider.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            CollectorError::Http(_) | CollectorError::CircuitOpen { .. } => true,
+            CollectorError::Status { status: code, .. } | CollectorError::Api { code, .. } => {
+                *code >= 500 || *code == 429
+            }
+            _ => false,
+        }
+    }
+}
+
+impl CollectorError {
+    /// Converts to the matching Python exception class, prefixing the message with `context`.
+    pub fn into_py_err(self, context: &str) -> PyErr {
+        let message = format!("{}: {}", context, self);
+        match self {
+            CollectorError::MissingApiKey(_) => exceptions::ApiKeyError::new_err(message),
+            CollectorError::Status { status: 401 | 403, .. } | CollectorError::Api { code: 401 | 403, .. } => {
+                exceptions::ApiKeyError::new_err(message)
+            }
+            CollectorError::PlanRestricted { .. } => exceptions::PlanRestrictedError::new_err(message),
+            CollectorError::Status { status: 429, .. }
+            | CollectorError::Api { code: 429, .. }
+            | CollectorError::BudgetExceeded { .. } => {
+                exceptions::RateLimitError::new_err(message)
+            }
+            CollectorError::Http(_)
+            | CollectorError::Status { .. }
+            | CollectorError::Api { .. }
+            | CollectorError::CircuitOpen { .. } => {
+                exceptions::NetworkError::new_err(message)
+            }
+            CollectorError::Parse { .. }
+            | CollectorError::ResponseTooLarge { .. }
+            | CollectorError::UnexpectedContentType { .. } => {
+                exceptions::ParseError::new_err(message)
+            }
+            CollectorError::InvalidTimeRange(_)
+            | CollectorError::InvalidInput(_)
+            | CollectorError::ForecastHorizon { .. } => {
+                pyo3::exceptions::PyValueError::new_err(message)
+            }
+            CollectorError::Io(_) => pyo3::exceptions::PyIOError::new_err(message),
+            CollectorError::DeadlineExceeded { .. } => pyo3::exceptions::PyTimeoutError::new_err(message),
+        }
+    }
+}
+
+impl From<CollectorError> for PyErr {
+    fn from(e: CollectorError) -> Self {
+        e.into_py_err("rust_data_collector")
+    }
+}
+
+impl std::error::Error for CollectorError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CollectorError::Http(e) => Some(e),
+            CollectorError::Parse { source, .. } => Some(source),
+            CollectorError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for CollectorError {
+    fn from(e: reqwest::Error) -> Self {
+        CollectorError::Http(e)
+    }
+}
+
+impl From<std::io::Error> for CollectorError {
+    fn from(e: std::io::Error) -> Self {
+        CollectorError::Io(e)
+    }
+}
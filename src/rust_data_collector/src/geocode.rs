@@ -0,0 +1,130 @@
+// src/rust_data_collector/src/geocode.rs
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::{circuit, clock, http, CollectorError};
+
+// --- City Geocoding ---
+
+// Resolves a place name to coordinates with OpenWeatherMap's Geocoding API. Cities don't
+// move, so every answer is kept in a small JSON file and reused for a long time; a fixed
+// location given by name then costs one lookup, not one per fetch.
+
+const GEOCODING_URL: &str = "https://api.openweathermap.org/geo/1.0/direct";
+
+// Long enough to make lookups rare, short enough that a corrected entry upstream eventually
+// arrives.
+pub const GEOCODE_CACHE_TTL: Duration = Duration::days(365);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct CachedLocation {
+    lat: f64,
+    lon: f64,
+    resolved_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeocodingMatch {
+    lat: f64,
+    lon: f64,
+}
+
+static CACHE_FILE: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Where resolved locations are kept; `None` for the default file in the temp dir.
+pub fn set_cache_file(path: Option<PathBuf>) {
+    *CACHE_FILE.lock().unwrap() = path;
+}
+
+fn cache_path() -> PathBuf {
+    CACHE_FILE.lock().unwrap().clone()
+        .unwrap_or_else(|| std::env::temp_dir().join("rust_data_collector_geocode.json"))
+}
+
+// Case and surrounding whitespace don't make a different city.
+fn cache_key(city: &str, country: Option<&str>) -> String {
+    match country {
+        Some(country) => format!("{},{}", city.trim(), country.trim()).to_lowercase(),
+        None => city.trim().to_lowercase(),
+    }
+}
+
+// A missing or unreadable cache is just empty; it only saves requests.
+fn load_cache(path: &PathBuf) -> BTreeMap<String, CachedLocation> {
+    fs::read_to_string(path).ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_cache(path: &PathBuf, cache: &BTreeMap<String, CachedLocation>) -> Result<(), CollectorError> {
+    // Write-then-rename, as for the call budget state.
+    let tmp = path.with_extension("json.tmp");
+    let json = serde_json::to_string_pretty(cache)
+        .map_err(|e| CollectorError::Parse { source: e, body: String::new() })?;
+    fs::write(&tmp, json)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+fn request_location(api_key: &str, query: &str) -> Result<(f64, f64), CollectorError> {
+    log!("DEBUG (Rust): Geocoding {:?} with OpenWeatherMap", query);
+    let matches: Vec<GeocodingMatch> = circuit::OPENWEATHER.call(|| {
+        let response = http::client()?
+            .get(GEOCODING_URL)
+            .query(&[("q", query), ("limit", "1"), ("appid", api_key)])
+            .send()?;
+        let status = response.status();
+        let content_type = http::content_type(&response);
+        let body = http::read_body(response)?;
+        if !status.is_success() {
+            log!("ERROR (Rust): OpenWeatherMap geocoding returned non-success status {}. Full raw response: {}", status, body);
+            return Err(CollectorError::Status { status: status.as_u16(), body });
+        }
+        http::expect_json(content_type.as_deref(), &body)?;
+        serde_json::from_str(&body).map_err(|e| CollectorError::Parse { source: e, body })
+    })?;
+    matches.first()
+        .map(|m| (m.lat, m.lon))
+        .ok_or_else(|| CollectorError::InvalidInput(format!("No location found for {:?}", query)))
+}
+
+/// `(lat, lon)` of `city`, optionally narrowed by an ISO 3166 `country` code ("DE"). Served
+/// from the cache when it was resolved within `GEOCODE_CACHE_TTL`.
+pub fn geocode(api_key: &str, city: &str, country: Option<&str>) -> Result<(f64, f64), CollectorError> {
+    if city.trim().is_empty() {
+        return Err(CollectorError::InvalidInput("city must not be empty".to_string()));
+    }
+    let key = cache_key(city, country);
+    let path = cache_path();
+    let mut cache = load_cache(&path);
+    let now = clock::now();
+    if let Some(hit) = cache.get(&key).filter(|hit| now - hit.resolved_at < GEOCODE_CACHE_TTL) {
+        log!("DEBUG (Rust): Geocoding {:?} served from cache ({}, {}).", key, hit.lat, hit.lon);
+        return Ok((hit.lat, hit.lon));
+    }
+
+    let query = match country {
+        Some(country) => format!("{},{}", city.trim(), country.trim()),
+        None => city.trim().to_string(),
+    };
+    let (lat, lon) = request_location(api_key, &query)?;
+    cache.insert(key, CachedLocation { lat, lon, resolved_at: now });
+    // The lookup itself succeeded; a cache that can't be written only costs a request later.
+    if let Err(e) = save_cache(&path, &cache) {
+        log!("WARNING (Rust): Could not save geocoding cache {:?}: {}", path, e);
+    }
+    Ok((lat, lon))
+}
+
+/// Forgets every cached location.
+pub fn clear_cache() -> Result<(), CollectorError> {
+    match fs::remove_file(cache_path()) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,87 @@
+// src/rust_data_collector/src/health.rs
+
+use pyo3::prelude::*;
+use std::time::Instant;
+
+use crate::{http, request_openweather_data, OneCallBlocks, OneCallVersion, SMARD_BASE_URL, SMARD_PRICE_FILTER, SMARD_RESOLUTION};
+
+// --- Connectivity / Credential Self-Test ---
+
+#[pyclass(get_all)]
+#[derive(Debug, Clone)]
+pub struct HealthReport {
+    pub openweather_ok: bool,
+    pub smard_ok: bool,
+    pub openweather_latency_ms: u64,
+    pub smard_latency_ms: u64,
+    pub openweather_error: Option<String>,
+    pub smard_error: Option<String>,
+}
+
+#[pymethods]
+impl HealthReport {
+    fn __repr__(&self) -> String {
+        format!(
+            "HealthReport(openweather_ok={}, smard_ok={}, openweather_latency_ms={}, smard_latency_ms={})",
+            self.openweather_ok, self.smard_ok, self.openweather_latency_ms, self.smard_latency_ms
+        )
+    }
+}
+
+/// Makes the cheapest possible request to each API and reports whether it worked. Nothing
+/// is saved, and the circuit breakers are bypassed so the report reflects the real state.
+pub fn run_health_check(openweather_api_key: Option<&str>, lat: f64, lon: f64) -> HealthReport {
+    // A One Call request costs one call regardless of blocks, but current-only keeps the body tiny.
+    let blocks = OneCallBlocks { current: true, minutely: false, hourly: false, daily: false, alerts: false };
+    let started = Instant::now();
+    let openweather_result = match openweather_api_key {
+        Some(key) => request_openweather_data(key, lat, lon, &blocks, OneCallVersion::V3_0).map(|_| ()).map_err(|e| e.to_string()),
+        None => Err("OPENWEATHER_API_KEY not set".to_string()),
+    };
+    let openweather_latency_ms = started.elapsed().as_millis() as u64;
+
+    // The region index is the smallest SMARD document; we only care that it's served.
+    let url = format!("{}/{}/DE/index_{}.json", SMARD_BASE_URL, SMARD_PRICE_FILTER, SMARD_RESOLUTION);
+    let started = Instant::now();
+    let smard_result = ping_smard(&url);
+    let smard_latency_ms = started.elapsed().as_millis() as u64;
+
+    HealthReport {
+        openweather_ok: openweather_result.is_ok(),
+        smard_ok: smard_result.is_ok(),
+        openweather_latency_ms,
+        smard_latency_ms,
+        openweather_error: openweather_result.err(),
+        smard_error: smard_result.err(),
+    }
+}
+
+// Ok when `url` answers with a success status; the body is not read.
+fn ping_smard(url: &str) -> Result<(), String> {
+    match http::client().and_then(|client| Ok(client.get(url).send()?)) {
+        Ok(response) if response.status().is_success() => Ok(()),
+        Ok(response) => Err(format!("SMARD returned status {}", response.status())),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loopback;
+
+    fn serve_once(status_line: &'static str) -> String {
+        format!("{}/index_hour.json", loopback::serve_once(status_line, "", b"").0)
+    }
+
+    #[test]
+    fn smard_ping_reports_status() {
+        assert_eq!(ping_smard(&serve_once("200 OK")), Ok(()));
+        assert_eq!(ping_smard(&serve_once("503 Service Unavailable")), Err("SMARD returned status 503 Service Unavailable".to_string()));
+    }
+
+    #[test]
+    fn smard_ping_reports_unreachable_host() {
+        assert!(ping_smard(&format!("{}/index_hour.json", loopback::closed_port_url())).is_err());
+    }
+}
@@ -0,0 +1,142 @@
+// src/rust_data_collector/src/history.rs
+
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    budget, circuit, http, CollectorError, OpenWeatherClouds, OpenWeatherHourlyForecast, OpenWeatherOneCallResponse,
+    OpenWeatherWeather,
+};
+
+// --- OpenWeatherMap Historical Lookups ---
+
+const TIMEMACHINE_URL: &str = "https://api.openweathermap.org/data/3.0/onecall/timemachine";
+const TIMEMACHINE_FEATURE: &str = "One Call 3.0 timemachine (historical weather)";
+
+// Observed weather for one past hour. Timemachine records are flat (no `main` block),
+// unlike the current-weather shape.
+#[pyclass(get_all)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoricalWeather {
+    pub dt: i64, // Unix timestamp (seconds)
+    pub temp: f64,
+    pub feels_like: f64,
+    pub humidity: i32,
+    pub clouds: i32, // %
+    #[serde(default)]
+    pub description: String,
+}
+
+#[pymethods]
+impl HistoricalWeather {
+    fn __repr__(&self) -> String {
+        format!(
+            "HistoricalWeather(dt={}, temp={}, feels_like={}, humidity={}, clouds={}, description={:?})",
+            self.dt, self.temp, self.feels_like, self.humidity, self.clouds, self.description
+        )
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TimemachineRecord {
+    dt: i64,
+    temp: f64,
+    feels_like: f64,
+    humidity: i32,
+    clouds: i32,
+    weather: Vec<OpenWeatherWeather>,
+    #[serde(default)]
+    pressure: Option<i32>,
+    #[serde(default)]
+    dew_point: Option<f64>,
+    #[serde(default)]
+    visibility: Option<i32>,
+    #[serde(rename = "rain", default, with = "crate::one_hour_volume")]
+    rain_1h: Option<f64>,
+    #[serde(rename = "snow", default, with = "crate::one_hour_volume")]
+    snow_1h: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TimemachineResponse {
+    data: Vec<TimemachineRecord>,
+}
+
+fn request_timemachine(api_key: &str, lat: f64, lon: f64, dt: i64) -> Result<TimemachineRecord, CollectorError> {
+    let url = format!(
+        "{}?lat={}&lon={}&dt={}&appid={}&units=metric",
+        TIMEMACHINE_URL, lat, lon, dt, api_key
+    );
+    log!("DEBUG (Rust): OpenWeatherMap timemachine Request URL: {}", url);
+    let parsed: TimemachineResponse = circuit::OPENWEATHER.call(|| {
+        // Built first, so a passed deadline doesn't count against the budget.
+        let client = http::client()?;
+        budget::record_openweather_call()?;
+        let response = client.get(&url).send()?;
+        let status = response.status();
+        let content_type = http::content_type(&response);
+        let body = http::read_body(response)?;
+        if let Some(e) = crate::openweather_plan_restriction(status.as_u16(), &body, TIMEMACHINE_FEATURE) {
+            log!("ERROR (Rust): OpenWeatherMap plan does not include timemachine access. Full raw response: {}", body);
+            return Err(e);
+        }
+        if !status.is_success() {
+            log!("ERROR (Rust): OpenWeatherMap timemachine returned non-success status {}. Full raw response: {}", status, body);
+            return Err(CollectorError::Status { status: status.as_u16(), body });
+        }
+        http::expect_json(content_type.as_deref(), &body)?;
+        if let Some(e) = crate::embedded_openweather_error(&body, TIMEMACHINE_FEATURE) {
+            log!("ERROR (Rust): OpenWeatherMap timemachine returned an error body: {}", e);
+            return Err(e);
+        }
+        serde_json::from_str(&body).map_err(|e| CollectorError::Parse { source: e, body })
+    })?;
+
+    parsed.data.into_iter().next().ok_or_else(|| {
+        CollectorError::InvalidInput(format!("OpenWeatherMap has no historical record for dt={}", dt))
+    })
+}
+
+/// Recorded weather at `dt` (Unix seconds) from the One Call 3.0 timemachine endpoint.
+pub fn get_weather_at(api_key: &str, lat: f64, lon: f64, dt: i64) -> Result<HistoricalWeather, CollectorError> {
+    let record = request_timemachine(api_key, lat, lon, dt)?;
+    Ok(HistoricalWeather {
+        dt: record.dt,
+        temp: record.temp,
+        feels_like: record.feels_like,
+        humidity: record.humidity,
+        clouds: record.clouds,
+        description: record.weather.first().map(|w| w.description.clone()).unwrap_or_default(),
+    })
+}
+
+/// Recorded hours from the one containing `start` through `end` (Unix seconds) in the One Call
+/// `hourly` shape, so past windows are saved and merged like forecasts. Costs one call
+/// per hour; `pop` is 0 since there's nothing left to predict.
+pub fn get_hourly_history(
+    api_key: &str,
+    lat: f64,
+    lon: f64,
+    start: i64,
+    end: i64,
+) -> Result<OpenWeatherOneCallResponse, CollectorError> {
+    let first_hour = start - start.rem_euclid(3600);
+    let mut hourly = Vec::new();
+    for dt in (first_hour..=end).step_by(3600) {
+        let record = request_timemachine(api_key, lat, lon, dt)?;
+        hourly.push(OpenWeatherHourlyForecast {
+            dt: record.dt,
+            temp: record.temp,
+            weather: record.weather,
+            pop: 0.0,
+            clouds: OpenWeatherClouds { all: record.clouds },
+            pressure: record.pressure,
+            dew_point: record.dew_point,
+            visibility: record.visibility,
+            rain_1h: record.rain_1h,
+            snow_1h: record.snow_1h,
+            confidence: None,
+        });
+    }
+    Ok(OpenWeatherOneCallResponse { current: None, hourly, minutely: None, daily: None, alerts: None, config_hash: None })
+}
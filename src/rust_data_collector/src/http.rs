@@ -0,0 +1,296 @@
+// src/rust_data_collector/src/http.rs
+
+use reqwest::blocking::{Client, ClientBuilder, Response};
+use std::fmt;
+use std::io::{self, Read};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+use crate::CollectorError;
+
+// --- Client Timeouts ---
+
+// Connecting gets a short budget so an unreachable host fails fast; the overall timeout
+// (connect, headers and body) is longer so slow but progressing downloads can finish.
+pub const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 5;
+pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+static CONNECT_TIMEOUT_SECS: AtomicU64 = AtomicU64::new(DEFAULT_CONNECT_TIMEOUT_SECS);
+static TIMEOUT_SECS: AtomicU64 = AtomicU64::new(DEFAULT_TIMEOUT_SECS);
+
+pub fn set_timeouts(connect_timeout_secs: u64, timeout_secs: u64) {
+    CONNECT_TIMEOUT_SECS.store(connect_timeout_secs, Ordering::Relaxed);
+    TIMEOUT_SECS.store(timeout_secs, Ordering::Relaxed);
+}
+
+// Compressed transfer: reqwest advertises every codec the crate was built with in
+// `Accept-Encoding` and decompresses transparently, so callers always see the plain body.
+// gzip and deflate are always in; brotli comes with the "brotli" feature. zstd isn't
+// offered: reqwest 0.11 has no zstd codec (it arrived in 0.12). The size limit below
+// applies to the decompressed body.
+
+/// A client with the configured timeouts, for every provider request. Under a deadline
+/// (see `with_deadline`) this fails once it has passed, and the timeouts shrink to what's
+/// left of it.
+pub fn client() -> Result<Client, CollectorError> {
+    let remaining = check_deadline()?;
+    let cap = |timeout: Duration| remaining.map_or(timeout, |r| timeout.min(r));
+    Ok(tls_backend(Client::builder())
+        .connect_timeout(cap(Duration::from_secs(CONNECT_TIMEOUT_SECS.load(Ordering::Relaxed))))
+        .timeout(cap(Duration::from_secs(TIMEOUT_SECS.load(Ordering::Relaxed))))
+        .build()?)
+}
+
+// The TLS backend follows the cargo feature the crate was built with (see Cargo.toml).
+#[cfg(feature = "native-tls")]
+fn tls_backend(builder: ClientBuilder) -> ClientBuilder {
+    builder.use_native_tls()
+}
+
+#[cfg(all(feature = "rustls-tls", not(feature = "native-tls")))]
+fn tls_backend(builder: ClientBuilder) -> ClientBuilder {
+    builder.use_rustls_tls()
+}
+
+#[cfg(not(any(feature = "rustls-tls", feature = "native-tls")))]
+compile_error!("enable a TLS backend: the \"rustls-tls\" (default) or \"native-tls\" feature");
+
+// --- Deadlines ---
+
+// A cap on a whole operation (all its requests and retries), unlike the per-request
+// timeouts above. Per thread, so concurrent operations each keep their own; code that fans
+// out to worker threads hands it on with `current_deadline` / `with_deadline`.
+
+#[derive(Debug, Clone, Copy)]
+pub struct Deadline {
+    pub at: Instant,
+    pub limit: Duration,
+}
+
+impl Deadline {
+    pub fn after(limit: Duration) -> Self {
+        Deadline { at: Instant::now() + limit, limit }
+    }
+}
+
+thread_local! {
+    static DEADLINE: Cell<Option<Deadline>> = const { Cell::new(None) };
+}
+
+pub struct DeadlineGuard(Option<Deadline>);
+
+/// Applies `deadline` to this thread's requests until the guard is dropped.
+pub fn with_deadline(deadline: Option<Deadline>) -> DeadlineGuard {
+    DeadlineGuard(DEADLINE.with(|d| d.replace(deadline)))
+}
+
+impl Drop for DeadlineGuard {
+    fn drop(&mut self) {
+        DEADLINE.with(|d| d.set(self.0));
+    }
+}
+
+pub fn current_deadline() -> Option<Deadline> {
+    DEADLINE.with(|d| d.get())
+}
+
+/// Time left before the deadline, `None` without one, or `DeadlineExceeded` once it's up.
+pub fn check_deadline() -> Result<Option<Duration>, CollectorError> {
+    let Some(deadline) = current_deadline() else {
+        return Ok(None);
+    };
+    match deadline.at.checked_duration_since(Instant::now()) {
+        Some(remaining) if !remaining.is_zero() => Ok(Some(remaining)),
+        _ => Err(CollectorError::DeadlineExceeded { limit_secs: deadline.limit.as_secs_f64() }),
+    }
+}
+
+/// `DeadlineExceeded` in place of `error` if the deadline has passed, e.g. when a request
+/// failed because its timeout was cut short by it.
+pub fn deadline_error_or(error: CollectorError) -> CollectorError {
+    match check_deadline() {
+        Err(exceeded) => exceeded,
+        Ok(_) => error,
+    }
+}
+
+// --- Response Size Limits ---
+
+// A misconfigured base URL or a hostile endpoint could otherwise stream an unbounded body
+// into memory. Every response body goes through here.
+pub const DEFAULT_MAX_RESPONSE_BYTES: u64 = 50 * 1024 * 1024;
+
+static MAX_RESPONSE_BYTES: AtomicU64 = AtomicU64::new(DEFAULT_MAX_RESPONSE_BYTES);
+
+pub fn set_max_response_bytes(limit: u64) {
+    MAX_RESPONSE_BYTES.store(limit, Ordering::Relaxed);
+}
+
+pub fn max_response_bytes() -> u64 {
+    MAX_RESPONSE_BYTES.load(Ordering::Relaxed)
+}
+
+// Carried inside the io::Error a `CappedReader` fails with, so the limit survives being
+// wrapped by serde_json.
+#[derive(Debug)]
+struct LimitExceeded(u64);
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "response exceeded {} bytes", self.0)
+    }
+}
+
+impl std::error::Error for LimitExceeded {}
+
+/// Fails with an error instead of reading past `limit` bytes (rather than silently
+/// truncating like `Read::take`, which would surface as a confusing parse error).
+pub struct CappedReader<R> {
+    inner: R,
+    remaining: u64,
+    limit: u64,
+}
+
+impl<R: Read> Read for CappedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // Read one byte past the limit so a body of exactly `limit` bytes still passes.
+        let max = buf.len().min(self.remaining.saturating_add(1).min(usize::MAX as u64) as usize);
+        let n = self.inner.read(&mut buf[..max])?;
+        if n as u64 > self.remaining {
+            return Err(io::Error::other(LimitExceeded(self.limit)));
+        }
+        self.remaining -= n as u64;
+        Ok(n)
+    }
+}
+
+/// `ResponseTooLarge` for errors raised by a `CappedReader`, `Io` for anything else.
+pub fn from_io_error(e: io::Error) -> CollectorError {
+    match e.get_ref().and_then(|inner| inner.downcast_ref::<LimitExceeded>()) {
+        Some(LimitExceeded(limit)) => CollectorError::ResponseTooLarge { limit: *limit },
+        None => CollectorError::Io(e),
+    }
+}
+
+/// The response body as a stream, refused up front when `Content-Length` is over the
+/// limit and cut off once it actually goes over.
+pub fn capped_reader(response: Response) -> Result<CappedReader<Response>, CollectorError> {
+    let limit = max_response_bytes();
+    if response.content_length().is_some_and(|len| len > limit) {
+        return Err(CollectorError::ResponseTooLarge { limit });
+    }
+    Ok(CappedReader { inner: response, remaining: limit, limit })
+}
+
+/// The whole response body as text, within the size limit.
+pub fn read_body(response: Response) -> Result<String, CollectorError> {
+    let mut body = String::new();
+    capped_reader(response)?.read_to_string(&mut body).map_err(from_io_error)?;
+    Ok(body)
+}
+
+// --- Content Types ---
+
+// A captive portal or a proxy's error page answers 200 with HTML, which would otherwise
+// surface as a baffling JSON parse error. Checked on successful responses only; an error
+// status already says more than its page's content type.
+
+const BODY_SNIPPET_CHARS: usize = 200;
+
+/// The response's `Content-Type` header, if it sent a readable one.
+pub fn content_type(response: &Response) -> Option<String> {
+    response.headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+fn is_json(content_type: &str) -> bool {
+    let mime = content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+    mime == "application/json" || mime == "text/json" || mime.ends_with("+json")
+}
+
+fn unexpected_content_type(content_type: &str, body: &str) -> CollectorError {
+    CollectorError::UnexpectedContentType {
+        content_type: content_type.to_string(),
+        body_snippet: body.trim().chars().take(BODY_SNIPPET_CHARS).collect(),
+    }
+}
+
+/// `UnexpectedContentType` unless `content_type` is JSON. A response without the header
+/// gets the benefit of the doubt and is left to the parser.
+pub fn expect_json(content_type: Option<&str>, body: &str) -> Result<(), CollectorError> {
+    match content_type {
+        Some(content_type) if !is_json(content_type) => Err(unexpected_content_type(content_type, body)),
+        _ => Ok(()),
+    }
+}
+
+/// `expect_json` for a response that is parsed as a stream: passes it through unread, or
+/// reads it for the error's snippet.
+pub fn ensure_json(response: Response) -> Result<Response, CollectorError> {
+    match content_type(&response) {
+        Some(content_type) if !is_json(&content_type) => {
+            let body = read_body(response)?;
+            Err(unexpected_content_type(&content_type, &body))
+        }
+        _ => Ok(response),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loopback;
+
+    const PORTAL_PAGE: &str = "<!DOCTYPE html><html><body>Please log in to the hotel Wi-Fi</body></html>";
+
+    fn serve_once(content_type: &'static str, body: &'static str) -> String {
+        loopback::serve_once("200 OK", &format!("Content-Type: {}\r\n", content_type), body.as_bytes()).0
+    }
+
+    #[test]
+    fn html_body_is_unexpected_content_type() {
+        match expect_json(Some("text/html; charset=utf-8"), PORTAL_PAGE) {
+            Err(CollectorError::UnexpectedContentType { content_type, body_snippet }) => {
+                assert_eq!(content_type, "text/html; charset=utf-8");
+                assert_eq!(body_snippet, PORTAL_PAGE);
+            }
+            other => panic!("expected UnexpectedContentType, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn json_types_and_missing_header_pass() {
+        for content_type in [Some("application/json"), Some("Application/JSON; charset=utf-8"), Some("application/geo+json"), None] {
+            assert!(expect_json(content_type, "{}").is_ok(), "{:?}", content_type);
+        }
+    }
+
+    #[test]
+    fn streamed_html_response_is_rejected_before_parsing() {
+        let response = client().unwrap().get(serve_once("text/html", PORTAL_PAGE)).send().unwrap();
+        assert!(matches!(ensure_json(response), Err(CollectorError::UnexpectedContentType { .. })));
+
+        let response = client().unwrap().get(serve_once("application/json", "{\"data\":[]}")).send().unwrap();
+        assert_eq!(read_body(ensure_json(response).unwrap()).unwrap(), "{\"data\":[]}");
+    }
+
+    #[test]
+    fn gzip_response_is_negotiated_and_decompressed() {
+        let (url, server) = loopback::serve_once(
+            "200 OK",
+            "Content-Type: application/json\r\nContent-Encoding: gzip\r\n",
+            include_bytes!("fixtures/prices_sample.json.gz"),
+        );
+        let body = read_body(client().unwrap().get(url).send().unwrap()).unwrap();
+        assert_eq!(body, include_str!("fixtures/prices_sample.json"));
+
+        let request = server.join().unwrap();
+        let accept_encoding = loopback::header(&request, "accept-encoding").expect("Accept-Encoding sent");
+        assert!(accept_encoding.contains("gzip") && accept_encoding.contains("deflate"), "{}", accept_encoding);
+        #[cfg(feature = "brotli")]
+        assert!(accept_encoding.contains("br"), "{}", accept_encoding);
+    }
+}
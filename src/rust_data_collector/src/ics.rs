@@ -0,0 +1,104 @@
+// src/rust_data_collector/src/ics.rs
+
+use chrono::{DateTime, TimeZone, Utc};
+use chrono_tz::Europe::Berlin;
+
+use crate::CollectorError;
+
+// --- iCalendar Export ---
+
+// Cheap windows as calendar events (RFC 5545), so a calendar app can subscribe to "when to
+// run the dishwasher". Times are German local time with a VTIMEZONE block for it, which
+// clients need to place TZID-qualified times correctly across DST changes.
+
+const PRODID: &str = "-//smart_energy_optimizer//cheap windows//EN";
+
+// Europe/Berlin since the EU-wide DST rules of 1996: CEST from the last Sunday in March,
+// CET from the last Sunday in October.
+const BERLIN_VTIMEZONE: &str = "BEGIN:VTIMEZONE\r\n\
+TZID:Europe/Berlin\r\n\
+BEGIN:DAYLIGHT\r\n\
+TZOFFSETFROM:+0100\r\n\
+TZOFFSETTO:+0200\r\n\
+TZNAME:CEST\r\n\
+DTSTART:19700329T020000\r\n\
+RRULE:FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU\r\n\
+END:DAYLIGHT\r\n\
+BEGIN:STANDARD\r\n\
+TZOFFSETFROM:+0200\r\n\
+TZOFFSETTO:+0100\r\n\
+TZNAME:CET\r\n\
+DTSTART:19701025T030000\r\n\
+RRULE:FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU\r\n\
+END:STANDARD\r\n\
+END:VTIMEZONE\r\n";
+
+// TEXT values escape backslash, semicolon, comma and newlines.
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+// Content lines longer than 75 octets continue on the next line after a leading space,
+// split on character boundaries so multi-byte text stays valid UTF-8.
+fn fold_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+fn berlin_time(timestamp_ms: i64) -> Result<DateTime<chrono_tz::Tz>, CollectorError> {
+    Utc.timestamp_millis_opt(timestamp_ms)
+        .single()
+        .map(|t| t.with_timezone(&Berlin))
+        .ok_or_else(|| CollectorError::InvalidInput(format!("invalid timestamp {} (expected epoch milliseconds)", timestamp_ms)))
+}
+
+/// A VCALENDAR with one VEVENT per `(start_ms, end_ms, mean EUR/MWh)` window, summarised
+/// as e.g. "Run dishwasher 02:00–04:00 · 4.1 ct/kWh". `now` is the DTSTAMP of every event.
+/// UIDs derive from the window, so re-exporting the same window updates the same event.
+pub fn to_ics(windows: &[(i64, i64, f64)], title_prefix: &str, now: DateTime<Utc>) -> Result<String, CollectorError> {
+    let mut ics = String::from("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n");
+    ics.push_str(&fold_line(&format!("PRODID:{}", PRODID)));
+    ics.push_str("CALSCALE:GREGORIAN\r\n");
+    ics.push_str(BERLIN_VTIMEZONE);
+
+    let stamp = now.format("%Y%m%dT%H%M%SZ");
+    for &(start_ms, end_ms, price) in windows {
+        if end_ms <= start_ms {
+            return Err(CollectorError::InvalidInput(format!(
+                "window end ({}) must be after its start ({})",
+                end_ms, start_ms
+            )));
+        }
+        let (start, end) = (berlin_time(start_ms)?, berlin_time(end_ms)?);
+        let summary = format!(
+            "{} {}–{} · {:.1} ct/kWh",
+            title_prefix,
+            start.format("%H:%M"),
+            end.format("%H:%M"),
+            price / 10.0 // EUR/MWh -> ct/kWh
+        );
+        ics.push_str("BEGIN:VEVENT\r\n");
+        ics.push_str(&fold_line(&format!("UID:{}-{}@smart_energy_optimizer", start_ms, end_ms)));
+        ics.push_str(&format!("DTSTAMP:{}\r\n", stamp));
+        ics.push_str(&format!("DTSTART;TZID=Europe/Berlin:{}\r\n", start.format("%Y%m%dT%H%M%S")));
+        ics.push_str(&format!("DTEND;TZID=Europe/Berlin:{}\r\n", end.format("%Y%m%dT%H%M%S")));
+        ics.push_str(&fold_line(&format!("SUMMARY:{}", escape_text(summary.trim_start()))));
+        ics.push_str("TRANSP:TRANSPARENT\r\n");
+        ics.push_str("END:VEVENT\r\n");
+    }
+    ics.push_str("END:VCALENDAR\r\n");
+    Ok(ics)
+}
@@ -0,0 +1,79 @@
+// src/rust_data_collector/src/influx.rs
+
+use std::fmt::Write as _;
+
+use crate::merge::HourlyPoint;
+use crate::{http, secrets, CollectorError, SmardApiResponse};
+
+// --- InfluxDB Export ---
+
+// Writes prices and the aligned hours to an InfluxDB 2.x bucket in line protocol, for
+// Grafana dashboards. Points are keyed by measurement and time, so writing an hour again
+// overwrites it. Connection details come from the secrets file or the environment like
+// the API keys: INFLUX_URL, INFLUX_TOKEN, INFLUX_ORG and INFLUX_BUCKET.
+
+#[derive(Debug, Clone)]
+pub struct InfluxConfig {
+    pub url: String, // e.g. "http://localhost:8086"
+    pub token: String,
+    pub org: String,
+    pub bucket: String,
+}
+
+impl InfluxConfig {
+    pub fn load(secrets_file: Option<&str>) -> Result<Self, CollectorError> {
+        Ok(InfluxConfig {
+            url: secrets::get_secret("INFLUX_URL", secrets_file)?,
+            token: secrets::get_secret("INFLUX_TOKEN", secrets_file)?,
+            org: secrets::get_secret("INFLUX_ORG", secrets_file)?,
+            bucket: secrets::get_secret("INFLUX_BUCKET", secrets_file)?,
+        })
+    }
+}
+
+// Tag values escape commas, spaces and equals signs.
+fn escape_tag(value: &str) -> String {
+    value.replace(',', "\\,").replace(' ', "\\ ").replace('=', "\\=")
+}
+
+/// `price` (EUR/MWh, tagged by region) and `weather` lines at second precision. Unpriced
+/// points and hours are left out rather than written as empty fields.
+pub fn line_protocol(prices: Option<&SmardApiResponse>, region: &str, hourly: &[HourlyPoint]) -> String {
+    let mut lines = String::new();
+    for dp in prices.iter().flat_map(|p| p.data.iter()) {
+        if let Some(value) = dp.value {
+            let _ = writeln!(lines, "price,region={} value={} {}", escape_tag(region), value, dp.timestamp / 1000);
+        }
+    }
+    for p in hourly {
+        let mut fields = format!("temp={},clouds={}i,pop={}", p.temp, p.clouds, p.pop);
+        for (name, value) in [("rain_1h", p.rain_1h), ("snow_1h", p.snow_1h), ("ghi", p.ghi), ("price", p.price)] {
+            if let Some(value) = value {
+                let _ = write!(fields, ",{}={}", name, value);
+            }
+        }
+        let _ = writeln!(lines, "weather {} {}", fields, p.timestamp);
+    }
+    lines
+}
+
+pub fn write_lines(config: &InfluxConfig, lines: String) -> Result<(), CollectorError> {
+    if lines.is_empty() {
+        return Ok(());
+    }
+    let url = format!("{}/api/v2/write", config.url.trim_end_matches('/'));
+    let response = http::client()?
+        .post(url)
+        .query(&[("org", config.org.as_str()), ("bucket", config.bucket.as_str()), ("precision", "s")])
+        .header("Authorization", format!("Token {}", config.token))
+        .header("Content-Type", "text/plain; charset=utf-8")
+        .body(lines)
+        .send()?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = http::read_body(response)?;
+        log!("ERROR (Rust): InfluxDB write returned non-success status {}. Full raw response: {}", status, body);
+        return Err(CollectorError::Status { status: status.as_u16(), body });
+    }
+    Ok(())
+}
@@ -1,14 +1,81 @@
 // src/rust_data_collector/src/lib.rs
 
-use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
-use chrono::{Utc, Duration};
-use dotenv::dotenv;
-use std::env;
-use std::fs;
-use std::path::Path;
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use chrono_tz::Europe::Berlin;
+use std::collections::HashMap;
+use std::io::BufReader;
+use std::path::PathBuf;
 use pyo3::prelude::*;
 
+// Every diagnostic line goes through here rather than straight to `println!`, so output
+// written to stdout (see `output::OutputTarget`) stays clean.
+macro_rules! log {
+    ($($arg:tt)*) => {
+        $crate::output::log_line(&format!($($arg)*))
+    };
+}
+
+pub mod alerts;
+pub mod backfill;
+pub mod battery;
+pub mod briefing;
+pub mod budget;
+pub mod cache;
+pub mod carbon;
+pub mod circuit;
+pub mod clock;
+pub mod coverage;
+pub mod diff;
+mod error;
+pub mod geocode;
+pub mod health;
+pub mod history;
+pub mod http;
+pub mod ics;
+#[cfg(feature = "live")]
+pub mod live;
+#[cfg(test)]
+mod loopback;
+pub mod merge;
+pub mod outlook;
+pub mod output;
+pub mod pipeline;
+pub mod preflight;
+pub mod prices;
+pub mod provenance;
+pub mod pv;
+pub mod quality;
+pub mod refresh;
+pub mod resample;
+pub mod rolling;
+pub mod savings;
+pub mod secrets;
+pub mod selftest;
+pub mod smard;
+pub mod smard_csv;
+pub mod solar;
+pub mod spreads;
+pub mod storage;
+pub mod store;
+pub mod summary;
+pub mod timestamp;
+pub mod units;
+pub mod validate;
+pub mod weather;
+pub mod window;
+#[cfg(feature = "xlsx")]
+pub mod xlsx;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+#[cfg(feature = "influx")]
+pub mod influx;
+#[cfg(feature = "mqtt")]
+pub mod mqtt;
+
+pub use error::{exceptions, CollectorError};
+pub use smard::{parse_smard_reader, parse_smard_response};
+
 // --- Data Structures for API Responses ---
 
 // OpenWeatherMap Current Weather (simplified)
@@ -24,6 +91,8 @@ pub struct OpenWeatherMain {
     pub temp: f64,
     pub feels_like: f64,
     pub humidity: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pressure: Option<i32>, // Sea-level pressure, hPa
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -38,8 +107,25 @@ pub struct OpenWeatherHourlyForecast {
     pub dt: i64, // Unix timestamp
     pub temp: f64,
     pub weather: Vec<OpenWeatherWeather>,
+    #[serde(default)] // Older One Call 2.5 responses can omit it
     pub pop: f64, // Probability of precipitation
     pub clouds: OpenWeatherClouds,
+    // Not in every response (older 2.5 payloads, some timemachine records), hence optional.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pressure: Option<i32>, // hPa
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dew_point: Option<f64>, // °C
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<i32>, // m, capped at 10 km by OpenWeatherMap
+    // Precipitation volume over the hour, mm. OpenWeatherMap nests it as `{"1h": 0.5}` and
+    // leaves the key out entirely when there is none.
+    #[serde(rename = "rain", default, with = "one_hour_volume", skip_serializing_if = "Option::is_none")]
+    pub rain_1h: Option<f64>,
+    #[serde(rename = "snow", default, with = "one_hour_volume", skip_serializing_if = "Option::is_none")]
+    pub snow_1h: Option<f64>,
+    // Not from the API: our reliability estimate for this hour, see `quality::forecast_confidence`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f64>,
     // Note: OpenWeatherMap's hourly forecast doesn't directly give solar irradiance
     // For a more accurate solar prediction, a dedicated solar API (like Solcast, Meteotest)
     // or a sophisticated solar model based on cloud cover, time of day, season, etc., is needed.
@@ -51,78 +137,400 @@ pub struct OpenWeatherClouds {
     pub all: i32, // Cloudiness, %
 }
 
+// Maps `{"1h": x}` to `Some(x)` and back, so the saved JSON keeps OpenWeatherMap's shape.
+mod one_hour_volume {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct Volume {
+        #[serde(rename = "1h")]
+        one_hour: Option<f64>,
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<f64>, D::Error> {
+        Ok(Option::<Volume>::deserialize(deserializer)?.and_then(|v| v.one_hour))
+    }
+
+    pub fn serialize<S: Serializer>(value: &Option<f64>, serializer: S) -> Result<S::Ok, S::Error> {
+        value.map(|one_hour| Volume { one_hour: Some(one_hour) }).serialize(serializer)
+    }
+}
+
 // Wrapper for OpenWeatherMap One Call API response
+// Every block can be excluded from the request (see `OneCallBlocks`), so every block is
+// optional here. daily/minutely/alerts are passed through untyped until something needs them.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct OpenWeatherOneCallResponse {
-    pub current: OpenWeatherCurrent,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current: Option<OpenWeatherCurrent>,
+    #[serde(default)]
     pub hourly: Vec<OpenWeatherHourlyForecast>,
-    // daily, alerts, minutely etc. can be added if needed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minutely: Option<Vec<serde_json::Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily: Option<Vec<serde_json::Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alerts: Option<Vec<serde_json::Value>>,
+    // Not from the API: added when saving, see `provenance`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_hash: Option<String>,
+}
+
+// Which One Call blocks to request. Anything not requested goes into `exclude=`.
+#[derive(Debug, Clone, Copy)]
+pub struct OneCallBlocks {
+    pub current: bool,
+    pub minutely: bool,
+    pub hourly: bool,
+    pub daily: bool,
+    pub alerts: bool,
+}
+
+impl Default for OneCallBlocks {
+    // current + hourly, which is what the dashboard has always used
+    fn default() -> Self {
+        OneCallBlocks { current: true, minutely: false, hourly: true, daily: false, alerts: false }
+    }
+}
+
+// One Call endpoint version. Subscriptions from before 3.0 only have access to 2.5,
+// whose response layout is the same for the blocks we parse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OneCallVersion {
+    V3_0,
+    V2_5,
+}
+
+impl OneCallVersion {
+    pub fn parse(version: &str) -> Option<Self> {
+        match version {
+            "3.0" => Some(OneCallVersion::V3_0),
+            "2.5" => Some(OneCallVersion::V2_5),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            OneCallVersion::V3_0 => "3.0",
+            OneCallVersion::V2_5 => "2.5",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct OneCallOptions {
+    pub blocks: OneCallBlocks,
+    pub api_version: OneCallVersion,
+    // Retry on 2.5 when 3.0 answers 401, for keys that predate the 3.0 subscription.
+    pub fallback_to_v2_5: bool,
+}
+
+impl Default for OneCallOptions {
+    fn default() -> Self {
+        OneCallOptions {
+            blocks: OneCallBlocks::default(),
+            api_version: OneCallVersion::V3_0,
+            fallback_to_v2_5: false,
+        }
+    }
+}
+
+impl OneCallBlocks {
+    /// Comma-separated `exclude` value, e.g. `"minutely,daily,alerts"`; empty if nothing is excluded.
+    pub fn exclude_param(&self) -> String {
+        [
+            ("current", self.current),
+            ("minutely", self.minutely),
+            ("hourly", self.hourly),
+            ("daily", self.daily),
+            ("alerts", self.alerts),
+        ]
+        .iter()
+        .filter(|(_, wanted)| !wanted)
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>()
+        .join(",")
+    }
 }
 
 // SMARD API (Day-ahead auction price)
 // Example SMARD JSON: {"data":[{"timestamp":1672531200000,"value":-0.01},{"timestamp":...}]}
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SmardDataPoint {
-    pub timestamp: i64, // Milliseconds since epoch
-    pub value: f64,     // Price in EUR/MWh
+    pub timestamp: i64,     // Milliseconds since epoch
+    pub value: Option<f64>, // Price in EUR/MWh; SMARD sends null for hours not published yet
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SmardApiResponse {
     pub data: Vec<SmardDataPoint>,
+    // Set when the filter was declared to publish in another unit; `data` has already been
+    // converted to EUR/MWh, see `units::normalize_price_unit`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_unit: Option<String>,
+    // Settings that produced the saved file, see `provenance`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_hash: Option<String>,
+}
+
+// Tomorrow's day-ahead auction is published around 13:00 CET. Before that the window
+// exists but holds no values, which is not the same thing as the fetch failing.
+#[derive(Debug)]
+pub enum TomorrowPrices {
+    Published(SmardApiResponse),
+    NotYetPublished,
+}
+
+// Today's and tomorrow's day-ahead prices as one series. Everything from
+// `tomorrow_start_ms` on comes from the auction for tomorrow; while that isn't published,
+// the series ends with today and `tomorrow_published` is false.
+#[pyclass(get_all)]
+#[derive(Debug, Clone)]
+pub struct MultiDayPrices {
+    pub prices: Vec<(i64, Option<f64>)>, // (timestamp_ms, EUR/MWh), in timestamp order
+    pub tomorrow_start_ms: i64,           // Berlin midnight
+    pub tomorrow_published: bool,
+}
+
+#[pymethods]
+impl MultiDayPrices {
+    fn __repr__(&self) -> String {
+        format!(
+            "MultiDayPrices(prices={}, tomorrow_start_ms={}, tomorrow_published={})",
+            self.prices.len(), self.tomorrow_start_ms, self.tomorrow_published
+        )
+    }
+}
+
+// Outcome of a full fetch-and-save run. Warnings are non-fatal problems spotted along
+// the way (e.g. a stale SMARD feed) that the caller should still hear about.
+#[pyclass(get_all)]
+#[derive(Debug, Clone)]
+pub struct FetchResult {
+    pub weather_path: PathBuf,
+    pub smard_path: PathBuf,
+    pub warnings: Vec<quality::Warning>, // `{"code", "message"}` dicts in Python
+    // What was written, parsed: weather and prices merged per hour, and the price series.
+    pub hourly: Vec<merge::HourlyPoint>,
+    pub prices: Vec<(i64, Option<f64>)>,
+    // `(start_ms, end_ms, count, expected_count)` actually obtained, see `coverage::coverage`.
+    pub weather_coverage: Option<(i64, i64, usize, usize)>,
+    pub prices_coverage: Option<(i64, i64, usize, usize)>,
+    // Also written into both files, see `provenance::RunConfig`.
+    pub config_hash: String,
+}
+
+#[pymethods]
+impl FetchResult {
+    fn __repr__(&self) -> String {
+        format!(
+            "FetchResult(weather_path={:?}, smard_path={:?}, hours={}, prices={}, warnings={}, weather_coverage={:?}, prices_coverage={:?}, config_hash={:?})",
+            self.weather_path, self.smard_path, self.hourly.len(), self.prices.len(), self.warnings.len(),
+            self.weather_coverage, self.prices_coverage, self.config_hash
+        )
+    }
 }
 
 
 // --- Functions to Fetch Data ---
 
-fn get_openweather_data(api_key: &str, lat: f64, lon: f64) -> Result<OpenWeatherOneCallResponse, reqwest::Error> {
+fn get_openweather_data(
+    api_key: &str,
+    lat: f64,
+    lon: f64,
+    options: &OneCallOptions,
+) -> Result<OpenWeatherOneCallResponse, CollectorError> {
+    let cache_key = one_call_cache_key(lat, lon, options);
+    if let Some(body) = cache::weather().get(&cache_key) {
+        log!("DEBUG (Rust): OpenWeatherMap response served from cache ({}).", cache_key);
+        return parse_openweather_body(body);
+    }
+    refresh_openweather_data(api_key, lat, lon, options)
+}
+
+// Fetches past the cache and stores the response, replacing any cached one.
+fn refresh_openweather_data(
+    api_key: &str,
+    lat: f64,
+    lon: f64,
+    options: &OneCallOptions,
+) -> Result<OpenWeatherOneCallResponse, CollectorError> {
+    let body = circuit::OPENWEATHER.call(|| {
+        match request_openweather_body(api_key, lat, lon, &options.blocks, options.api_version) {
+            Err(
+                CollectorError::Status { status: 401, .. }
+                | CollectorError::Api { code: 401, .. }
+                | CollectorError::PlanRestricted { .. },
+            ) if options.fallback_to_v2_5 && options.api_version == OneCallVersion::V3_0 => {
+                log!("DEBUG (Rust): One Call 3.0 rejected the API key (401), retrying on 2.5...");
+                request_openweather_body(api_key, lat, lon, &options.blocks, OneCallVersion::V2_5)
+            }
+            result => result,
+        }
+    })?;
+    // Parse before caching so a malformed body is never served again.
+    let parsed = parse_openweather_body(body.clone())?;
+    cache::weather().insert(one_call_cache_key(lat, lon, options), body);
+    Ok(parsed)
+}
+
+// Everything that changes the response, minus the API key. Built from the same query as
+// the request, so a response fetched without e.g. `daily` never answers a request for it.
+fn one_call_cache_key(lat: f64, lon: f64, options: &OneCallOptions) -> String {
+    format!("onecall/{}?{}", options.api_version.as_str(), one_call_query(lat, lon, &options.blocks))
+}
+
+// Query parameters of a One Call request except `appid`.
+fn one_call_query(lat: f64, lon: f64, blocks: &OneCallBlocks) -> String {
+    let mut query = format!("lat={}&lon={}&units=metric", lat, lon);
+    let exclude = blocks.exclude_param();
+    if !exclude.is_empty() {
+        query.push_str(&format!("&exclude={}", exclude));
+    }
+    query
+}
+
+// Uncached, unguarded single request (used by the health check).
+fn request_openweather_data(
+    api_key: &str,
+    lat: f64,
+    lon: f64,
+    blocks: &OneCallBlocks,
+    api_version: OneCallVersion,
+) -> Result<OpenWeatherOneCallResponse, CollectorError> {
+    parse_openweather_body(request_openweather_body(api_key, lat, lon, blocks, api_version)?)
+}
+
+fn request_openweather_body(
+    api_key: &str,
+    lat: f64,
+    lon: f64,
+    blocks: &OneCallBlocks,
+    api_version: OneCallVersion,
+) -> Result<String, CollectorError> {
     let url = format!(
-        "https://api.openweathermap.org/data/3.0/onecall?lat={}&lon={}&exclude=minutely,daily,alerts&appid={}&units=metric",
-        lat, lon, api_key
+        "https://api.openweathermap.org/data/{}/onecall?{}&appid={}",
+        api_version.as_str(), one_call_query(lat, lon, blocks), api_key
     );
-    println!("DEBUG (Rust): OpenWeatherMap API Request URL: {}", url);
-    let client = Client::new();
+    log!("DEBUG (Rust): OpenWeatherMap API Request URL: {}", url);
+    // Built first, so a passed deadline doesn't count against the budget.
+    let client = http::client()?;
+    let calls_today = budget::record_openweather_call()?;
+    log!("DEBUG (Rust): OpenWeatherMap calls used today: {}", calls_today);
     let response = client.get(&url).send()?; // This sends the request and gets the reqwest::blocking::Response object
 
-    // --- Corrected Debug Block and Error Handling ---
     let status = response.status(); // Access status BEFORE consuming the response body
-    println!("DEBUG (Rust): OpenWeatherMap Response Status: {}", status);
+    log!("DEBUG (Rust): OpenWeatherMap Response Status: {}", status);
+    let content_type = http::content_type(&response);
 
     // Consume the response body into text
-    let response_text = response.text()?; // .text() consumes the response, so we need to clone if we wanted to read it multiple times (not needed here)
-    println!("DEBUG (Rust): OpenWeatherMap Raw Response (first 500 chars): {}", &response_text[..std::cmp::min(response_text.len(), 500)]);
+    let response_text = http::read_body(response)?;
+    log!("DEBUG (Rust): OpenWeatherMap Raw Response (first 500 chars): {}", &response_text[..std::cmp::min(response_text.len(), 500)]);
 
-    // Check for non-200 status codes. reqwest's `error_for_status()` is the idiomatic way.
-    // If the status is 4xx or 5xx, this will convert it into a reqwest::Error.
-    let response_for_status = response_text.clone(); // Clone to use for potential error reporting
-    let mut response_result = Ok(()); // Dummy result to build upon
-
-    // Manually check status and build custom error if not success
+    let feature = format!("One Call {}", api_version.as_str());
+    if let Some(e) = openweather_plan_restriction(status.as_u16(), &response_text, &feature) {
+        log!("ERROR (Rust): OpenWeatherMap plan does not include {}. Full raw response: {}", feature, response_text);
+        return Err(e);
+    }
+    // Manually check status and keep the full body in the error for debugging
     if !status.is_success() {
-        println!("ERROR (Rust): OpenWeatherMap API returned non-success status {}. Full raw response: {}", status, response_text);
-        return Err(reqwest::Error::builder()
-            .status(status)
-            .text(response_text) // Include the full text for debugging
-            .build());
+        log!("ERROR (Rust): OpenWeatherMap API returned non-success status {}. Full raw response: {}", status, response_text);
+        return Err(CollectorError::Status {
+            status: status.as_u16(),
+            body: response_text,
+        });
+    }
+    http::expect_json(content_type.as_deref(), &response_text)?;
+    if let Some(e) = embedded_openweather_error(&response_text, &feature) {
+        log!("ERROR (Rust): OpenWeatherMap returned an error body with status {}: {}", status, e);
+        return Err(e);
+    }
+    Ok(response_text)
+}
+
+// OpenWeatherMap answers 401 both for a bad key and for a valid key whose plan doesn't
+// include the endpoint (One Call 3.0 needs its own subscription); only the message tells
+// them apart. A bad key is always "Invalid API key", a missing subscription names the plan.
+fn is_plan_restriction(status: u16, message: &str) -> bool {
+    let message = message.to_lowercase();
+    matches!(status, 401 | 403)
+        && !message.contains("invalid api key")
+        && ["subscription", "plan", "upgrade"].iter().any(|word| message.contains(word))
+}
+
+/// `PlanRestricted` naming `feature` when a status and body are OpenWeatherMap's answer for
+/// an endpoint outside the key's plan.
+pub(crate) fn openweather_plan_restriction(status: u16, body: &str, feature: &str) -> Option<CollectorError> {
+    is_plan_restriction(status, body).then(|| CollectorError::PlanRestricted { feature: feature.to_string(), body: body.to_string() })
+}
+
+// OpenWeatherMap sometimes answers 200 with `{"cod": 401, "message": "Invalid API key"}`
+// instead of a real status; `cod` may be a number or a string. Successful One Call
+// responses have no `cod` at all. A plan restriction comes back as such, naming `feature`.
+pub(crate) fn embedded_openweather_error(body: &str, feature: &str) -> Option<CollectorError> {
+    #[derive(Deserialize)]
+    struct ErrorBody {
+        cod: serde_json::Value,
+        message: String,
     }
 
-    // Now, attempt to deserialize the text
-    let parsed_response: OpenWeatherOneCallResponse = serde_json::from_str(&response_text)
+    let error: ErrorBody = serde_json::from_str(body).ok()?;
+    let code = match &error.cod {
+        serde_json::Value::Number(n) => n.as_u64()?,
+        serde_json::Value::String(s) => s.parse().ok()?,
+        _ => return None,
+    };
+    if code == 200 {
+        return None;
+    }
+    let code = u16::try_from(code).ok()?;
+    if is_plan_restriction(code, &error.message) {
+        return Some(CollectorError::PlanRestricted { feature: feature.to_string(), body: body.to_string() });
+    }
+    Some(CollectorError::Api { code, message: error.message })
+}
+
+fn parse_openweather_body(response_text: String) -> Result<OpenWeatherOneCallResponse, CollectorError> {
+    let mut response: OpenWeatherOneCallResponse = serde_json::from_str(&response_text)
         .map_err(|e| {
             // If deserialization fails, print the full response text for more context
-            println!("ERROR (Rust): Failed to deserialize OpenWeatherMap response. Error: {}", e);
-            println!("ERROR (Rust): Full raw response was: {}", response_text); // CRUCIAL: Full response on error
-            
-            // Build a reqwest::Error for deserialization failure using its builder.
-            // reqwest::Error::builder().build() creates a generic error.
-            reqwest::Error::builder()
-                .text(response_text) // Include the response text for debugging
-                .build() // This creates a generic reqwest::Error, kind will be "Unknown"
+            log!("ERROR (Rust): Failed to deserialize OpenWeatherMap response. Error: {}", e);
+            log!("ERROR (Rust): Full raw response was: {}", response_text); // CRUCIAL: Full response on error
+            CollectorError::Parse { source: e, body: response_text.clone() }
         })?;
+    quality::drop_duplicate_hours(&mut response.hourly);
+    quality::sort_hourly(&mut response.hourly);
+    // Relative to now rather than fetch time, so cached responses age correctly.
+    quality::attach_confidence(&mut response.hourly, clock::now());
+    Ok(response)
+}
 
-    Ok(parsed_response)
+// Guards the SMARD filter against windows that would silently match nothing.
+fn validate_time_range(start_timestamp_ms: i64, end_timestamp_ms: i64) -> Result<(), CollectorError> {
+    for (name, ts) in [("start", start_timestamp_ms), ("end", end_timestamp_ms)] {
+        // Below this it is almost certainly seconds (off by ~1000x) passed as milliseconds.
+        if ts < timestamp::MIN_PLAUSIBLE_TIMESTAMP_MS {
+            return Err(CollectorError::InvalidTimeRange(format!(
+                "{} timestamp {} looks like seconds, not milliseconds (did you mean {}?)",
+                name, ts, ts * 1000
+            )));
+        }
+    }
+    if start_timestamp_ms >= end_timestamp_ms {
+        return Err(CollectorError::InvalidTimeRange(format!(
+            "start_timestamp_ms ({}) must be before end_timestamp_ms ({})",
+            start_timestamp_ms, end_timestamp_ms
+        )));
+    }
+    Ok(())
 }
 
+// SMARD endpoint settings for the day-ahead auction price (public data, no API key needed).
+const SMARD_BASE_URL: &str = "https://www.smard.de/app/chart_data";
+const SMARD_PRICE_FILTER: &str = "1001";
+const SMARD_RESOLUTION: &str = "hour";
+
 // For SMARD, we will fetch data for the last 48 hours for demonstration.
 // SMARD API data URLs are typically structured like this for 'Day-ahead auction price' (filter 1001):
 // https://www.smard.de/app/chart_data/1001/DE/index_hour.json
@@ -138,7 +546,24 @@ fn get_smard_day_ahead_prices(
     resolution: &str,
     start_timestamp_ms: i64,
     end_timestamp_ms: i64
-) -> Result<SmardApiResponse, reqwest::Error> {
+) -> Result<SmardApiResponse, CollectorError> {
+    get_smard_series(base_url, filter, smard::SmardSeriesKind::Price, region, resolution, start_timestamp_ms, end_timestamp_ms)
+}
+
+/// Any SMARD series. `kind` declares what `filter` is expected to measure; a known filter
+/// of a different kind is rejected before anything is fetched.
+pub fn get_smard_series(
+    base_url: &str,
+    filter: &str,
+    kind: smard::SmardSeriesKind,
+    region: &str,
+    resolution: &str,
+    start_timestamp_ms: i64,
+    end_timestamp_ms: i64
+) -> Result<SmardApiResponse, CollectorError> {
+    kind.validate_filter(filter)?;
+    validate_time_range(start_timestamp_ms, end_timestamp_ms)?;
+
     // SMARD's chart_data endpoint doesn't support direct time range queries.
     // It provides data up to "index_hour.json".
     // To get historical data, one typically downloads CSVs from their "Data download" section.
@@ -148,74 +573,2089 @@ fn get_smard_day_ahead_prices(
     
     // For simplicity, let's fetch the general hourly index, which usually contains recent data.
     // Note: The specific URL format for historical data ranges might differ or require manual download.
-    let url = format!("{}/{}/{}/index_{}.json", base_url, filter, region, resolution);
-    println!("Fetching SMARD data from: {}", url); // Debug print
-    let client = Client::new();
-    let response = client.get(&url).send()?.json::<SmardApiResponse>()?;
-
-    // Filter data by timestamp in Rust, as SMARD `index_hour.json` returns all available data.
-    let filtered_data: Vec<SmardDataPoint> = response.data.into_iter()
-        .filter(|dp| dp.timestamp >= start_timestamp_ms && dp.timestamp <= end_timestamp_ms)
-        .collect();
+    let url = smard_index_url(base_url, filter, region, resolution);
+    if let Some(data) = cache::smard().get_with(&url, |c| c.window(start_timestamp_ms, end_timestamp_ms)) {
+        log!("DEBUG (Rust): SMARD data served from cache ({}).", url);
+        return Ok(SmardApiResponse { data, source_unit: None, config_hash: None });
+    }
+    refresh_smard_index(url, start_timestamp_ms, end_timestamp_ms)
+}
+
+fn smard_index_url(base_url: &str, filter: &str, region: &str, resolution: &str) -> String {
+    format!("{}/{}/{}/index_{}.json", base_url, filter, region, resolution)
+}
 
-    Ok(SmardApiResponse { data: filtered_data })
+// Fetches past the cache and stores the window, replacing any cached one.
+fn refresh_smard_index(url: String, start_timestamp_ms: i64, end_timestamp_ms: i64) -> Result<SmardApiResponse, CollectorError> {
+    log!("Fetching SMARD data from: {}", url); // Debug print
+    let response = circuit::SMARD.call(|| {
+        let response = http::client()?.get(&url).send()?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = http::read_body(response)?;
+            return Err(CollectorError::Status { status: status.as_u16(), body });
+        }
+        // Parse straight off the wire instead of buffering the (potentially multi-MB) body.
+        let response = http::ensure_json(response)?;
+        smard::parse_smard_reader(BufReader::new(http::capped_reader(response)?), start_timestamp_ms, end_timestamp_ms)
+    })?;
+    cache::smard().insert(url, cache::CachedPrices {
+        start_ms: start_timestamp_ms,
+        end_ms: end_timestamp_ms,
+        data: response.data.clone(),
+    });
+    Ok(response)
+}
+
+// The auction covers the German local day, so a day is midnight-to-midnight in
+// Europe/Berlin (23 or 25 hours long on DST change days), not a UTC day.
+fn local_days_window_ms(first: NaiveDate, days: i64) -> (i64, i64) {
+    let start = prices::berlin_midnight(first);
+    let end = prices::berlin_midnight(first + Duration::days(days));
+    (start.timestamp_millis(), end.timestamp_millis() - 1)
+}
+
+fn tomorrow_window_ms(now: DateTime<Utc>) -> (i64, i64) {
+    local_days_window_ms(now.with_timezone(&Berlin).date_naive() + Duration::days(1), 1)
+}
+
+pub fn fetch_tomorrow_prices(region: &str) -> Result<TomorrowPrices, CollectorError> {
+    let (start_timestamp_ms, end_timestamp_ms) = tomorrow_window_ms(clock::now());
+    let response = get_smard_day_ahead_prices(
+        SMARD_BASE_URL,
+        SMARD_PRICE_FILTER,
+        region,
+        SMARD_RESOLUTION,
+        start_timestamp_ms,
+        end_timestamp_ms
+    )?;
+
+    if response.data.iter().all(|dp| dp.value.is_none()) {
+        log!("DEBUG (Rust): Tomorrow's day-ahead auction for {} is not published yet.", region);
+        return Ok(TomorrowPrices::NotYetPublished);
+    }
+    Ok(TomorrowPrices::Published(response))
+}
+
+/// Today's prices and, once the auction is published, tomorrow's, from one SMARD request.
+/// Tomorrow's still-empty hours are dropped rather than returned as gaps.
+pub fn fetch_multi_day_prices(region: &str, now: DateTime<Utc>) -> Result<MultiDayPrices, CollectorError> {
+    let today = now.with_timezone(&Berlin).date_naive();
+    let (start_timestamp_ms, end_timestamp_ms) = local_days_window_ms(today, 2);
+    let tomorrow_start_ms = tomorrow_window_ms(now).0;
+    let response = get_smard_day_ahead_prices(
+        SMARD_BASE_URL,
+        SMARD_PRICE_FILTER,
+        region,
+        SMARD_RESOLUTION,
+        start_timestamp_ms,
+        end_timestamp_ms
+    )?;
+
+    let tomorrow_published = response.data.iter().any(|dp| dp.timestamp >= tomorrow_start_ms && dp.value.is_some());
+    if !tomorrow_published {
+        log!("DEBUG (Rust): Tomorrow's day-ahead auction for {} is not published yet; returning today only.", region);
+    }
+    let mut prices: Vec<(i64, Option<f64>)> = response.data.iter()
+        .filter(|dp| tomorrow_published || dp.timestamp < tomorrow_start_ms)
+        .map(|dp| (dp.timestamp, dp.value))
+        .collect();
+    prices.sort_by_key(|(ts, _)| *ts);
+    Ok(MultiDayPrices { prices, tomorrow_start_ms, tomorrow_published })
 }
 
 // --- Python Bindings ---
-#[pyfunction]
-fn fetch_and_save_data(data_dir: &str, lat: f64, lon: f64) -> PyResult<String> {
-    dotenv().ok(); // Load .env file
 
-    println!("DEBUG (Rust): Attempting to load OPENWEATHER_API_KEY...");
-    let openweather_api_key = env::var("OPENWEATHER_API_KEY")
+// Shared by every pyfunction that talks to OpenWeatherMap.
+fn load_openweather_api_key(secrets_file: Option<&str>) -> PyResult<String> {
+    log!("DEBUG (Rust): Attempting to load OPENWEATHER_API_KEY...");
+    let openweather_api_key = secrets::get_secret("OPENWEATHER_API_KEY", secrets_file)
     .map_err(|e| {
         // This line will print to the terminal where Streamlit is running if the key is not found
-        println!("ERROR (Rust): OPENWEATHER_API_KEY not found or invalid. Error details: {}", e);
-        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("OPENWEATHER_API_KEY not set: {}", e))
+        log!("ERROR (Rust): OPENWEATHER_API_KEY not found or invalid. Error details: {}", e);
+        e.into_py_err("Could not load OPENWEATHER_API_KEY")
     })?;
-    println!("DEBUG (Rust): OPENWEATHER_API_KEY successfully loaded.");
-    
+    log!("DEBUG (Rust): OPENWEATHER_API_KEY successfully loaded.");
+    Ok(openweather_api_key)
+}
+
+// Price series cross the Python boundary as `(timestamp_ms, value)` pairs, the same shape
+// `SeriesStore.points()` returns.
+fn to_points(pairs: Vec<(i64, Option<f64>)>) -> Vec<SmardDataPoint> {
+    pairs.into_iter()
+        .map(|(timestamp, value)| SmardDataPoint { timestamp, value })
+        .collect()
+}
+
+// Optional epoch-ms argument from Python, defaulting to the current time.
+fn time_or_now(timestamp_ms: Option<i64>) -> PyResult<DateTime<Utc>> {
+    match timestamp_ms {
+        None => Ok(clock::now()),
+        Some(ms) => Utc.timestamp_millis_opt(ms).single().ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(format!("Invalid timestamp {} (expected epoch milliseconds)", ms))
+        }),
+    }
+}
+
+fn parse_series_kind(kind: &str) -> PyResult<smard::SmardSeriesKind> {
+    smard::SmardSeriesKind::parse(kind).ok_or_else(|| {
+        pyo3::exceptions::PyValueError::new_err(format!(
+            "Unknown SMARD series kind {:?}, expected \"price\", \"generation\" or \"load\"",
+            kind
+        ))
+    })
+}
+
+// A declared price unit only makes sense for price series.
+fn parse_price_unit(price_unit: Option<&str>, kind: smard::SmardSeriesKind) -> PyResult<Option<units::PriceUnit>> {
+    let Some(name) = price_unit else {
+        return Ok(None);
+    };
+    if kind != smard::SmardSeriesKind::Price {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!("price_unit only applies to price series, not {:?}", kind)));
+    }
+    units::PriceUnit::parse(name).map(Some).ok_or_else(|| {
+        pyo3::exceptions::PyValueError::new_err(format!(
+            "Unknown price unit {:?}, expected \"EUR/MWh\", \"EUR/kWh\" or \"ct/kWh\"",
+            name
+        ))
+    })
+}
+
+fn parse_one_call_version(api_version: &str) -> PyResult<OneCallVersion> {
+    OneCallVersion::parse(api_version).ok_or_else(|| {
+        PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+            "Unsupported One Call api_version {:?}, expected \"3.0\" or \"2.5\"",
+            api_version
+        ))
+    })
+}
+
+/// Price window of a run at `now` without an explicit one: the last 48 hours, or after an
+/// incremental run only what it didn't already store, plus a small overlap.
+pub fn lookback_window_ms(now: DateTime<Utc>, last_stored_ms: Option<i64>) -> (i64, i64) {
+    let now_ms = now.timestamp_millis();
+    match last_stored_ms {
+        Some(last_ms) => ((last_ms - storage::INCREMENTAL_OVERLAP.num_milliseconds()).min(now_ms - 1), now_ms),
+        None => ((now - Duration::hours(48)).timestamp_millis(), now_ms),
+    }
+}
+
+/// The key the day-ahead prices for `region` are tracked under in the fetch state file.
+pub fn smard_price_series(region: &str) -> String {
+    format!("smard:{}:{}:{}", SMARD_PRICE_FILTER, region, SMARD_RESOLUTION)
+}
+
+// Everything behind `fetch_and_save_data` and `fetch_and_save_data_with_result`.
+#[allow(clippy::too_many_arguments)]
+fn fetch_and_save(
+    data_dir: &str,
+    lat: f64,
+    lon: f64,
+    max_staleness_hours: i64,
+    rotate_by_date: bool,
+    fetch_current: bool,
+    fetch_minutely: bool,
+    fetch_daily: bool,
+    fetch_alerts: bool,
+    api_version: &str,
+    fallback_to_v2_5: bool,
+    price_decimals: Option<u32>,
+    secrets_file: Option<&str>,
+    incremental: bool,
+    as_of: Option<i64>,
+    start: Option<&str>,
+    end: Option<&str>,
+    check_plausibility: bool,
+    deadline_secs: Option<f64>,
+    state_file: Option<PathBuf>,
+    pretty: bool,
+    sinks: Option<Vec<String>>,
+    price_unit: Option<&str>,
+) -> PyResult<FetchResult> {
+    // Bounds everything below, retries included; each request's timeout is cut to what's left.
+    let deadline = match deadline_secs {
+        None => None,
+        Some(secs) if secs.is_finite() && secs > 0.0 => Some(http::Deadline::after(std::time::Duration::from_secs_f64(secs))),
+        Some(secs) => return Err(pyo3::exceptions::PyValueError::new_err(format!("deadline_secs must be positive, got {}", secs))),
+    };
+    let _deadline = http::with_deadline(deadline);
+    let price_unit = parse_price_unit(price_unit, smard::SmardSeriesKind::Price)?;
+
+    let openweather_api_key = load_openweather_api_key(secrets_file)?;
+
     // SMARD API keys are commented out in .env and config.py as per our findings for public data.
-    let smard_base_url = "https://www.smard.de/app/chart_data";
-    let smard_price_filter = "1001";
     let smard_region = "DE";
-    let smard_resolution = "hour";
-
-    let now = Utc::now();
-    let end_timestamp_ms = now.timestamp_millis();
-    let start_timestamp_ms = (now - Duration::hours(48)).timestamp_millis(); // Last 48 hours
-
-    // Fetch OpenWeatherMap data
-    println!("Fetching OpenWeatherMap data...");
-    let weather_data = get_openweather_data(&openweather_api_key, lat, lon)
-        .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to fetch OpenWeatherMap data: {}", e)))?;
-    let weather_path = Path::new(data_dir).join("weather_data.json");
-    fs::write(&weather_path, serde_json::to_string_pretty(&weather_data).unwrap())
-        .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to write weather data: {}", e)))?;
-    println!("OpenWeatherMap data saved to {:?}", weather_path);
-
-    // Fetch SMARD data
-    println!("Fetching SMARD data...");
+
+    // `as_of` (epoch ms) pins "now", so a past run's windows and staleness check can be reproduced.
+    let now = time_or_now(as_of)?;
+    // An explicit window picks its provider paths itself, see `window::plan_window`.
+    let plan = match (start, end) {
+        (None, None) => None,
+        (Some(start), Some(end)) => {
+            if incremental {
+                return Err(pyo3::exceptions::PyValueError::new_err("incremental can't be combined with an explicit start/end window"));
+            }
+            let plan = window::parse_rfc3339("start", start)
+                .and_then(|start| Ok((start, window::parse_rfc3339("end", end)?)))
+                .and_then(|(start, end)| window::plan_window(start, end, now))
+                .map_err(|e| e.into_py_err("Invalid fetch window"))?;
+            log!("DEBUG (Rust): Fetch window {} .. {}: weather via {:?}, SMARD via {:?}", plan.start, plan.end, plan.weather, plan.smard);
+            Some(plan)
+        }
+        _ => return Err(pyo3::exceptions::PyValueError::new_err("start and end must be given together")),
+    };
+    // The merge reads the previous `smard_prices.json`, which a dated directory never has.
+    if incremental && rotate_by_date {
+        return Err(pyo3::exceptions::PyValueError::new_err("incremental can't be combined with rotate_by_date"));
+    }
+    let smard_series = smard_price_series(smard_region);
+    let state_file = state_file.unwrap_or_else(|| storage::default_fetch_state_path(data_dir));
+    let last_stored_ms = if incremental { storage::last_stored_timestamp(&state_file, &smard_series) } else { None };
+    let (start_timestamp_ms, end_timestamp_ms) = match plan {
+        Some(plan) => (plan.start.timestamp_millis(), plan.end.timestamp_millis()),
+        None => lookback_window_ms(now, last_stored_ms),
+    };
+    let out_dir = storage::output_dir(data_dir, rotate_by_date, now)
+        .map_err(|e| e.into_py_err("Failed to create output directory"))?;
+
+    let options = OneCallOptions {
+        blocks: OneCallBlocks {
+            current: fetch_current,
+            minutely: fetch_minutely,
+            hourly: true,
+            daily: fetch_daily,
+            alerts: fetch_alerts,
+        },
+        api_version: parse_one_call_version(api_version)?,
+        fallback_to_v2_5,
+    };
+    let window = match (plan, incremental) {
+        (Some(plan), _) => format!("{}/{}", plan.start.to_rfc3339(), plan.end.to_rfc3339()),
+        (None, true) => "incremental".to_string(),
+        (None, false) => "last 48h".to_string(),
+    };
+    let mut run_config = provenance::RunConfig::new(lat, lon, &options, smard_region, window);
+    if let Some(window::WeatherRoute::Timemachine) = plan.map(|p| p.weather) {
+        run_config.weather_api = "onecall/3.0/timemachine".to_string();
+    }
+    run_config.price_decimals = price_decimals;
+    run_config.price_unit = price_unit.map(|unit| unit.as_str());
+    let config_hash = run_config.hash();
+    log!("DEBUG (Rust): Config hash {} for {:?}", config_hash, run_config);
+    let weather_path = out_dir.join("weather_data.json");
+    let smard_path = out_dir.join("smard_prices.json");
+
+    let mut default_pipeline = pipeline::Pipeline::new();
+    default_pipeline = match plan.map(|p| p.weather) {
+        Some(window::WeatherRoute::Timemachine) => default_pipeline.source(pipeline::TimemachineSource {
+            api_key: openweather_api_key,
+            lat,
+            lon,
+            start: start_timestamp_ms / 1000,
+            end: end_timestamp_ms / 1000,
+        }),
+        _ => default_pipeline.source(pipeline::OpenWeatherSource { api_key: openweather_api_key, lat, lon, options }),
+    };
+    default_pipeline = match plan.map(|p| p.smard) {
+        Some(window::SmardRoute::Historical) => default_pipeline.source(pipeline::SmardHistoricalSource {
+            region: smard_region.to_string(),
+            start_ms: start_timestamp_ms,
+            end_ms: end_timestamp_ms,
+        }),
+        _ => default_pipeline.source(pipeline::SmardSource {
+            region: smard_region.to_string(),
+            start_ms: start_timestamp_ms,
+            end_ms: end_timestamp_ms,
+        }),
+    };
+    if let Some(window::WeatherRoute::Forecast) = plan.map(|p| p.weather) {
+        default_pipeline = default_pipeline.transform(pipeline::TrimWeather {
+            start: start_timestamp_ms / 1000,
+            end: end_timestamp_ms / 1000,
+        });
+    }
+    // Before anything reads the prices as EUR/MWh.
+    if let Some(unit) = price_unit {
+        default_pipeline = default_pipeline.transform(pipeline::DeclarePriceUnit(unit));
+    }
+    // None keeps full precision.
+    if let Some(decimals) = price_decimals {
+        default_pipeline = default_pipeline.transform(pipeline::RoundPrices(decimals));
+    }
+    if incremental {
+        default_pipeline = default_pipeline.transform(pipeline::MergeStoredPrices(smard_path.clone()));
+    }
+    default_pipeline = default_pipeline.transform(pipeline::Align);
+    if check_plausibility {
+        default_pipeline = default_pipeline.transform(pipeline::CheckPlausibility { lat, lon });
+    }
+    // A window that ended in the past is expected to have old prices.
+    if plan.is_none_or(|p| p.end >= now) {
+        default_pipeline = default_pipeline.transform(pipeline::CheckStaleness { now, max_staleness_hours });
+    }
+    default_pipeline = default_pipeline.transform(pipeline::StampConfigHash(config_hash.clone()));
+    let sink_names = sinks.unwrap_or_else(|| vec!["json".to_string()]);
+    let settings = pipeline::SinkSettings { out_dir: &out_dir, pretty, region: smard_region, secrets_file };
+    for name in &sink_names {
+        let named = pipeline::named_sinks(name, &settings).map_err(|e| e.into_py_err("Invalid sinks"))?;
+        default_pipeline = default_pipeline.boxed_sinks(named);
+    }
+    #[cfg(feature = "live")]
+    {
+        default_pipeline = default_pipeline.sink(pipeline::LiveSink { now });
+    }
+    let _quiet = sink_names.iter().any(|name| name == "stdout").then(output::quiet_stdout);
+
+    log!("Fetching OpenWeatherMap and SMARD data...");
+    let data = default_pipeline.run().map_err(|e| e.into_py_err())?;
+
+    let newest_ms = data.prices.iter()
+        .flat_map(|p| p.data.iter())
+        .filter(|dp| dp.value.is_some())
+        .map(|dp| dp.timestamp)
+        .max();
+    // Backtest windows don't move the incremental state.
+    if let (Some(newest_ms), None) = (newest_ms, plan) {
+        storage::save_last_stored_timestamp(&state_file, &smard_series, newest_ms)
+            .map_err(|e| e.into_py_err("Failed to save fetch state"))?;
+    }
+
+    for warning in &data.warnings {
+        log!("WARNING (Rust): {}", warning);
+    }
+    let prices = data.prices.iter()
+        .flat_map(|p| p.data.iter())
+        .map(|dp| (dp.timestamp, dp.value))
+        .collect();
+    let weather_coverage = data.weather.as_ref()
+        .and_then(|w| coverage::weather_coverage(&w.hourly))
+        .map(|c| c.as_tuple());
+    let prices_coverage = data.prices.as_ref()
+        .and_then(|p| coverage::coverage(&p.data))
+        .map(|c| c.as_tuple());
+    // Same series `get_hourly_series` builds, from the data just saved.
+    Ok(FetchResult {
+        weather_path,
+        smard_path,
+        warnings: data.warnings,
+        hourly: data.hourly,
+        prices,
+        weather_coverage,
+        prices_coverage,
+        config_hash,
+    })
+}
+
+/// Fetches weather and day-ahead prices and saves them to `data_dir`. By default this is
+/// the 48h forecast plus the last 48h of prices. With `start` and `end` (RFC 3339 strings)
+/// the window is explicit instead:
+///
+/// - inside now-1h .. now+48h: One Call forecast, trimmed to the window
+/// - entirely in the past: timemachine, one OpenWeatherMap call per hour
+/// - both, or past the forecast horizon: `ValueError`
+///
+/// SMARD prices come from the index, or from the historical segments when the window
+/// starts more than 7 days back. With `check_plausibility`, weather that looks degenerate
+/// (impossible temperatures, a constant forecast) is reported in the returned warnings.
+/// `deadline_secs` caps the whole run, retries included; past it, `TimeoutError` is raised.
+/// A valid key without a One Call 3.0 subscription raises `PlanRestrictedError` rather
+/// than `ApiKeyError`, unless `fallback_to_v2_5` retries on 2.5.
+///
+/// With `incremental`, only SMARD prices newer than the last run's are requested (the
+/// first run, with no state yet, uses the default 48h lookback). The newest timestamp is
+/// recorded in `state_file` (default `data_dir/.fetch_state.json`) once the run succeeds.
+/// The new prices are merged into the existing `smard_prices.json`, which keeps the newest
+/// year of hourly points; older ones drop out. It can't be combined with `rotate_by_date`,
+/// whose dated directories would each start an empty file, or with `start`/`end`.
+/// The `collect` binary does the same for prices from the shell (`collect --since-last
+/// --state-file PATH`), sharing the state file.
+/// `pretty=False` writes the JSON files on one line, for storage rather than reading.
+///
+/// `sinks` picks where the data goes, any combination of "json" (the two files above, the
+/// default), "csv" (`hourly.csv`), "stdout" (the `fetch_json` document) and, in builds with
+/// the matching feature, "sqlite" (`energy_data.sqlite`), "xlsx" (`energy_data.xlsx`) and
+/// "influx" (the bucket named by INFLUX_URL/INFLUX_TOKEN/INFLUX_ORG/INFLUX_BUCKET) and "mqtt"
+/// (retained messages under MQTT_TOPIC on MQTT_HOST, see `mqtt.rs`). The returned paths are
+/// where the "json" files are, or would be.
+///
+/// Prices are saved in EUR/MWh, which is what the day-ahead filter publishes. `price_unit`
+/// ("EUR/kWh" or "ct/kWh") declares otherwise, as in `fetch_smard_series`: the prices are
+/// converted and `smard_prices.json` records the original unit as `source_unit`.
+///
+/// Both files carry a `config_hash` of the settings that shaped them (location, One Call
+/// version and blocks, region, resolution, window, rounding, crate version), so saved data
+/// can be traced back to its configuration. Runs with the same settings share the hash; a
+/// default or incremental window counts as one setting, whatever the time.
+#[pyfunction]
+#[pyo3(signature = (
+    data_dir,
+    lat,
+    lon,
+    max_staleness_hours = quality::DEFAULT_MAX_STALENESS_HOURS,
+    rotate_by_date = false,
+    fetch_current = true,
+    fetch_minutely = false,
+    fetch_daily = false,
+    fetch_alerts = false,
+    api_version = "3.0",
+    fallback_to_v2_5 = false,
+    price_decimals = Some(prices::DEFAULT_PRICE_DECIMALS),
+    secrets_file = None,
+    incremental = false,
+    as_of = None,
+    start = None,
+    end = None,
+    check_plausibility = true,
+    deadline_secs = None,
+    state_file = None,
+    pretty = true,
+    sinks = None,
+    price_unit = None,
+))]
+#[allow(clippy::too_many_arguments)]
+fn fetch_and_save_data(
+    data_dir: &str,
+    lat: f64,
+    lon: f64,
+    max_staleness_hours: i64,
+    rotate_by_date: bool,
+    fetch_current: bool,
+    fetch_minutely: bool,
+    fetch_daily: bool,
+    fetch_alerts: bool,
+    api_version: &str,
+    fallback_to_v2_5: bool,
+    price_decimals: Option<u32>,
+    secrets_file: Option<&str>,
+    incremental: bool,
+    as_of: Option<i64>,
+    start: Option<&str>,
+    end: Option<&str>,
+    check_plausibility: bool,
+    deadline_secs: Option<f64>,
+    state_file: Option<PathBuf>,
+    pretty: bool,
+    sinks: Option<Vec<String>>,
+    price_unit: Option<&str>,
+) -> PyResult<String> {
+    let result = fetch_and_save(
+        data_dir,
+        lat,
+        lon,
+        max_staleness_hours,
+        rotate_by_date,
+        fetch_current,
+        fetch_minutely,
+        fetch_daily,
+        fetch_alerts,
+        api_version,
+        fallback_to_v2_5,
+        price_decimals,
+        secrets_file,
+        incremental,
+        as_of,
+        start,
+        end,
+        check_plausibility,
+        deadline_secs,
+        state_file,
+        pretty,
+        sinks,
+        price_unit,
+    )?;
+    if result.warnings.is_empty() {
+        return Ok("Data fetching complete.".to_string());
+    }
+    let messages: Vec<&str> = result.warnings.iter().map(|w| w.message.as_str()).collect();
+    Ok(format!("Data fetching complete with warnings: {}", messages.join("; ")))
+}
+
+/// Same as `fetch_and_save_data`, but returns a `FetchResult` with the saved file paths,
+/// the warnings as `{"code": ..., "message": ...}` dicts (e.g. code "stale_prices"), and the
+/// data itself (`hourly` merged points and the `(timestamp_ms, price)` series), so the UI
+/// can render without reading the files back. Warnings never abort the fetch.
+#[pyfunction]
+#[pyo3(signature = (
+    data_dir,
+    lat,
+    lon,
+    max_staleness_hours = quality::DEFAULT_MAX_STALENESS_HOURS,
+    rotate_by_date = false,
+    fetch_current = true,
+    fetch_minutely = false,
+    fetch_daily = false,
+    fetch_alerts = false,
+    api_version = "3.0",
+    fallback_to_v2_5 = false,
+    price_decimals = Some(prices::DEFAULT_PRICE_DECIMALS),
+    secrets_file = None,
+    incremental = false,
+    as_of = None,
+    start = None,
+    end = None,
+    check_plausibility = true,
+    deadline_secs = None,
+    state_file = None,
+    pretty = true,
+    sinks = None,
+    price_unit = None,
+))]
+#[allow(clippy::too_many_arguments)]
+fn fetch_and_save_data_with_result(
+    data_dir: &str,
+    lat: f64,
+    lon: f64,
+    max_staleness_hours: i64,
+    rotate_by_date: bool,
+    fetch_current: bool,
+    fetch_minutely: bool,
+    fetch_daily: bool,
+    fetch_alerts: bool,
+    api_version: &str,
+    fallback_to_v2_5: bool,
+    price_decimals: Option<u32>,
+    secrets_file: Option<&str>,
+    incremental: bool,
+    as_of: Option<i64>,
+    start: Option<&str>,
+    end: Option<&str>,
+    check_plausibility: bool,
+    deadline_secs: Option<f64>,
+    state_file: Option<PathBuf>,
+    pretty: bool,
+    sinks: Option<Vec<String>>,
+    price_unit: Option<&str>,
+) -> PyResult<FetchResult> {
+    fetch_and_save(
+        data_dir,
+        lat,
+        lon,
+        max_staleness_hours,
+        rotate_by_date,
+        fetch_current,
+        fetch_minutely,
+        fetch_daily,
+        fetch_alerts,
+        api_version,
+        fallback_to_v2_5,
+        price_decimals,
+        secrets_file,
+        incremental,
+        as_of,
+        start,
+        end,
+        check_plausibility,
+        deadline_secs,
+        state_file,
+        pretty,
+        sinks,
+        price_unit,
+    )
+}
+
+/// The 48h forecast merged with day-ahead prices as `HourlyPoint` objects. With
+/// `include_solar`, `ghi` is filled from Solcast when `SOLCAST_TOKEN` is configured.
+#[pyfunction]
+#[pyo3(signature = (lat, lon, include_solar = false, secrets_file = None))]
+fn get_hourly_series(lat: f64, lon: f64, include_solar: bool, secrets_file: Option<&str>) -> PyResult<Vec<merge::HourlyPoint>> {
+    let openweather_api_key = load_openweather_api_key(secrets_file)?;
+
+    // Cover the forecast horizon rather than the past, so prices line up with the weather hours.
+    let now = clock::now();
+    let start_timestamp_ms = (now - Duration::hours(1)).timestamp_millis();
+    let end_timestamp_ms = (now + Duration::hours(48)).timestamp_millis();
+
+    // Only the hourly forecast is merged, so skip the current block too.
+    let options = OneCallOptions {
+        blocks: OneCallBlocks { current: false, ..OneCallBlocks::default() },
+        ..OneCallOptions::default()
+    };
+    let weather_data = get_openweather_data(&openweather_api_key, lat, lon, &options)
+        .map_err(|e| e.into_py_err("Failed to fetch OpenWeatherMap data"))?;
     let smard_data = get_smard_day_ahead_prices(
-        smard_base_url,
-        smard_price_filter,
-        smard_region,
-        smard_resolution,
+        SMARD_BASE_URL,
+        SMARD_PRICE_FILTER,
+        "DE",
+        SMARD_RESOLUTION,
         start_timestamp_ms,
         end_timestamp_ms
     )
-    .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to fetch SMARD data: {}", e)))?;
-    let smard_path = Path::new(data_dir).join("smard_prices.json");
-    fs::write(&smard_path, serde_json::to_string_pretty(&smard_data).unwrap())
-        .map_err(|e| PyErr::new::<pyo3::exceptions::PyIOError, _>(format!("Failed to write SMARD data: {}", e)))?;
-    println!("SMARD data saved to {:?}", smard_path);
+    .map_err(|e| e.into_py_err("Failed to fetch SMARD data"))?;
+
+    let mut points = merge::merge_hourly(&weather_data, &smard_data);
+    if include_solar {
+        use solar::SolarProvider;
+
+        match solar::SolcastProvider::from_secrets(None, secrets_file).and_then(|p| p.fetch_irradiance(lat, lon, 48)) {
+            Ok(irradiance) => merge::attach_irradiance(&mut points, &irradiance),
+            Err(e) => log!("WARNING (Rust): Solar forecast unavailable, leaving ghi empty: {}", e),
+        }
+    }
+    for warning in quality::check_weather_plausibility(&weather_data.hourly).into_iter()
+        .chain(quality::check_irradiance_plausibility(&points, lat, lon))
+        .chain(merge::timestamp_warnings(&weather_data, &smard_data))
+        .chain(quality::check_diurnal_offset(&points, lat, lon))
+    {
+        log!("WARNING (Rust): {}", warning);
+    }
+    Ok(points)
+}
+
+// Weather, prices and their alignment over the forecast horizon, as used by `fetch_json`
+// and `save_json`.
+fn forecast_pipeline(lat: f64, lon: f64, secrets_file: Option<&str>) -> PyResult<pipeline::Pipeline> {
+    let openweather_api_key = load_openweather_api_key(secrets_file)?;
+
+    // Same forecast window as `get_hourly_series`, so "merged" lines up with "weather".
+    let now = clock::now();
+    let options = OneCallOptions::default();
+    let mut run_config = provenance::RunConfig::new(lat, lon, &options, "DE", "next 48h");
+    run_config.price_decimals = Some(prices::DEFAULT_PRICE_DECIMALS);
+    Ok(pipeline::Pipeline::new()
+        .source(pipeline::OpenWeatherSource { api_key: openweather_api_key, lat, lon, options })
+        .source(pipeline::SmardSource {
+            region: "DE".to_string(),
+            start_ms: (now - Duration::hours(1)).timestamp_millis(),
+            end_ms: (now + Duration::hours(48)).timestamp_millis(),
+        })
+        .transform(pipeline::RoundPrices(prices::DEFAULT_PRICE_DECIMALS))
+        .transform(pipeline::Align)
+        .transform(pipeline::StampConfigHash(run_config.hash())))
+}
+
+/// Weather, SMARD prices and the merged hourly series for the next 48h as one JSON
+/// document `{"weather": ..., "smard": ..., "merged": [...], "config_hash": "..."}`.
+/// Nothing is written to disk.
+#[pyfunction]
+#[pyo3(signature = (lat, lon, secrets_file = None))]
+fn fetch_json(lat: f64, lon: f64, secrets_file: Option<&str>) -> PyResult<String> {
+    let data = forecast_pipeline(lat, lon, secrets_file)?.run().map_err(|e| e.into_py_err())?;
+    Ok(pipeline::combined_document(&data).to_string())
+}
+
+/// Writes the `fetch_json` document to `output`: a file path, or `"-"` for stdout (e.g.
+/// `python fetch.py | jq .merged`). While writing to stdout, debug output is suppressed and
+/// warnings go to stderr, so the stream is pure JSON. The document is on one line unless
+/// `pretty`.
+#[pyfunction]
+#[pyo3(signature = (output, lat, lon, secrets_file = None, pretty = false))]
+fn save_json(output: &str, lat: f64, lon: f64, secrets_file: Option<&str>, pretty: bool) -> PyResult<()> {
+    let target = output::OutputTarget::parse(output);
+    let _quiet = (target == output::OutputTarget::Stdout).then(output::quiet_stdout);
+    forecast_pipeline(lat, lon, secrets_file)?
+        .sink(pipeline::CombinedJsonSink::new(target, pretty))
+        .run()
+        .map_err(|e| e.into_py_err())?;
+    Ok(())
+}
+
+/// Keeps `data_dir/rolling_window.json` holding the last `window_hours` hours: fetches the
+/// forecast and the prices since the window start, merges them into the file (one point
+/// per hour, sorted, newer data winning) and drops hours older than the window. Hours of
+/// the forecast still ahead are kept too and become the record once they have passed. The
+/// file is replaced atomically, so readers never see a partial write. A file that can't be
+/// read as a rolling window is renamed to `rolling_window.json.corrupt` and a new window
+/// started. Returns its path.
+#[pyfunction]
+#[pyo3(signature = (data_dir, lat, lon, window_hours, secrets_file = None, pretty = false))]
+fn rolling_fetch_and_store(
+    data_dir: &str,
+    lat: f64,
+    lon: f64,
+    window_hours: u32,
+    secrets_file: Option<&str>,
+    pretty: bool,
+) -> PyResult<PathBuf> {
+    if window_hours == 0 {
+        return Err(pyo3::exceptions::PyValueError::new_err("window_hours must be at least 1"));
+    }
+    let openweather_api_key = load_openweather_api_key(secrets_file)?;
+    let now = clock::now();
+    let data = pipeline::Pipeline::new()
+        .source(pipeline::OpenWeatherSource { api_key: openweather_api_key, lat, lon, options: OneCallOptions::default() })
+        .source(pipeline::SmardSource {
+            region: "DE".to_string(),
+            start_ms: (now - Duration::hours(i64::from(window_hours))).timestamp_millis(),
+            end_ms: (now + Duration::hours(48)).timestamp_millis(),
+        })
+        .transform(pipeline::RoundPrices(prices::DEFAULT_PRICE_DECIMALS))
+        .transform(pipeline::Align)
+        .run()
+        .map_err(|e| e.into_py_err())?;
+    let path = PathBuf::from(data_dir).join(rolling::ROLLING_FILE);
+    let window = rolling::update_window(&path, data.hourly, window_hours, now, pretty)
+        .map_err(|e| e.into_py_err("Failed to update rolling window"))?;
+    log!("Rolling window saved to {:?} ({} hours)", path, window.hourly.len());
+    Ok(path)
+}
+
+/// Offline check that the installed module works: parses bundled sample weather and prices,
+/// aligns them, and compares the daily statistics and cheapest hours with known values.
+/// Needs no network or API key. A mismatch is logged and returns `False`.
+#[pyfunction]
+fn self_test() -> bool {
+    match selftest::run() {
+        Ok(()) => true,
+        Err(failure) => {
+            log!("ERROR (Rust): Self-test failed: {}", failure);
+            false
+        }
+    }
+}
+
+/// Current temperature, current price and the cheapest upcoming hour, for status widgets.
+/// Only the One Call current block and the prices for the next 48h are fetched; nothing
+/// is written to disk.
+#[pyfunction]
+#[pyo3(signature = (lat, lon, secrets_file = None))]
+fn fetch_summary(lat: f64, lon: f64, secrets_file: Option<&str>) -> PyResult<summary::Summary> {
+    let openweather_api_key = load_openweather_api_key(secrets_file)?;
+
+    let now = clock::now();
+    let options = OneCallOptions {
+        blocks: OneCallBlocks { current: true, minutely: false, hourly: false, daily: false, alerts: false },
+        ..OneCallOptions::default()
+    };
+    let weather_data = get_openweather_data(&openweather_api_key, lat, lon, &options)
+        .map_err(|e| e.into_py_err("Failed to fetch OpenWeatherMap data"))?;
+    let smard_data = get_smard_day_ahead_prices(
+        SMARD_BASE_URL,
+        SMARD_PRICE_FILTER,
+        "DE",
+        SMARD_RESOLUTION,
+        (now - Duration::hours(1)).timestamp_millis(),
+        (now + Duration::hours(48)).timestamp_millis(),
+    )
+    .map_err(|e| e.into_py_err("Failed to fetch SMARD data"))?;
+
+    Ok(summary::summarize(&weather_data, &smard_data.data, now))
+}
+
+/// Everything for a dashboard's front page in one `Outlook`: current and next-24h price
+/// statistics, the cheapest and dearest `window_hours` runs, grid carbon intensity, the PV
+/// peak (with a `PanelSpec`, from Solcast) and a weather summary. Weather and prices are
+/// required; when carbon or PV can't be fetched their fields are `None` and the reason is
+/// in `warnings`. Nothing is written to disk.
+#[pyfunction]
+#[pyo3(signature = (lat, lon, panel = None, window_hours = outlook::DEFAULT_WINDOW_HOURS, secrets_file = None))]
+fn energy_outlook(
+    lat: f64,
+    lon: f64,
+    panel: Option<outlook::PanelSpec>,
+    window_hours: u32,
+    secrets_file: Option<&str>,
+) -> PyResult<outlook::Outlook> {
+    use solar::SolarProvider;
+
+    let openweather_api_key = load_openweather_api_key(secrets_file)?;
+
+    let now = clock::now();
+    let weather_data = get_openweather_data(&openweather_api_key, lat, lon, &OneCallOptions::default())
+        .map_err(|e| e.into_py_err("Failed to fetch OpenWeatherMap data"))?;
+    let smard_data = get_smard_day_ahead_prices(
+        SMARD_BASE_URL,
+        SMARD_PRICE_FILTER,
+        "DE",
+        SMARD_RESOLUTION,
+        (now - Duration::hours(1)).timestamp_millis(),
+        (now + outlook::OUTLOOK_HORIZON).timestamp_millis(),
+    )
+    .map_err(|e| e.into_py_err("Failed to fetch SMARD data"))?;
+
+    let mut warnings = Vec::new();
+    let carbon = carbon::fetch_carbon_intensity(
+        "DE",
+        SMARD_RESOLUTION,
+        (now - outlook::OUTLOOK_HORIZON).timestamp_millis(),
+        now.timestamp_millis(),
+    )
+    .map(|(carbon, skipped)| {
+        warnings.extend(skipped);
+        carbon
+    })
+    .unwrap_or_else(|e| {
+        warnings.push(quality::Warning::new("no_carbon_intensity", format!("Carbon intensity unavailable: {}", e)));
+        Vec::new()
+    });
+    let pv = panel.and_then(|panel| {
+        solar::SolcastProvider::from_secrets(Some(panel.capacity_kw), secrets_file)
+            .and_then(|p| p.fetch_irradiance(lat, lon, outlook::OUTLOOK_HORIZON.num_hours() as u32))
+            .map_err(|e| warnings.push(quality::Warning::new("no_pv_forecast", format!("PV forecast unavailable: {}", e))))
+            .ok()
+    });
+    for warning in &warnings {
+        log!("WARNING (Rust): {}", warning);
+    }
+
+    Ok(outlook::build_outlook(&weather_data, &smard_data.data, &carbon, pv.as_deref(), now, window_hours, warnings))
+}
+
+/// Fetches the same data as `fetch_json` and writes it to an Excel workbook at `path`:
+/// a price summary plus Weather, Prices and Merged sheets.
+#[cfg(feature = "xlsx")]
+#[pyfunction]
+#[pyo3(signature = (path, lat, lon, secrets_file = None))]
+fn export_xlsx(path: PathBuf, lat: f64, lon: f64, secrets_file: Option<&str>) -> PyResult<()> {
+    let openweather_api_key = load_openweather_api_key(secrets_file)?;
+
+    let now = clock::now();
+    pipeline::Pipeline::new()
+        .source(pipeline::OpenWeatherSource { api_key: openweather_api_key, lat, lon, options: OneCallOptions::default() })
+        .source(pipeline::SmardSource {
+            region: "DE".to_string(),
+            start_ms: (now - Duration::hours(1)).timestamp_millis(),
+            end_ms: (now + Duration::hours(48)).timestamp_millis(),
+        })
+        .transform(pipeline::RoundPrices(prices::DEFAULT_PRICE_DECIMALS))
+        .transform(pipeline::Align)
+        .sink(pipeline::XlsxSink(path))
+        .run()
+        .map_err(|e| e.into_py_err())?;
+    Ok(())
+}
+
+/// Price differences between every pair of SMARD `regions` (e.g. `["DE", "AT"]`) over a
+/// millisecond window, one `RegionSpread` per shared timestamp. Regions are fetched in parallel.
+#[pyfunction]
+#[pyo3(signature = (regions, start_ms, end_ms, resolution = SMARD_RESOLUTION))]
+fn fetch_region_spreads(regions: Vec<String>, start_ms: i64, end_ms: i64, resolution: &str) -> PyResult<Vec<spreads::RegionSpread>> {
+    spreads::fetch_region_spreads(&regions, resolution, start_ms, end_ms)
+        .map_err(|e| e.into_py_err("Failed to fetch region spreads"))
+}
+
+/// `(timestamp_ms, value)` points of any SMARD series. `kind` ("price", "generation" or
+/// "load") must match what `filter` measures, so a generation series can't be mistaken for
+/// prices. Prices are returned in EUR/MWh: for a price filter that publishes something
+/// else, declare its `price_unit` ("EUR/kWh" or "ct/kWh") and the values are converted to
+/// EUR/MWh like every other price. Without it, values are taken to be EUR/MWh as they are.
+#[pyfunction]
+#[pyo3(signature = (filter, kind, start_ms, end_ms, region = "DE", resolution = SMARD_RESOLUTION, price_unit = None))]
+#[allow(clippy::too_many_arguments)]
+fn fetch_smard_series(
+    filter: &str,
+    kind: &str,
+    start_ms: i64,
+    end_ms: i64,
+    region: &str,
+    resolution: &str,
+    price_unit: Option<&str>,
+) -> PyResult<Vec<(i64, Option<f64>)>> {
+    let kind = parse_series_kind(kind)?;
+    let price_unit = parse_price_unit(price_unit, kind)?;
+    let mut response = get_smard_series(SMARD_BASE_URL, filter, kind, region, resolution, start_ms, end_ms)
+        .map_err(|e| e.into_py_err("Failed to fetch SMARD series"))?;
+    if let Some(unit) = price_unit {
+        units::normalize_price_unit(&mut response, unit);
+    }
+    Ok(response.data.iter().map(|dp| (dp.timestamp, dp.value)).collect())
+}
+
+/// Grid carbon intensity in gCO2/kWh as `(points, warnings)`: `(timestamp_ms, intensity)`
+/// points weighted by each SMARD generation source and the active emission-factor table,
+/// and a `{"code", "message"}` dict for each source that couldn't be fetched or has no
+/// factor and so is left out of the weighting.
+#[pyfunction]
+#[pyo3(signature = (start_ms, end_ms, region = "DE", resolution = SMARD_RESOLUTION))]
+fn fetch_carbon_intensity(
+    start_ms: i64,
+    end_ms: i64,
+    region: &str,
+    resolution: &str,
+) -> PyResult<carbon::CarbonIntensity> {
+    carbon::fetch_carbon_intensity(region, resolution, start_ms, end_ms)
+        .map_err(|e| e.into_py_err("Failed to fetch carbon intensity"))
+}
+
+/// Replaces the built-in emission factors with a JSON or TOML file mapping SMARD source
+/// names (e.g. "lignite", "solar") to gCO2/kWh, and returns the loaded table.
+#[pyfunction]
+fn load_emission_factors(path: &str) -> PyResult<HashMap<String, f64>> {
+    carbon::load_emission_factors(path).map_err(|e| e.into_py_err("Failed to load emission factors"))
+}
+
+/// `(filter_id, label)` pairs for the commonly used SMARD filters, e.g. `("4068",
+/// "Generation: Photovoltaics")`, for a dropdown instead of raw filter numbers.
+#[pyfunction]
+fn list_smard_filters() -> Vec<(&'static str, &'static str)> {
+    smard::list_smard_filters()
+}
+
+/// Backfills a SMARD series from its historical segments as `(timestamp_ms, value)`
+/// points in timestamp order. `progress(fraction_done, segments_remaining)` is called after
+/// each segment, e.g. to drive a Streamlit progress bar. At most `segment_concurrency`
+/// segment requests run at once, so long backfills don't get rate-limited by SMARD; each
+/// segment is retried on its own when it fails transiently. Prices are returned in EUR/MWh;
+/// `price_unit` declares a filter that publishes another unit, as in `fetch_smard_series`.
+#[pyfunction]
+#[pyo3(signature = (
+    start_ms,
+    end_ms,
+    filter = SMARD_PRICE_FILTER,
+    kind = "price",
+    region = "DE",
+    resolution = SMARD_RESOLUTION,
+    segment_concurrency = backfill::DEFAULT_SEGMENT_CONCURRENCY,
+    progress = None,
+    price_unit = None,
+))]
+#[allow(clippy::too_many_arguments)]
+fn get_smard_historical(
+    py: Python<'_>,
+    start_ms: i64,
+    end_ms: i64,
+    filter: &str,
+    kind: &str,
+    region: &str,
+    resolution: &str,
+    segment_concurrency: usize,
+    progress: Option<PyObject>,
+    price_unit: Option<&str>,
+) -> PyResult<Vec<(i64, Option<f64>)>> {
+    let kind = parse_series_kind(kind)?;
+    let price_unit = parse_price_unit(price_unit, kind)?;
+    // The GIL is released for the backfill, so other Python threads (a Streamlit UI) keep
+    // running; it's only taken back to report progress.
+    let mut response = py
+        .allow_threads(|| {
+            let mut report = |done: usize, total: usize| {
+                if let Some(callback) = &progress {
+                    Python::with_gil(|py| {
+                        // A broken progress bar shouldn't abort the backfill.
+                        if let Err(e) = callback.call1(py, (done as f64 / total as f64, total - done)) {
+                            log!("WARNING (Rust): SMARD backfill progress callback failed: {}", e);
+                        }
+                    })
+                }
+            };
+            backfill::get_smard_historical(
+                SMARD_BASE_URL, filter, kind, region, resolution, start_ms, end_ms, segment_concurrency, &mut report,
+            )
+        })
+        .map_err(|e| e.into_py_err("Failed to backfill SMARD data"))?;
+    if let Some(unit) = price_unit {
+        units::normalize_price_unit(&mut response, unit);
+    }
+    Ok(response.data.iter().map(|dp| (dp.timestamp, dp.value)).collect())
+}
+
+/// `(timestamp_ms, value)` points from SMARD's CSV download, the most reliable source for
+/// deep history. `module_id` is the download manager's series id (8004169, the default, is
+/// the DE-LU day-ahead price); it differs from the chart_data filter numbers.
+#[pyfunction]
+#[pyo3(signature = (start_ms, end_ms, module_id = smard_csv::DEFAULT_PRICE_MODULE_ID, region = "DE", resolution = SMARD_RESOLUTION))]
+fn get_smard_csv_download(start_ms: i64, end_ms: i64, module_id: u64, region: &str, resolution: &str) -> PyResult<Vec<(i64, Option<f64>)>> {
+    let response = smard_csv::get_smard_csv_download(module_id, region, resolution, start_ms, end_ms)
+        .map_err(|e| e.into_py_err("Failed to download SMARD CSV"))?;
+    Ok(response.data.iter().map(|dp| (dp.timestamp, dp.value)).collect())
+}
+
+/// Returns tomorrow's hourly day-ahead prices as `(timestamp_ms, eur_per_mwh)` pairs, or
+/// `None` if the auction hasn't been published yet. Fetch errors raise instead.
+#[pyfunction]
+#[pyo3(signature = (region = "DE"))]
+fn get_tomorrow_prices(region: &str) -> PyResult<Option<Vec<(i64, f64)>>> {
+    match fetch_tomorrow_prices(region) {
+        Ok(TomorrowPrices::Published(response)) => Ok(Some(
+            response.data.iter()
+                .filter_map(|dp| dp.value.map(|v| (dp.timestamp, v)))
+                .collect(),
+        )),
+        Ok(TomorrowPrices::NotYetPublished) => Ok(None),
+        Err(e) => Err(e.into_py_err("Failed to fetch tomorrow's SMARD prices")),
+    }
+}
+
+/// Today's and tomorrow's day-ahead prices as one `MultiDayPrices` series for "plan
+/// tomorrow" jobs. Points from `tomorrow_start_ms` on are tomorrow's auction; before it is
+/// published (around 13:00 CET) only today is returned, with `tomorrow_published=False`.
+#[pyfunction]
+#[pyo3(signature = (region = "DE", now_ms = None))]
+fn fetch_prices_multi_day_ahead(region: &str, now_ms: Option<i64>) -> PyResult<MultiDayPrices> {
+    fetch_multi_day_prices(region, time_or_now(now_ms)?)
+        .map_err(|e| e.into_py_err("Failed to fetch SMARD prices"))
+}
+
+/// Starts the WebSocket server that pushes each completed fetch to live dashboards.
+#[cfg(feature = "live")]
+#[pyfunction]
+#[pyo3(signature = (addr = "127.0.0.1:8765"))]
+fn start_live_server(addr: &str) -> PyResult<()> {
+    live::start_server(addr)
+        .map_err(|e| e.into_py_err("Failed to start live server"))
+}
+
+/// Deletes `YYYY-MM-DD` folders under `data_dir` older than `days` days (see `rotate_by_date`).
+/// Returns the removed paths.
+#[pyfunction]
+fn prune_older_than(data_dir: &str, days: i64) -> PyResult<Vec<String>> {
+    let removed = storage::prune_older_than(data_dir, days, clock::now())
+        .map_err(|e| e.into_py_err(&format!("Failed to prune {}", data_dir)))?;
+    Ok(removed.iter().map(|p| p.display().to_string()).collect())
+}
+
+/// Hourly GHI/DNI/DHI forecast from Solcast (token in `SOLCAST_TOKEN`). Pass the array's
+/// peak power to also get Solcast's PV output estimate per hour. Solcast forecasts up to
+/// 336 hours; more raises `ValueError`, or is cut to 336 with `clamp_hours`.
+#[pyfunction]
+#[pyo3(signature = (lat, lon, hours = 48, pv_capacity_kw = None, secrets_file = None, clamp_hours = false))]
+fn fetch_solar_forecast(
+    lat: f64,
+    lon: f64,
+    hours: u32,
+    pv_capacity_kw: Option<f64>,
+    secrets_file: Option<&str>,
+    clamp_hours: bool,
+) -> PyResult<Vec<solar::IrradiancePoint>> {
+    use solar::SolarProvider;
+
+    let provider = solar::SolcastProvider::from_secrets(pv_capacity_kw, secrets_file)
+        .map_err(|e| e.into_py_err("Solcast is not configured"))?;
+    let hours = weather::limit_forecast_hours(provider.name(), hours, provider.max_forecast_hours(), clamp_hours)
+        .map_err(|e| e.into_py_err("Invalid forecast horizon"))?;
+    provider.fetch_irradiance(lat, lon, hours)
+        .map_err(|e| e.into_py_err("Failed to fetch Solcast forecast"))
+}
+
+/// Cheap connectivity and credential check for both APIs; saves nothing. Meant as a
+/// startup self-test, so a missing API key is reported rather than raised.
+#[pyfunction]
+#[pyo3(signature = (lat, lon, secrets_file = None))]
+fn health_check(lat: f64, lon: f64, secrets_file: Option<&str>) -> health::HealthReport {
+    let openweather_api_key = secrets::get_secret("OPENWEATHER_API_KEY", secrets_file).ok();
+    health::run_health_check(openweather_api_key.as_deref(), lat, lon)
+}
+
+/// Pre-flight check of a run's configuration: API key, `data_dir`, coordinates, region,
+/// units, resolution and (optionally) connectivity. Problems are collected into the report
+/// rather than raised, so a setup screen can show them all at once.
+#[pyfunction]
+#[pyo3(signature = (
+    data_dir,
+    lat,
+    lon,
+    region = "DE",
+    units = "metric",
+    resolution = SMARD_RESOLUTION,
+    check_connectivity = true,
+    secrets_file = None,
+))]
+#[allow(clippy::too_many_arguments)]
+fn validate_config(
+    data_dir: &str,
+    lat: f64,
+    lon: f64,
+    region: &str,
+    units: &str,
+    resolution: &str,
+    check_connectivity: bool,
+    secrets_file: Option<&str>,
+) -> preflight::ValidationReport {
+    let openweather_api_key = secrets::get_secret("OPENWEATHER_API_KEY", secrets_file).ok();
+    preflight::validate_config(
+        openweather_api_key.as_deref(), data_dir, lat, lon, region, units, resolution, check_connectivity,
+    )
+}
 
-    Ok("Data fetching complete.".to_string())
+/// Recorded weather at a past `dt` (Unix seconds) via the One Call timemachine endpoint.
+/// Raises `PlanRestrictedError` when the key's plan doesn't include it.
+#[pyfunction]
+#[pyo3(signature = (lat, lon, dt, secrets_file = None))]
+fn get_weather_at(lat: f64, lon: f64, dt: i64, secrets_file: Option<&str>) -> PyResult<history::HistoricalWeather> {
+    let openweather_api_key = load_openweather_api_key(secrets_file)?;
+    history::get_weather_at(&openweather_api_key, lat, lon, dt)
+        .map_err(|e| e.into_py_err("Failed to fetch historical OpenWeatherMap data"))
+}
+
+/// `(lat, lon)` for a place name, e.g. `geocode_city("Berlin", "DE")`, via OpenWeatherMap's
+/// Geocoding API. Answers are cached on disk for a year, so a fixed city costs one lookup.
+#[pyfunction]
+#[pyo3(signature = (city, country = None, secrets_file = None))]
+fn geocode_city(city: &str, country: Option<&str>, secrets_file: Option<&str>) -> PyResult<(f64, f64)> {
+    let openweather_api_key = load_openweather_api_key(secrets_file)?;
+    geocode::geocode(&openweather_api_key, city, country).map_err(|e| e.into_py_err("Failed to geocode"))
+}
+
+/// Forgets every location `geocode_city` has cached.
+#[pyfunction]
+fn clear_geocode_cache() -> PyResult<()> {
+    geocode::clear_cache().map_err(|e| e.into_py_err("Failed to clear geocoding cache"))
+}
+
+/// Where `geocode_city` keeps resolved locations (default: a file in the temp dir, which may
+/// not survive a reboot).
+#[pyfunction]
+#[pyo3(signature = (cache_file = None))]
+fn set_geocode_cache_file(cache_file: Option<PathBuf>) {
+    geocode::set_cache_file(cache_file);
+}
+
+/// Cheapest `(start_ms, cost_eur)` for running a `power_kw` appliance for `duration_hours`
+/// between `not_before_ms` and `not_after_ms`, given `(timestamp_ms, EUR/MWh)` prices.
+/// Raises `ValueError` saying why when no window fits or `power_kw` isn't a positive number.
+#[pyfunction]
+#[pyo3(signature = (points, duration_hours, not_before_ms, not_after_ms, power_kw = 1.0))]
+fn optimal_start(
+    points: Vec<(i64, Option<f64>)>,
+    duration_hours: u32,
+    not_before_ms: i64,
+    not_after_ms: i64,
+    power_kw: f64,
+) -> PyResult<(i64, f64)> {
+    prices::optimal_start(&to_points(points), duration_hours, not_before_ms, not_after_ms, power_kw)
+        .map_err(|reason| pyo3::exceptions::PyValueError::new_err(format!("No appliance start window found: {}", reason)))
+}
+
+/// iCalendar (.ics) text with one event per `(start_ms, end_ms, EUR/MWh)` cheap window,
+/// titled e.g. "Run dishwasher 02:00–04:00 · 4.1 ct/kWh" with `title_prefix = "Run
+/// dishwasher"`. Times are Europe/Berlin.
+#[pyfunction]
+#[pyo3(signature = (cheapest_windows, title_prefix = "Cheap energy"))]
+fn to_ics(cheapest_windows: Vec<(i64, i64, f64)>, title_prefix: &str) -> PyResult<String> {
+    ics::to_ics(&cheapest_windows, title_prefix, clock::now()).map_err(|e| e.into_py_err("Failed to build calendar"))
+}
+
+/// `(timestamp_ms, price)` points grouped into German local days (DST-aware, so switch
+/// days have 23 or 25 hours), oldest first.
+#[pyfunction]
+fn daily_prices(points: Vec<(i64, Option<f64>)>) -> Vec<prices::DailyPrices> {
+    prices::daily_prices(&to_points(points))
+}
+
+/// A seconds or milliseconds timestamp as epoch milliseconds. `unit` ("s" or "ms") skips
+/// the magnitude-based guess.
+#[pyfunction]
+#[pyo3(signature = (raw, unit = None))]
+fn normalize_timestamp(raw: i64, unit: Option<&str>) -> PyResult<i64> {
+    let unit = unit
+        .map(|name| {
+            timestamp::TimestampUnit::parse(name).ok_or_else(|| {
+                pyo3::exceptions::PyValueError::new_err(format!("Unknown timestamp unit {:?}, expected \"s\" or \"ms\"", name))
+            })
+        })
+        .transpose()?;
+    timestamp::normalize_timestamp_as(raw, unit)
+        .map(|t| t.timestamp_millis())
+        .ok_or_else(|| pyo3::exceptions::PyValueError::new_err(format!("Timestamp {} is out of range", raw)))
+}
+
+/// Heuristic reliability (1.0 = now, halving every `half_life_hours`) of a forecast
+/// `hours_ahead` in the future.
+#[pyfunction]
+#[pyo3(signature = (hours_ahead, half_life_hours = None))]
+fn forecast_confidence(hours_ahead: f64, half_life_hours: Option<f64>) -> PyResult<f64> {
+    let half_life = half_life_hours.unwrap_or_else(quality::confidence_half_life);
+    if half_life <= 0.0 || half_life.is_nan() {
+        return Err(pyo3::exceptions::PyValueError::new_err("half_life_hours must be positive"));
+    }
+    Ok(quality::forecast_confidence(hours_ahead, half_life))
+}
+
+/// Half-life used for the `confidence` attached to every hourly forecast entry.
+#[pyfunction]
+fn set_confidence_half_life(hours: f64) -> PyResult<()> {
+    if hours <= 0.0 || hours.is_nan() {
+        return Err(pyo3::exceptions::PyValueError::new_err("hours must be positive"));
+    }
+    quality::set_confidence_half_life(hours);
+    Ok(())
+}
+
+/// Merged hours (from `get_hourly_series`) as a Grafana JSON datasource response string,
+/// one target per metric in `metrics` (see `merge::GRAFANA_METRICS`).
+#[pyfunction]
+#[pyo3(signature = (points, metrics = vec!["price".to_string()]))]
+fn to_grafana_timeseries(points: Vec<merge::HourlyPoint>, metrics: Vec<String>) -> PyResult<String> {
+    let value = merge::to_grafana_timeseries(&points, &metrics)
+        .map_err(|e| e.into_py_err("Could not build Grafana series"))?;
+    Ok(value.to_string())
+}
+
+/// A `(timestamp_ms, price)` series as an aWATTar market data JSON string
+/// (`start_timestamp`, `end_timestamp`, `marketprice`, `unit`), for tools that read aWATTar.
+#[pyfunction]
+fn to_awattar_format(points: Vec<(i64, Option<f64>)>) -> String {
+    prices::to_awattar_format(&to_points(points)).to_string()
+}
+
+/// Reads an aWATTar market data JSON string back into `(timestamp_ms, price)` pairs.
+#[pyfunction]
+fn from_awattar_format(json: &str) -> PyResult<Vec<(i64, Option<f64>)>> {
+    let value: serde_json::Value = serde_json::from_str(json)
+        .map_err(|e| CollectorError::Parse { source: e, body: json.to_string() }.into_py_err("Invalid aWATTar JSON"))?;
+    let points = prices::from_awattar_format(&value).map_err(|e| e.into_py_err("Invalid aWATTar market data"))?;
+    Ok(points.iter().map(|dp| (dp.timestamp, dp.value)).collect())
+}
+
+/// Next hourly price change after `now_ms` (default: now) as
+/// `(timestamp_ms, current_price, next_price, delta)`, or `None` at the end of the series.
+#[pyfunction]
+#[pyo3(signature = (points, now_ms = None))]
+fn next_price_change(points: Vec<(i64, Option<f64>)>, now_ms: Option<i64>) -> PyResult<Option<(i64, f64, f64, f64)>> {
+    let change = prices::next_price_change(&to_points(points), time_or_now(now_ms)?);
+    Ok(change.map(|c| (c.timestamp, c.current_price, c.next_price, c.delta)))
+}
+
+/// Span actually covered by `(timestamp_ms, value)` points as
+/// `(start_ms, end_ms, count, expected_count)`: the first and last point with a value, how
+/// many points have one, and how many a gapless series would have. `None` if no point has
+/// a value. Plans shouldn't reach past `end_ms`.
+#[pyfunction]
+#[pyo3(name = "coverage")]
+fn series_coverage(points: Vec<(i64, Option<f64>)>) -> Option<(i64, i64, usize, usize)> {
+    coverage::coverage(&to_points(points)).map(|c| c.as_tuple())
+}
+
+/// Actions for the active weather alerts in `alerts_json` (One Call's `alerts` array as
+/// JSON, e.g. from a saved `weather_data.json`), timed against the prices in `merged`:
+/// heat means pre-cooling and frost pre-heating in the cheapest hour before the alert's
+/// dearest one; storm, snow or ice mean topping up the battery before it starts. `rules`
+/// replaces the built-in `(keyword, action)` pairs, matched in order against the event name
+/// and tags; actions are "pre_cool", "pre_heat" and "charge_battery".
+#[pyfunction]
+#[pyo3(signature = (alerts_json, merged, rules = None, now_ms = None))]
+fn alert_recommendations(
+    alerts_json: &str,
+    merged: Vec<merge::HourlyPoint>,
+    rules: Option<Vec<(String, String)>>,
+    now_ms: Option<i64>,
+) -> PyResult<Vec<alerts::Recommendation>> {
+    let values: Vec<serde_json::Value> = serde_json::from_str(alerts_json)
+        .map_err(|e| CollectorError::Parse { source: e, body: alerts_json.to_string() }.into_py_err("Invalid alerts JSON"))?;
+    let rules = match rules {
+        Some(pairs) => alerts::parse_rules(&pairs).map_err(|e| e.into_py_err("Invalid alert rules"))?,
+        None => alerts::default_rules(),
+    };
+    Ok(alerts::alert_recommendations(&alerts::parse_alerts(&values), &merged, &rules, time_or_now(now_ms)?))
+}
+
+/// One-paragraph briefing for a German local day (`date` as "YYYY-MM-DD", default: today)
+/// from merged `HourlyPoint`s and optional `(timestamp_ms, gCO2/kWh)` carbon intensities,
+/// e.g. "Cheapest hour today is 03:00 at 4.2 ct/kWh; ...". Weather comes from `merged`.
+#[pyfunction]
+#[pyo3(signature = (merged, carbon = Vec::new(), date = None))]
+fn summarize_day(merged: Vec<merge::HourlyPoint>, carbon: Vec<(i64, Option<f64>)>, date: Option<&str>) -> PyResult<String> {
+    let today = clock::now().with_timezone(&Berlin).date_naive();
+    let date = match date {
+        None => today,
+        Some(date) => chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|e| {
+            pyo3::exceptions::PyValueError::new_err(format!("Invalid date {:?} (expected YYYY-MM-DD): {}", date, e))
+        })?,
+    };
+    Ok(briefing::summarize_day(&merged, &carbon, date, today))
+}
+
+/// Converts a temperature between "C", "F" and "K" (full names and the OpenWeatherMap
+/// `units` values "metric", "imperial" and "standard" work too).
+#[pyfunction]
+fn convert_temperature(value: f64, from_unit: &str, to_unit: &str) -> PyResult<f64> {
+    let parse = |unit: &str| {
+        units::TemperatureUnit::parse(unit).ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(format!("Unknown temperature unit {:?}, expected \"C\", \"F\" or \"K\"", unit))
+        })
+    };
+    units::convert_temperature(value, parse(from_unit)?, parse(to_unit)?)
+        .map_err(|e| e.into_py_err("Failed to convert temperature"))
+}
+
+/// The `n` cheapest upcoming hours (after `now_ms`, default: now) as
+/// `(timestamp_ms, price)`, cheapest first; equal prices earliest first.
+#[pyfunction]
+#[pyo3(signature = (points, n, now_ms = None))]
+fn cheapest_hours(points: Vec<(i64, Option<f64>)>, n: usize, now_ms: Option<i64>) -> PyResult<Vec<(i64, f64)>> {
+    Ok(prices::cheapest_hours(&to_points(points), n, time_or_now(now_ms)?))
+}
+
+/// The `n` most expensive upcoming hours as `(timestamp_ms, price)`, dearest first; equal
+/// prices earliest first. The hours to discharge a battery into.
+#[pyfunction]
+#[pyo3(signature = (points, n, now_ms = None))]
+fn most_expensive_hours(points: Vec<(i64, Option<f64>)>, n: usize, now_ms: Option<i64>) -> PyResult<Vec<(i64, f64)>> {
+    Ok(prices::most_expensive_hours(&to_points(points), n, time_or_now(now_ms)?))
+}
+
+/// `(start_ms, mean_price)` of the most expensive run of `hours` consecutive upcoming
+/// hours, earliest on a tie, or `None` if the series has no such run.
+#[pyfunction]
+#[pyo3(signature = (points, hours, now_ms = None))]
+fn most_expensive_contiguous_window(points: Vec<(i64, Option<f64>)>, hours: u32, now_ms: Option<i64>) -> PyResult<Option<(i64, f64)>> {
+    Ok(prices::most_expensive_contiguous_window(&to_points(points), hours, time_or_now(now_ms)?))
+}
+
+/// Percentile rank (0 = cheapest, 1 = dearest) of the price at `at_ts` (ms, default: now)
+/// within `points`, e.g. 0.15 means cheaper than 85% of the series. `None` values are
+/// ignored; returns `None` if no price is known at or before `at_ts`.
+#[pyfunction]
+#[pyo3(signature = (points, at_ts = None))]
+fn price_percentile_rank(points: Vec<(i64, Option<f64>)>, at_ts: Option<i64>) -> PyResult<Option<f64>> {
+    Ok(prices::price_percentile_rank(&to_points(points), time_or_now(at_ts)?))
+}
+
+/// Band of a price in EUR/MWh: "very_cheap", "cheap", "normal", "expensive" or
+/// "very_expensive". `thresholds` are the four ascending lower bounds of the bands above
+/// "very_cheap"; without them they're taken from the quintiles of `points`, see
+/// `price_band_thresholds`.
+#[pyfunction]
+#[pyo3(signature = (value, thresholds = None, points = None))]
+fn classify_price(value: f64, thresholds: Option<[f64; 4]>, points: Option<Vec<(i64, Option<f64>)>>) -> PyResult<&'static str> {
+    let thresholds = match (thresholds, points) {
+        (Some(thresholds), _) => thresholds,
+        (None, Some(points)) => prices::quantile_thresholds(&to_points(points)).ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err("points has no prices to derive thresholds from")
+        })?,
+        (None, None) => return Err(pyo3::exceptions::PyValueError::new_err("Either thresholds or points is required")),
+    };
+    prices::classify_price(value, &thresholds)
+        .map(|band| band.as_str())
+        .map_err(|e| e.into_py_err("Invalid price thresholds"))
+}
+
+/// The 20th, 40th, 60th and 80th percentile of the prices in `points`, as thresholds for
+/// `classify_price` that split the series into five equally full bands. `None` without
+/// prices.
+#[pyfunction]
+fn price_band_thresholds(points: Vec<(i64, Option<f64>)>) -> Option<[f64; 4]> {
+    prices::quantile_thresholds(&to_points(points))
+}
+
+/// `(timestamp_ms, tariff)` for each point, for stepped block tariffs: `bands` are
+/// `(threshold, tariff)` pairs sorted by threshold (EUR/MWh), each band running from its
+/// threshold up to the next one's. Prices below the first threshold get the first band's
+/// tariff, and missing prices stay `None`. Unsorted or repeated thresholds raise
+/// `ValueError`.
+#[pyfunction]
+fn apply_tariff_bands(points: Vec<(i64, Option<f64>)>, bands: Vec<(f64, f64)>) -> PyResult<Vec<(i64, Option<f64>)>> {
+    prices::apply_tariff_bands(&to_points(points), &bands).map_err(|e| e.into_py_err("Invalid tariff bands"))
+}
+
+/// `(timestamp_ms, value)` points on a grid of `target_step_ms` (e.g. 900000 for quarter
+/// hours). Upsampling: `method` "ffill" repeats each value, "interpolate" is linear between
+/// points. Downsampling: "mean" or "sum" over each grid interval.
+#[pyfunction]
+#[pyo3(name = "resample", signature = (points, target_step_ms, method = "mean"))]
+fn resample_series(points: Vec<(i64, Option<f64>)>, target_step_ms: i64, method: &str) -> PyResult<Vec<(i64, Option<f64>)>> {
+    let method = resample::ResampleMethod::parse(method).ok_or_else(|| {
+        pyo3::exceptions::PyValueError::new_err(format!(
+            "Unknown resample method {:?}, expected \"ffill\", \"interpolate\", \"mean\" or \"sum\"",
+            method
+        ))
+    })?;
+    let resampled = resample::resample(&to_points(points), target_step_ms, method)
+        .map_err(|e| e.into_py_err("Failed to resample"))?;
+    Ok(resampled.iter().map(|dp| (dp.timestamp, dp.value)).collect())
+}
+
+/// Battery arbitrage plan over `(timestamp_ms, EUR/MWh)` prices: which hours to charge and
+/// discharge, with the state of charge per hour (`soc_kwh`, one more entry than `steps`).
+/// The plan stays within `min_soc_kwh`..`capacity_kwh` and `max_power_kw`, counts
+/// `round_trip_efficiency` losses, and ends at `initial_soc_kwh`. Hour pairs whose spread
+/// is below `cycle_break_even` for `cycle_cost_eur_per_kwh` are not traded; the wear of the
+/// plan is in `wear_cost`.
+#[pyfunction]
+#[pyo3(signature = (
+    prices,
+    capacity_kwh,
+    max_power_kw,
+    initial_soc_kwh,
+    min_soc_kwh = 0.0,
+    round_trip_efficiency = 0.9,
+    cycle_cost_eur_per_kwh = 0.0,
+))]
+fn optimize_battery_schedule(
+    prices: Vec<(i64, Option<f64>)>,
+    capacity_kwh: f64,
+    max_power_kw: f64,
+    initial_soc_kwh: f64,
+    min_soc_kwh: f64,
+    round_trip_efficiency: f64,
+    cycle_cost_eur_per_kwh: f64,
+) -> PyResult<battery::BatterySchedule> {
+    let spec = battery::BatterySpec {
+        capacity_kwh,
+        max_power_kw,
+        min_soc_kwh,
+        initial_soc_kwh,
+        round_trip_efficiency,
+        cycle_cost_eur_per_kwh,
+    };
+    battery::optimize_battery_schedule(&to_points(prices), &spec).map_err(|e| e.into_py_err("Failed to schedule battery"))
+}
+
+/// Minimum spread in EUR/kWh (selling minus buying price) for a charge/discharge cycle to
+/// pay off, given the round-trip `efficiency` and the wear per kWh discharged. The lost
+/// energy is paid at `charge_price_eur_per_kwh`, so at the default of 0 this is the wear
+/// alone.
+#[pyfunction]
+#[pyo3(signature = (efficiency, cycle_cost_eur_per_kwh, charge_price_eur_per_kwh = 0.0))]
+fn cycle_break_even(efficiency: f64, cycle_cost_eur_per_kwh: f64, charge_price_eur_per_kwh: f64) -> PyResult<f64> {
+    if !(efficiency > 0.0 && efficiency <= 1.0) {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!("efficiency must be in (0, 1], got {}", efficiency)));
+    }
+    if !(cycle_cost_eur_per_kwh.is_finite() && cycle_cost_eur_per_kwh >= 0.0) {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "cycle_cost_eur_per_kwh must be zero or positive, got {}",
+            cycle_cost_eur_per_kwh
+        )));
+    }
+    Ok(battery::cycle_break_even(efficiency, cycle_cost_eur_per_kwh, charge_price_eur_per_kwh))
+}
+
+/// Measured PV output from a `timestamp,kW` CSV as `(unix_seconds, kw)` pairs.
+#[pyfunction]
+fn load_actual_pv(path: &str) -> PyResult<Vec<(i64, f64)>> {
+    pv::load_actual_pv(path).map_err(|e| e.into_py_err("Failed to load PV actuals"))
+}
+
+/// MAE/RMSE/bias of a PV forecast against actuals, both `(unix_seconds, kw)` pairs aligned by
+/// hour. `None` if they share no hours.
+#[pyfunction]
+fn compare_pv_forecast(forecast: Vec<(i64, f64)>, actuals: Vec<(i64, f64)>) -> Option<pv::PvAccuracy> {
+    pv::compare_pv_forecast(&forecast, &actuals)
+}
+
+/// Self-consumption vs. export over the hours shared by `pv_kw`, `load_kw` (both
+/// `(unix_seconds, kw)` hourly averages) and `prices` (`(timestamp_ms, EUR/MWh)`), with
+/// exports paid `feed_in_tariff` EUR/kWh.
+#[pyfunction]
+fn estimate_savings(
+    pv_kw: Vec<(i64, f64)>,
+    load_kw: Vec<(i64, f64)>,
+    prices: Vec<(i64, Option<f64>)>,
+    feed_in_tariff: f64,
+) -> savings::SavingsReport {
+    savings::estimate_savings(&pv_kw, &load_kw, &prices, feed_in_tariff)
+}
+
+/// Cost of running `load_kwh` in the hour containing `from_ts` versus the hour containing
+/// `to_ts` (seconds or milliseconds), at `(timestamp_ms, EUR/MWh)` prices. `ValueError` if
+/// either hour is unpriced.
+#[pyfunction]
+fn simulate_load_shift(prices: Vec<(i64, Option<f64>)>, load_kwh: f64, from_ts: i64, to_ts: i64) -> PyResult<savings::LoadShift> {
+    savings::simulate_load_shift(&prices, load_kwh, from_ts, to_ts)
+        .map_err(|e| e.into_py_err("Failed to simulate load shift"))
+}
+
+/// Cost of an hourly `load_profile` (`(unix_seconds, kWh)`) at spot `prices`
+/// (`(timestamp_ms, EUR/MWh)`) against a `flat_rate` in EUR/kWh, over the hours that have
+/// a price. `savings` is what the spot tariff saves, in EUR and as `savings_pct` of the
+/// flat cost. Load in hours without a price is counted in `unmatched_hours` and
+/// `unmatched_kwh` instead. Spot prices are the bare exchange price, without fees or taxes.
+#[pyfunction]
+fn compare_to_flat_tariff(
+    prices: Vec<(i64, Option<f64>)>,
+    load_profile: Vec<(i64, f64)>,
+    flat_rate: f64,
+) -> PyResult<savings::TariffComparison> {
+    savings::compare_to_flat_tariff(&prices, &load_profile, flat_rate)
+        .map_err(|e| e.into_py_err("Failed to compare tariffs"))
+}
+
+/// Hour-by-hour comparison of two saved `weather_data.json` (temperature) or
+/// `smard_prices.json` (price) files, including hours added or dropped between them.
+#[pyfunction]
+fn diff_snapshots(old_path: &str, new_path: &str) -> PyResult<diff::SnapshotDiff> {
+    diff::diff_snapshots(old_path, new_path).map_err(|e| e.into_py_err("Failed to diff snapshots"))
+}
+
+/// Hours still ahead whose forecast moved sharply between two merged series, e.g. the
+/// `hourly` of the previous and the current `FetchResult`: a price change of more than
+/// `price_threshold` EUR/MWh or a temperature change of more than `temp_threshold` °C.
+/// Each is a `ForecastDeltaAlert` with the old and new value; an empty list means a plan
+/// made on `old` still stands.
+#[pyfunction]
+#[pyo3(signature = (old, new, price_threshold, temp_threshold, now_ms = None))]
+fn forecast_delta_alert(
+    old: Vec<merge::HourlyPoint>,
+    new: Vec<merge::HourlyPoint>,
+    price_threshold: f64,
+    temp_threshold: f64,
+    now_ms: Option<i64>,
+) -> PyResult<Vec<diff::ForecastDeltaAlert>> {
+    diff::forecast_delta_alert(&old, &new, price_threshold, temp_threshold, time_or_now(now_ms)?)
+        .map_err(|e| e.into_py_err("Failed to compare forecasts"))
+}
+
+/// Checks a saved `weather_data.json`, `smard_prices.json` or `rolling_window.json` before
+/// it is used offline: it must parse, have its series non-empty and in time order, and be
+/// in the expected units. Returns the kind ("weather", "prices" or "rolling"); otherwise
+/// raises `ValueError` listing every problem, e.g. "weather_data.json is missing the hourly
+/// array (or it is empty)".
+#[pyfunction]
+fn validate_saved_file(path: PathBuf) -> PyResult<&'static str> {
+    validate::validate_saved_file(&path).map_err(|e| e.into_py_err("Invalid saved file"))
+}
+
+/// Which entry to keep when OpenWeatherMap repeats an hourly timestamp: "first"
+/// (default) or "last". Each duplicate is logged either way.
+#[pyfunction]
+fn set_duplicate_hour_policy(keep: &str) -> PyResult<()> {
+    match keep {
+        "first" => quality::set_keep_last_duplicate(false),
+        "last" => quality::set_keep_last_duplicate(true),
+        _ => {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Unknown duplicate hour policy {:?}, expected \"first\" or \"last\"",
+                keep
+            )))
+        }
+    }
+    Ok(())
+}
+
+/// Pins "now" to `now_ms` (epoch milliseconds) for everything that would otherwise read
+/// the system clock: default windows, forecast confidence, the daily call budget and store
+/// eviction. `None` (default) goes back to the system clock.
+#[pyfunction]
+#[pyo3(signature = (now_ms = None))]
+fn set_fixed_clock(now_ms: Option<i64>) -> PyResult<()> {
+    let fixed = match now_ms {
+        None => None,
+        Some(ms) => Some(std::sync::Arc::new(clock::FixedClock(time_or_now(Some(ms))?)) as std::sync::Arc<dyn clock::Clock>),
+    };
+    clock::set_clock(fixed);
+    Ok(())
+}
+
+/// Timeouts for every provider request: `connect_timeout_secs` to establish the
+/// connection (default 5) and `timeout_secs` for the whole request including the body
+/// (default 30).
+#[pyfunction]
+#[pyo3(signature = (connect_timeout_secs = http::DEFAULT_CONNECT_TIMEOUT_SECS, timeout_secs = http::DEFAULT_TIMEOUT_SECS))]
+fn set_http_timeouts(connect_timeout_secs: u64, timeout_secs: u64) -> PyResult<()> {
+    if connect_timeout_secs == 0 || timeout_secs == 0 {
+        return Err(pyo3::exceptions::PyValueError::new_err("Timeouts must be at least 1 second"));
+    }
+    http::set_timeouts(connect_timeout_secs, timeout_secs);
+    Ok(())
+}
+
+/// Largest response body any provider may send, in bytes (default 50 MB). Bigger bodies
+/// are refused with a `ParseError` instead of being read into memory.
+#[pyfunction]
+fn set_max_response_bytes(limit: u64) {
+    http::set_max_response_bytes(limit);
+}
+
+/// Empties the OpenWeatherMap and SMARD response caches (e.g. after changing parameters)
+/// and resets their counters. Safe to call while fetches are running.
+#[pyfunction]
+fn clear_cache() {
+    cache::weather().clear();
+    cache::smard().clear();
+}
+
+/// `{"entries": ..., "hits": ..., "misses": ...}` for the OpenWeatherMap response cache,
+/// plus the same counters prefixed `smard_` for the SMARD cache.
+#[pyfunction]
+fn cache_stats() -> HashMap<&'static str, u64> {
+    let stats = cache::weather().stats();
+    let smard_stats = cache::smard().stats();
+    HashMap::from([
+        ("entries", stats.entries as u64),
+        ("hits", stats.hits),
+        ("misses", stats.misses),
+        ("smard_entries", smard_stats.entries as u64),
+        ("smard_hits", smard_stats.hits),
+        ("smard_misses", smard_stats.misses),
+    ])
+}
+
+/// How long cached OpenWeatherMap and SMARD responses are reused, in seconds.
+#[pyfunction]
+fn set_cache_ttl(ttl_secs: u64) {
+    let ttl = std::time::Duration::from_secs(ttl_secs);
+    cache::weather().set_ttl(ttl);
+    cache::smard().set_ttl(ttl);
+}
+
+/// Fetches weather and prices into the caches on a background thread and returns at
+/// once, so the first `fetch_and_save_data` after startup is served from memory.
+/// Failures are only logged; the first real call then fetches as usual.
+#[pyfunction]
+#[pyo3(signature = (lat, lon, secrets_file = None))]
+fn warmup(lat: f64, lon: f64, secrets_file: Option<String>) {
+    std::thread::spawn(move || {
+        let now = clock::now();
+        let weather = secrets::get_secret("OPENWEATHER_API_KEY", secrets_file.as_deref())
+            .and_then(|api_key| get_openweather_data(&api_key, lat, lon, &OneCallOptions::default()));
+        if let Err(e) = weather {
+            log!("WARNING (Rust): Weather cache warmup failed, the first fetch will retry: {}", e);
+        }
+        // Wide enough to cover both the 48h lookback and the 48h forecast window.
+        let prices = get_smard_day_ahead_prices(
+            SMARD_BASE_URL,
+            SMARD_PRICE_FILTER,
+            "DE",
+            SMARD_RESOLUTION,
+            (now - Duration::hours(49)).timestamp_millis(),
+            (now + Duration::hours(49)).timestamp_millis(),
+        );
+        if let Err(e) = prices {
+            log!("WARNING (Rust): SMARD cache warmup failed, the first fetch will retry: {}", e);
+        }
+        log!("DEBUG (Rust): Cache warmup finished.");
+    });
+}
+
+// How long a refresh thread keeps serving its last good value while fetches fail.
+const MAX_REFRESH_STALENESS: std::time::Duration = std::time::Duration::from_secs(3 * 3600);
+
+// Whether a refresh thread whose last success was at `last_ok` may keep serving that value.
+fn kept_fresh(last_ok: Option<std::time::Instant>) -> bool {
+    last_ok.is_some_and(|at| at.elapsed() < MAX_REFRESH_STALENESS)
+}
+
+/// Keeps the weather and price caches fresh from a background thread, so `fetch_*` calls
+/// for `lat`/`lon` are served from memory. Refreshes every `interval_secs` (default: one
+/// minute before cached entries expire, see `set_cache_ttl`). A failed refresh keeps the
+/// last good value in the cache, for up to 3 hours, and is retried at the next interval.
+/// Returns a `BackgroundRefresh` handle; `stop()` ends the thread, as does interpreter
+/// exit.
+#[pyfunction]
+#[pyo3(signature = (lat, lon, interval_secs = None, secrets_file = None))]
+fn start_background_refresh(
+    py: Python<'_>,
+    lat: f64,
+    lon: f64,
+    interval_secs: Option<f64>,
+    secrets_file: Option<String>,
+) -> PyResult<Py<refresh::BackgroundRefresh>> {
+    let ttl = cache::weather().ttl();
+    let interval = match interval_secs {
+        None => ttl.saturating_sub(std::time::Duration::from_secs(60)).max(std::time::Duration::from_secs(1)),
+        Some(secs) if secs.is_finite() && secs > 0.0 => std::time::Duration::from_secs_f64(secs),
+        Some(secs) => return Err(pyo3::exceptions::PyValueError::new_err(format!("interval_secs must be positive, got {}", secs))),
+    };
+    if interval >= ttl {
+        log!("WARNING (Rust): Refresh interval {:?} is not below the cache TTL {:?}, entries will expire between refreshes.", interval, ttl);
+    }
+    let api_key = load_openweather_api_key(secrets_file.as_deref())?;
+    let options = OneCallOptions::default();
+    let weather_key = one_call_cache_key(lat, lon, &options);
+    let smard_url = smard_index_url(SMARD_BASE_URL, SMARD_PRICE_FILTER, "DE", SMARD_RESOLUTION);
+    // Last successful refresh; until there is one, a failure has no good value of ours to keep.
+    let mut weather_ok: Option<std::time::Instant> = None;
+    let mut smard_ok: Option<std::time::Instant> = None;
+    let inner = refresh::RefreshLoop::spawn(interval, move || {
+        let now = clock::now();
+        match refresh_openweather_data(&api_key, lat, lon, &options) {
+            Ok(_) => weather_ok = Some(std::time::Instant::now()),
+            Err(e) if kept_fresh(weather_ok) && cache::weather().renew(&weather_key) => {
+                log!("WARNING (Rust): Weather refresh failed, keeping the last good forecast: {}", e);
+            }
+            Err(e) => log!("WARNING (Rust): Weather refresh failed, will retry: {}", e),
+        }
+        // Same window as `warmup`.
+        let prices = refresh_smard_index(
+            smard_url.clone(),
+            (now - Duration::hours(49)).timestamp_millis(),
+            (now + Duration::hours(49)).timestamp_millis(),
+        );
+        match prices {
+            Ok(_) => smard_ok = Some(std::time::Instant::now()),
+            Err(e) if kept_fresh(smard_ok) && cache::smard().renew(&smard_url) => {
+                log!("WARNING (Rust): SMARD refresh failed, keeping the last good prices: {}", e);
+            }
+            Err(e) => log!("WARNING (Rust): SMARD refresh failed, will retry: {}", e),
+        }
+    });
+    let handle = Py::new(py, refresh::BackgroundRefresh { inner, interval_secs: interval.as_secs_f64() })?;
+    // Stop before the interpreter finalizes, rather than leave the thread running into it.
+    py.import_bound("atexit")?.call_method1("register", (handle.getattr(py, "stop")?,))?;
+    Ok(handle)
+}
+
+/// Normalized hourly forecast for the next `hours` hours from the first provider in
+/// `providers` that succeeds (`"open-meteo"`, `"openweather"`); `WeatherData.provider` says
+/// which one served it. Outages fall through to the next provider, rejected requests raise
+/// immediately. Providers forecast at most so far ahead (OpenWeatherMap 48 hours, Open-Meteo
+/// 384); one that can't cover `hours` is skipped, or with `clamp_hours` serves what it has.
+#[pyfunction]
+#[pyo3(signature = (
+    lat,
+    lon,
+    providers = vec!["open-meteo".to_string(), "openweather".to_string()],
+    secrets_file = None,
+    hours = weather::DEFAULT_FORECAST_HOURS,
+    clamp_hours = false,
+))]
+fn fetch_weather_with_fallback(
+    lat: f64,
+    lon: f64,
+    providers: Vec<String>,
+    secrets_file: Option<&str>,
+    hours: u32,
+    clamp_hours: bool,
+) -> PyResult<weather::WeatherData> {
+    weather::fetch_weather_with_fallback(lat, lon, &providers, hours, clamp_hours, secrets_file)
+        .map_err(|e| e.into_py_err("Failed to fetch weather"))
+}
+
+/// Weighted hour-by-hour average of several `WeatherData` forecasts (e.g. Open-Meteo and
+/// OpenWeatherMap) for temperature, cloud cover, precipitation chance and irradiance.
+/// Hours only some forecasts cover are averaged over those, and so are cloud cover and
+/// precipitation chance where a provider left them out (`None`). `weights` defaults to
+/// equal weights.
+#[pyfunction]
+#[pyo3(signature = (forecasts, weights = None))]
+fn ensemble_weather(forecasts: Vec<weather::WeatherData>, weights: Option<Vec<f64>>) -> PyResult<weather::WeatherData> {
+    let weights = weights.unwrap_or_else(|| vec![1.0; forecasts.len()]);
+    weather::ensemble_weather(&forecasts, &weights).map_err(|e| e.into_py_err("Failed to build weather ensemble"))
+}
+
+/// Caps OpenWeatherMap calls per UTC day. Counts persist in `state_file` (default: a file
+/// in the temp dir). With `enforce=False` going over budget only logs a warning.
+#[pyfunction]
+#[pyo3(signature = (daily_call_budget = budget::DEFAULT_DAILY_CALL_BUDGET, state_file = None, enforce = true))]
+fn configure_call_budget(daily_call_budget: u32, state_file: Option<PathBuf>, enforce: bool) {
+    budget::configure(daily_call_budget, state_file, enforce);
+}
+
+/// OpenWeatherMap calls made today (UTC) according to the budget state file.
+#[pyfunction]
+fn calls_used_today() -> u32 {
+    budget::calls_used_today()
+}
+
+/// Tunes the circuit breakers guarding OpenWeatherMap, SMARD and Open-Meteo: after `failure_threshold`
+/// consecutive failures a provider is skipped for `cooldown_secs`.
+#[pyfunction]
+#[pyo3(signature = (failure_threshold = circuit::DEFAULT_FAILURE_THRESHOLD, cooldown_secs = circuit::DEFAULT_COOLDOWN.as_secs()))]
+fn configure_circuit_breaker(failure_threshold: u32, cooldown_secs: u64) {
+    for breaker in [&circuit::OPENWEATHER, &circuit::SMARD, &circuit::OPEN_METEO] {
+        breaker.configure(failure_threshold, std::time::Duration::from_secs(cooldown_secs));
+    }
+}
+
+/// Minimum seconds between the starts of two requests to `provider` ("smard",
+/// "openweather" or "open-meteo"); 0 disables the spacing. SMARD defaults to 0.25s, the
+/// others to 0.
+#[pyfunction]
+fn set_min_request_interval(provider: &str, interval_secs: f64) -> PyResult<()> {
+    let breaker = circuit::by_name(provider).ok_or_else(|| {
+        pyo3::exceptions::PyValueError::new_err(format!(
+            "Unknown provider {:?}, expected \"smard\", \"openweather\" or \"open-meteo\"",
+            provider
+        ))
+    })?;
+    if !interval_secs.is_finite() || interval_secs < 0.0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "interval_secs must be a non-negative number of seconds, got {}",
+            interval_secs
+        )));
+    }
+    breaker.set_min_interval(std::time::Duration::from_secs_f64(interval_secs));
+    Ok(())
+}
+
+/// Closes all circuits again without waiting for the cooldown.
+#[pyfunction]
+fn reset_circuit_breakers() {
+    circuit::OPENWEATHER.reset();
+    circuit::SMARD.reset();
+    circuit::OPEN_METEO.reset();
 }
 
 /// A Python module implemented in Rust.
 #[pymodule]
-fn rust_data_collector(_py: Python, m: &PyModule) -> PyResult<()> {
+fn rust_data_collector(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("CollectorError", m.py().get_type_bound::<exceptions::CollectorError>())?;
+    m.add("ApiKeyError", m.py().get_type_bound::<exceptions::ApiKeyError>())?;
+    m.add("PlanRestrictedError", m.py().get_type_bound::<exceptions::PlanRestrictedError>())?;
+    m.add("NetworkError", m.py().get_type_bound::<exceptions::NetworkError>())?;
+    m.add("RateLimitError", m.py().get_type_bound::<exceptions::RateLimitError>())?;
+    m.add("ParseError", m.py().get_type_bound::<exceptions::ParseError>())?;
     m.add_function(wrap_pyfunction!(fetch_and_save_data, m)?)?;
+    m.add_function(wrap_pyfunction!(fetch_and_save_data_with_result, m)?)?;
+    m.add_function(wrap_pyfunction!(get_tomorrow_prices, m)?)?;
+    m.add_function(wrap_pyfunction!(fetch_prices_multi_day_ahead, m)?)?;
+    m.add_function(wrap_pyfunction!(fetch_json, m)?)?;
+    m.add_function(wrap_pyfunction!(save_json, m)?)?;
+    m.add_function(wrap_pyfunction!(rolling_fetch_and_store, m)?)?;
+    m.add_function(wrap_pyfunction!(fetch_summary, m)?)?;
+    m.add_function(wrap_pyfunction!(energy_outlook, m)?)?;
+    m.add_function(wrap_pyfunction!(fetch_region_spreads, m)?)?;
+    m.add_function(wrap_pyfunction!(fetch_smard_series, m)?)?;
+    m.add_function(wrap_pyfunction!(list_smard_filters, m)?)?;
+    m.add_function(wrap_pyfunction!(get_smard_historical, m)?)?;
+    m.add_function(wrap_pyfunction!(get_smard_csv_download, m)?)?;
+    m.add_function(wrap_pyfunction!(fetch_carbon_intensity, m)?)?;
+    m.add_function(wrap_pyfunction!(load_emission_factors, m)?)?;
+    m.add_function(wrap_pyfunction!(prune_older_than, m)?)?;
+    m.add_function(wrap_pyfunction!(fetch_solar_forecast, m)?)?;
+    m.add_function(wrap_pyfunction!(get_hourly_series, m)?)?;
+    m.add_function(wrap_pyfunction!(health_check, m)?)?;
+    m.add_function(wrap_pyfunction!(self_test, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_config, m)?)?;
+    m.add_function(wrap_pyfunction!(next_price_change, m)?)?;
+    m.add_function(wrap_pyfunction!(price_percentile_rank, m)?)?;
+    m.add_function(wrap_pyfunction!(classify_price, m)?)?;
+    m.add_function(wrap_pyfunction!(price_band_thresholds, m)?)?;
+    m.add_function(wrap_pyfunction!(apply_tariff_bands, m)?)?;
+    m.add_function(wrap_pyfunction!(resample_series, m)?)?;
+    m.add_function(wrap_pyfunction!(series_coverage, m)?)?;
+    m.add_function(wrap_pyfunction!(summarize_day, m)?)?;
+    m.add_function(wrap_pyfunction!(alert_recommendations, m)?)?;
+    m.add_function(wrap_pyfunction!(daily_prices, m)?)?;
+    m.add_function(wrap_pyfunction!(optimal_start, m)?)?;
+    m.add_function(wrap_pyfunction!(to_ics, m)?)?;
+    m.add_function(wrap_pyfunction!(cheapest_hours, m)?)?;
+    m.add_function(wrap_pyfunction!(most_expensive_hours, m)?)?;
+    m.add_function(wrap_pyfunction!(most_expensive_contiguous_window, m)?)?;
+    m.add_function(wrap_pyfunction!(optimize_battery_schedule, m)?)?;
+    m.add_function(wrap_pyfunction!(cycle_break_even, m)?)?;
+    m.add_function(wrap_pyfunction!(normalize_timestamp, m)?)?;
+    m.add_function(wrap_pyfunction!(convert_temperature, m)?)?;
+    m.add_function(wrap_pyfunction!(to_grafana_timeseries, m)?)?;
+    m.add_function(wrap_pyfunction!(to_awattar_format, m)?)?;
+    m.add_function(wrap_pyfunction!(from_awattar_format, m)?)?;
+    m.add_function(wrap_pyfunction!(forecast_confidence, m)?)?;
+    m.add_function(wrap_pyfunction!(set_confidence_half_life, m)?)?;
+    m.add_function(wrap_pyfunction!(set_duplicate_hour_policy, m)?)?;
+    m.add_function(wrap_pyfunction!(set_fixed_clock, m)?)?;
+    m.add_function(wrap_pyfunction!(get_weather_at, m)?)?;
+    m.add_function(wrap_pyfunction!(geocode_city, m)?)?;
+    m.add_function(wrap_pyfunction!(clear_geocode_cache, m)?)?;
+    m.add_function(wrap_pyfunction!(set_geocode_cache_file, m)?)?;
+    m.add_function(wrap_pyfunction!(load_actual_pv, m)?)?;
+    m.add_function(wrap_pyfunction!(compare_pv_forecast, m)?)?;
+    m.add_function(wrap_pyfunction!(estimate_savings, m)?)?;
+    m.add_function(wrap_pyfunction!(simulate_load_shift, m)?)?;
+    m.add_function(wrap_pyfunction!(compare_to_flat_tariff, m)?)?;
+    m.add_function(wrap_pyfunction!(diff_snapshots, m)?)?;
+    m.add_function(wrap_pyfunction!(forecast_delta_alert, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_saved_file, m)?)?;
+    m.add_function(wrap_pyfunction!(fetch_weather_with_fallback, m)?)?;
+    m.add_function(wrap_pyfunction!(ensemble_weather, m)?)?;
+    m.add_function(wrap_pyfunction!(clear_cache, m)?)?;
+    m.add_function(wrap_pyfunction!(cache_stats, m)?)?;
+    m.add_function(wrap_pyfunction!(set_cache_ttl, m)?)?;
+    m.add_function(wrap_pyfunction!(warmup, m)?)?;
+    m.add_function(wrap_pyfunction!(start_background_refresh, m)?)?;
+    m.add_function(wrap_pyfunction!(set_max_response_bytes, m)?)?;
+    m.add_function(wrap_pyfunction!(set_http_timeouts, m)?)?;
+    m.add_function(wrap_pyfunction!(configure_call_budget, m)?)?;
+    m.add_function(wrap_pyfunction!(calls_used_today, m)?)?;
+    m.add_function(wrap_pyfunction!(configure_circuit_breaker, m)?)?;
+    m.add_function(wrap_pyfunction!(reset_circuit_breakers, m)?)?;
+    m.add_function(wrap_pyfunction!(set_min_request_interval, m)?)?;
+    m.add_class::<solar::IrradiancePoint>()?;
+    m.add_class::<merge::HourlyPoint>()?;
+    m.add_class::<FetchResult>()?;
+    m.add_class::<MultiDayPrices>()?;
+    m.add_class::<health::HealthReport>()?;
+    m.add_class::<preflight::ValidationReport>()?;
+    m.add_class::<history::HistoricalWeather>()?;
+    m.add_class::<store::SeriesStore>()?;
+    m.add_class::<pv::PvAccuracy>()?;
+    m.add_class::<prices::DailyPrices>()?;
+    m.add_class::<summary::Summary>()?;
+    m.add_class::<outlook::PanelSpec>()?;
+    m.add_class::<outlook::Outlook>()?;
+    m.add_class::<alerts::Recommendation>()?;
+    m.add_class::<savings::SavingsReport>()?;
+    m.add_class::<savings::LoadShift>()?;
+    m.add_class::<savings::TariffComparison>()?;
+    m.add_class::<battery::BatteryStep>()?;
+    m.add_class::<battery::BatterySchedule>()?;
+    m.add_class::<diff::HourDelta>()?;
+    m.add_class::<diff::SnapshotDiff>()?;
+    m.add_class::<diff::ForecastDeltaAlert>()?;
+    m.add_class::<spreads::RegionSpread>()?;
+    m.add_class::<weather::WeatherHour>()?;
+    m.add_class::<weather::WeatherData>()?;
+    m.add_class::<refresh::BackgroundRefresh>()?;
+    #[cfg(feature = "live")]
+    m.add_function(wrap_pyfunction!(start_live_server, m)?)?;
+    #[cfg(feature = "xlsx")]
+    m.add_function(wrap_pyfunction!(export_xlsx, m)?)?;
     Ok(())
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    // Deserializes `value` as `T` and serializes it back; the saved shape must survive.
+    fn roundtrip<T: Serialize + serde::de::DeserializeOwned>(value: serde_json::Value) -> serde_json::Value {
+        let parsed: T = serde_json::from_value(value).expect("deserializes");
+        let saved = serde_json::to_string(&parsed).expect("serializes");
+        serde_json::from_str(&saved).expect("saved JSON parses")
+    }
+
+    #[test]
+    fn one_call_response_roundtrips_with_all_blocks() {
+        let body = json!({
+            "current": {"main": {"temp": 4.5, "feels_like": 1.2, "humidity": 80, "pressure": 1012}, "weather": [{"description": "mist", "icon": "50n"}], "dt": 1700000000},
+            "hourly": [
+                {"dt": 1700000000, "temp": 4.5, "weather": [{"description": "mist", "icon": "50n"}], "pop": 0.2, "clouds": {"all": 90},
+                 "pressure": 1012, "dew_point": 3.1, "visibility": 4000, "rain": {"1h": 0.4}, "confidence": 1.0},
+                {"dt": 1700003600, "temp": 4.0, "weather": [], "pop": 0.0, "clouds": {"all": 75}, "snow": {"1h": 1.5}}
+            ],
+            "minutely": [{"dt": 1700000000, "precipitation": 0}],
+            "daily": [{"dt": 1700000000}],
+            "alerts": [],
+            "config_hash": "0123456789abcdef"
+        });
+        assert_eq!(roundtrip::<OpenWeatherOneCallResponse>(body.clone()), body);
+    }
+
+    #[test]
+    fn one_call_response_roundtrips_without_blocks() {
+        // Excluded blocks stay absent rather than coming back as null.
+        assert_eq!(roundtrip::<OpenWeatherOneCallResponse>(json!({"hourly": []})), json!({"hourly": []}));
+        assert_eq!(roundtrip::<OpenWeatherOneCallResponse>(json!({})), json!({"hourly": []}));
+    }
+
+    #[test]
+    fn one_call_response_without_current_still_parses_hourly() {
+        // What an `exclude=current,minutely,daily,alerts` request returns.
+        let body = r#"{"lat": 52.52, "lon": 13.405, "timezone": "Europe/Berlin", "hourly": [
+            {"dt": 1700003600, "temp": 4.0, "weather": [{"description": "overcast clouds", "icon": "04n"}], "pop": 0.1, "clouds": {"all": 100}},
+            {"dt": 1700000000, "temp": 4.5, "weather": [{"description": "mist", "icon": "50n"}], "pop": 0.2, "clouds": {"all": 90}}
+        ]}"#;
+        let response = parse_openweather_body(body.to_string()).expect("parses without current");
+        assert!(response.current.is_none());
+        assert!(response.minutely.is_none() && response.daily.is_none());
+        let hours: Vec<i64> = response.hourly.iter().map(|h| h.dt).collect();
+        assert_eq!(hours, [1700000000, 1700003600]);
+        assert_eq!(response.hourly[1].clouds.all, 100);
+    }
+
+    const START_MS: i64 = 1_700_000_000_000;
+    const END_MS: i64 = 1_700_172_800_000; // 48 hours later
+
+    #[test]
+    fn time_range_accepts_an_ordered_millisecond_window() {
+        assert!(validate_time_range(START_MS, END_MS).is_ok());
+    }
+
+    #[test]
+    fn time_range_rejects_swapped_bounds() {
+        let Err(CollectorError::InvalidTimeRange(message)) = validate_time_range(END_MS, START_MS) else {
+            panic!("swapped bounds accepted");
+        };
+        assert!(message.contains(&START_MS.to_string()) && message.contains(&END_MS.to_string()), "{}", message);
+        assert!(matches!(validate_time_range(START_MS, START_MS), Err(CollectorError::InvalidTimeRange(_))));
+    }
+
+    #[test]
+    fn time_range_rejects_seconds_with_a_hint() {
+        let Err(CollectorError::InvalidTimeRange(message)) = validate_time_range(START_MS / 1000, END_MS / 1000) else {
+            panic!("seconds accepted as milliseconds");
+        };
+        assert!(message.contains("looks like seconds") && message.contains(&START_MS.to_string()), "{}", message);
+        assert!(matches!(validate_time_range(START_MS, END_MS / 1000), Err(CollectorError::InvalidTimeRange(_))));
+    }
+
+    #[test]
+    fn smard_fetch_rejects_a_swapped_window_before_requesting() {
+        let result = get_smard_day_ahead_prices(SMARD_BASE_URL, SMARD_PRICE_FILTER, "DE", SMARD_RESOLUTION, END_MS, START_MS);
+        assert!(matches!(result, Err(CollectorError::InvalidTimeRange(_))));
+    }
+
+    #[test]
+    fn as_of_pins_the_fetch_window() {
+        let as_of = START_MS + 6 * 3_600_000;
+        let now = Utc.timestamp_millis_opt(as_of).unwrap();
+        assert_eq!(lookback_window_ms(now, None), (as_of - 48 * 3_600_000, as_of));
+        let last_ms = as_of - 3_600_000;
+        assert_eq!(lookback_window_ms(now, Some(last_ms)), (last_ms - storage::INCREMENTAL_OVERLAP.num_milliseconds(), as_of));
+        // State newer than `as_of` (a replay of an older run) still yields a valid window.
+        let (start, end) = lookback_window_ms(now, Some(as_of + 86_400_000));
+        assert!(start < end);
+    }
+
+    #[test]
+    fn smard_response_roundtrips() {
+        let body = json!({"data": [{"timestamp": 1700000000000_i64, "value": 95.3}, {"timestamp": 1700003600000_i64, "value": null}]});
+        // `source_unit` and `config_hash` are None, so they're left out again.
+        assert_eq!(roundtrip::<SmardApiResponse>(body.clone()), body);
+        let declared = json!({"data": [], "source_unit": "ct/kWh", "config_hash": "0123456789abcdef"});
+        assert_eq!(roundtrip::<SmardApiResponse>(declared.clone()), declared);
+        assert_eq!(roundtrip::<SmardApiResponse>(json!({"data": []})), json!({"data": []}));
+    }
+
+    #[test]
+    fn hourly_point_roundtrips() {
+        let point = json!({
+            "timestamp": 1700000000, "temp": 4.5, "clouds": 90, "pop": 0.2, "rain_1h": 0.4, "snow_1h": null,
+            "pressure": 1012, "dew_point": null, "visibility": 4000, "description": "mist", "ghi": null, "price": 95.3
+        });
+        assert_eq!(roundtrip::<merge::HourlyPoint>(point.clone()), point);
+    }
+
+    #[test]
+    fn error_body_sent_with_200_is_an_api_error() {
+        for body in [r#"{"cod":401,"message":"Invalid API key. Please see https://openweathermap.org/faq#error401 for more info."}"#,
+                     r#"{"cod":"401","message":"Invalid API key"}"#] {
+            match embedded_openweather_error(body, "One Call 3.0") {
+                Some(CollectorError::Api { code: 401, message }) => assert!(message.starts_with("Invalid API key")),
+                other => panic!("expected Api error, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn plan_error_body_sent_with_200_is_plan_restricted() {
+        let body = r#"{"cod":401,"message":"Please note that using One Call 3.0 requires a separate subscription to the One Call by Call plan."}"#;
+        match embedded_openweather_error(body, "One Call 3.0") {
+            Some(CollectorError::PlanRestricted { feature, .. }) => assert_eq!(feature, "One Call 3.0"),
+            other => panic!("expected PlanRestricted, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn successful_bodies_are_not_errors() {
+        assert!(embedded_openweather_error(r#"{"lat":52.52,"lon":13.405,"timezone":"Europe/Berlin","hourly":[]}"#, "One Call 3.0").is_none());
+        assert!(embedded_openweather_error(r#"{"cod":"200","message":"0"}"#, "One Call 3.0").is_none());
+        assert!(embedded_openweather_error("not json", "One Call 3.0").is_none());
+    }
+
+    #[test]
+    fn one_call_cache_key_leaves_out_the_api_key() {
+        let key = one_call_cache_key(52.52, 13.405, &OneCallOptions::default());
+        assert_eq!(key, "onecall/3.0?lat=52.52&lon=13.405&units=metric&exclude=minutely,daily,alerts");
+        assert!(!key.contains("appid"), "{}", key);
+    }
+
+    #[test]
+    fn one_call_cache_key_follows_the_query() {
+        let defaults = OneCallOptions::default();
+        let with_daily = OneCallOptions { blocks: OneCallBlocks { daily: true, ..defaults.blocks }, ..defaults };
+        let on_v2_5 = OneCallOptions { api_version: OneCallVersion::V2_5, ..defaults };
+        let key = |options: &OneCallOptions| one_call_cache_key(52.52, 13.405, options);
+        assert_ne!(key(&defaults), key(&with_daily));
+        assert_ne!(key(&defaults), key(&on_v2_5));
+        assert_ne!(key(&defaults), one_call_cache_key(48.14, 11.58, &defaults));
+    }
+
+    #[test]
+    fn hourly_only_response_does_not_answer_a_daily_request() {
+        // The cache is shared with every other test, so use a location of our own and only
+        // compare counters before and after.
+        let (lat, lon) = (-12.3456, 98.7654);
+        let defaults = OneCallOptions::default();
+        let with_daily = OneCallOptions { blocks: OneCallBlocks { daily: true, ..defaults.blocks }, ..defaults };
+        cache::weather().insert(one_call_cache_key(lat, lon, &defaults), r#"{"hourly":[]}"#.to_string());
+
+        let before = cache::weather().stats();
+        assert!(cache::weather().get(&one_call_cache_key(lat, lon, &with_daily)).is_none());
+        assert!(cache::weather().stats().misses > before.misses);
+
+        let before = cache::weather().stats();
+        assert_eq!(cache::weather().get(&one_call_cache_key(lat, lon, &defaults)).as_deref(), Some(r#"{"hourly":[]}"#));
+        assert!(cache::weather().stats().hits > before.hits);
+    }
+
+    #[test]
+    fn refresh_keeps_only_its_own_recent_value() {
+        let now = std::time::Instant::now();
+        assert!(!kept_fresh(None));
+        assert!(kept_fresh(Some(now)));
+        if let Some(long_ago) = now.checked_sub(MAX_REFRESH_STALENESS + std::time::Duration::from_secs(1)) {
+            assert!(!kept_fresh(Some(long_ago)));
+        }
+    }
+
+}
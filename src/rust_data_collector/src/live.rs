@@ -0,0 +1,195 @@
+// src/rust_data_collector/src/live.rs
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use futures_util::{SinkExt, StreamExt};
+use serde::Serialize;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::merge::HourlyPoint;
+use crate::timestamp::hour_start;
+use crate::CollectorError;
+
+// --- Live WebSocket Push Server ---
+
+// What every connected dashboard receives after a fetch completes.
+#[derive(Debug, Clone, Serialize)]
+pub struct LiveUpdate {
+    pub fetched_at: i64, // Unix timestamp (seconds)
+    pub current_price: Option<f64>, // EUR/MWh
+    pub latest: Option<HourlyPoint>,
+}
+
+/// The point for the hour containing `now` (Unix seconds), or the newest one when the
+/// series doesn't reach it.
+pub fn latest_point(hourly: &[HourlyPoint], now: i64) -> Option<HourlyPoint> {
+    let hour = hour_start(now);
+    hourly.iter().find(|p| hour_start(p.timestamp) == hour).or_else(|| hourly.iter().max_by_key(|p| p.timestamp)).cloned()
+}
+
+// The last published payload is kept so clients connecting mid-stream get it immediately
+// instead of waiting for the next fetch.
+struct LiveHub {
+    snapshot: Mutex<Option<String>>,
+    sender: broadcast::Sender<String>,
+}
+
+static HUB: OnceLock<LiveHub> = OnceLock::new();
+static SERVER_STARTED: AtomicBool = AtomicBool::new(false);
+
+fn hub() -> &'static LiveHub {
+    HUB.get_or_init(|| {
+        let (sender, _) = broadcast::channel(16);
+        LiveHub { snapshot: Mutex::new(None), sender }
+    })
+}
+
+/// Stores the update as the current snapshot and pushes it to all connected clients.
+/// Cheap no-op (apart from the snapshot) when the server isn't running.
+pub fn publish(update: &LiveUpdate) {
+    let payload = match serde_json::to_string(update) {
+        Ok(payload) => payload,
+        Err(e) => {
+            log!("ERROR (Rust): Failed to serialize live update: {}", e);
+            return;
+        }
+    };
+    *hub().snapshot.lock().unwrap() = Some(payload.clone());
+    // send() only fails when nobody is subscribed, which is fine.
+    let _ = hub().sender.send(payload);
+}
+
+/// Binds `addr` and serves WebSocket clients on a background thread. Binding happens
+/// before returning so a port conflict is reported to the caller. Only one server
+/// runs per process; later calls are ignored.
+pub fn start_server(addr: &str) -> Result<(), CollectorError> {
+    if SERVER_STARTED.swap(true, Ordering::SeqCst) {
+        log!("DEBUG (Rust): Live server already running, ignoring start request for {}", addr);
+        return Ok(());
+    }
+    let listener = std::net::TcpListener::bind(addr)
+        .and_then(|l| l.set_nonblocking(true).map(|_| l))
+        .map_err(|e| {
+            SERVER_STARTED.store(false, Ordering::SeqCst);
+            CollectorError::Io(e)
+        })?;
+    log!("DEBUG (Rust): Live WebSocket server listening on {}", addr);
+
+    std::thread::spawn(move || {
+        let runtime = match tokio::runtime::Builder::new_multi_thread().enable_all().build() {
+            Ok(runtime) => runtime,
+            Err(e) => {
+                log!("ERROR (Rust): Failed to start live server runtime: {}", e);
+                return;
+            }
+        };
+        runtime.block_on(async move {
+            let listener = match TcpListener::from_std(listener) {
+                Ok(listener) => listener,
+                Err(e) => {
+                    log!("ERROR (Rust): Failed to hand listener to tokio: {}", e);
+                    return;
+                }
+            };
+            loop {
+                match listener.accept().await {
+                    Ok((stream, peer)) => {
+                        log!("DEBUG (Rust): Live client connected from {}", peer);
+                        tokio::spawn(handle_client(stream));
+                    }
+                    Err(e) => log!("ERROR (Rust): Failed to accept live client: {}", e),
+                }
+            }
+        });
+    });
+    Ok(())
+}
+
+async fn handle_client(stream: TcpStream) {
+    let mut ws = match tokio_tungstenite::accept_async(stream).await {
+        Ok(ws) => ws,
+        Err(e) => {
+            log!("ERROR (Rust): WebSocket handshake failed: {}", e);
+            return;
+        }
+    };
+
+    // Subscribe before reading the snapshot so no update can slip in between.
+    let mut updates = hub().sender.subscribe();
+    let snapshot = hub().snapshot.lock().unwrap().clone();
+    if let Some(payload) = snapshot {
+        if ws.send(Message::Text(payload.into())).await.is_err() {
+            return;
+        }
+    }
+
+    loop {
+        tokio::select! {
+            update = updates.recv() => match update {
+                Ok(payload) => {
+                    if ws.send(Message::Text(payload.into())).await.is_err() {
+                        break;
+                    }
+                }
+                // A slow client missed some updates; the next one is a full snapshot anyway.
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            incoming = ws.next() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {} // Clients only listen; ignore anything they send.
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(timestamp: i64) -> HourlyPoint {
+        HourlyPoint {
+            timestamp,
+            temp: 10.0,
+            clouds: 50,
+            pop: 0.0,
+            rain_1h: None,
+            snow_1h: None,
+            pressure: None,
+            dew_point: None,
+            visibility: None,
+            description: "clear sky".to_string(),
+            ghi: None,
+            price: Some(80.0),
+        }
+    }
+
+    #[test]
+    fn latest_point_is_the_current_hour() {
+        let start = 1_699_999_200; // A whole hour
+        let hourly: Vec<HourlyPoint> = (0..4).map(|h| point(start + h * 3600)).collect();
+        let now = start + 2 * 3600 + 1800;
+        assert_eq!(latest_point(&hourly, now).map(|p| p.timestamp), Some(start + 2 * 3600));
+        // Past the end of the series, the newest point.
+        assert_eq!(latest_point(&hourly, now + 86_400).map(|p| p.timestamp), Some(start + 3 * 3600));
+        assert!(latest_point(&[], now).is_none());
+    }
+
+    #[test]
+    fn client_connecting_mid_stream_gets_the_snapshot_first() {
+        let update = LiveUpdate { fetched_at: 1_700_000_000, current_price: Some(80.5), latest: None };
+        publish(&update);
+        let addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        start_server(&addr.to_string()).unwrap();
+
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+        let first = runtime.block_on(async {
+            let (mut ws, _) = tokio_tungstenite::connect_async(format!("ws://{}", addr)).await.unwrap();
+            ws.next().await.unwrap().unwrap()
+        });
+        assert_eq!(first.into_text().unwrap().as_str(), serde_json::to_string(&update).unwrap());
+    }
+}
@@ -0,0 +1,82 @@
+// src/rust_data_collector/src/loopback.rs
+
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::thread::{self, JoinHandle};
+
+// --- Loopback Test Server ---
+
+// Canned HTTP answers on 127.0.0.1 for the tests that go through the real client.
+
+/// Answers one request with `status_line` (e.g. "503 Service Unavailable"), `headers` (each
+/// ending in "\r\n") and `body`. Returns the base URL ("http://127.0.0.1:PORT") and a handle
+/// yielding the request line and headers the server received.
+pub fn serve_once(status_line: &'static str, headers: &str, body: &[u8]) -> (String, JoinHandle<Vec<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let (headers, body) = (headers.to_string(), body.to_vec());
+    let server = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut request = Vec::new();
+        let mut line = String::new();
+        while reader.read_line(&mut line).unwrap() > 2 {
+            request.push(line.trim_end().to_string());
+            line.clear();
+        }
+        let mut stream = reader.into_inner();
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n",
+            status_line, headers, body.len()
+        ).unwrap();
+        stream.write_all(&body).unwrap();
+        request
+    });
+    (url, server)
+}
+
+/// Answers every request with what `respond(path)` returns: status line, headers (each
+/// ending in "\r\n") and body. Returns the base URL; the server lives as long as the test
+/// process.
+pub fn serve<F>(respond: F) -> String
+where
+    F: Fn(&str) -> (&'static str, String, Vec<u8>) + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut reader = BufReader::new(stream.unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+            let (status_line, headers, body) = respond(path);
+            let mut stream = reader.into_inner();
+            write!(
+                stream,
+                "HTTP/1.1 {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n",
+                status_line, headers, body.len()
+            ).unwrap();
+            stream.write_all(&body).unwrap();
+        }
+    });
+    url
+}
+
+/// The value of header `name` among the request lines `serve_once` hands back.
+pub fn header<'a>(request: &'a [String], name: &str) -> Option<&'a str> {
+    request.iter()
+        .find_map(|line| line.split_once(':').filter(|(n, _)| n.eq_ignore_ascii_case(name)))
+        .map(|(_, value)| value.trim())
+}
+
+/// A base URL nothing listens on.
+pub fn closed_port_url() -> String {
+    let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    format!("http://127.0.0.1:{}", port)
+}
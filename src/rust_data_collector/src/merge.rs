@@ -0,0 +1,198 @@
+// src/rust_data_collector/src/merge.rs
+
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::quality::Warning;
+use crate::solar::IrradiancePoint;
+use crate::timestamp::{hour_start, to_unix_seconds};
+use crate::{CollectorError, OpenWeatherOneCallResponse, SmardApiResponse};
+
+// --- Weather / Price Alignment ---
+
+// One forecast hour with the matching day-ahead price (and irradiance, if a solar
+// provider was queried) attached. Exposed to Python with attribute access.
+#[pyclass(get_all)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HourlyPoint {
+    pub timestamp: i64, // Unix timestamp (seconds), start of the hour
+    pub temp: f64,
+    pub clouds: i32,
+    pub pop: f64,
+    pub rain_1h: Option<f64>, // mm over the hour
+    pub snow_1h: Option<f64>, // mm over the hour
+    pub pressure: Option<i32>,   // hPa
+    pub dew_point: Option<f64>,  // °C
+    pub visibility: Option<i32>, // m
+    pub description: String,
+    pub ghi: Option<f64>,   // W/m²
+    pub price: Option<f64>, // EUR/MWh
+}
+
+#[pymethods]
+impl HourlyPoint {
+    fn __repr__(&self) -> String {
+        format!(
+            "HourlyPoint(timestamp={}, temp={}, clouds={}, pop={}, rain_1h={:?}, snow_1h={:?}, pressure={:?}, dew_point={:?}, visibility={:?}, description={:?}, ghi={:?}, price={:?})",
+            self.timestamp, self.temp, self.clouds, self.pop, self.rain_1h, self.snow_1h,
+            self.pressure, self.dew_point, self.visibility, self.description, self.ghi, self.price
+        )
+    }
+}
+
+/// Aligns each hourly weather entry with the SMARD price for the same hour.
+/// Every weather hour is kept; `price` is `None` where SMARD has no value.
+pub fn merge_hourly(weather: &OpenWeatherOneCallResponse, prices: &SmardApiResponse) -> Vec<HourlyPoint> {
+    weather.hourly.iter()
+        .map(|hour| {
+            let key = hour_start(hour.dt);
+            let price = prices.data.iter()
+                .find(|dp| hour_start(dp.timestamp) == key)
+                .and_then(|dp| dp.value);
+            HourlyPoint {
+                timestamp: key,
+                temp: hour.temp,
+                clouds: hour.clouds.all,
+                pop: hour.pop,
+                rain_1h: hour.rain_1h,
+                snow_1h: hour.snow_1h,
+                pressure: hour.pressure,
+                dew_point: hour.dew_point,
+                visibility: hour.visibility,
+                description: hour.weather.first().map(|w| w.description.clone()).unwrap_or_default(),
+                ghi: None,
+                price,
+            }
+        })
+        .collect()
+}
+
+// Both providers stamp hour starts in UTC. Minutes past the hour, once normalized to Unix
+// seconds, mean a local offset such as +05:30 was applied on the way; the merge floors them
+// to the hour and can pair the wrong hours.
+fn unaligned_timestamps(source: &str, timestamps: impl Iterator<Item = i64>) -> Option<Warning> {
+    let mut total = 0;
+    let mut offsets: Vec<i64> = Vec::new();
+    for ts in timestamps {
+        total += 1;
+        let offset = to_unix_seconds(ts).rem_euclid(3600);
+        if offset != 0 {
+            offsets.push(offset);
+        }
+    }
+    let first = *offsets.first()?;
+    let detail = if offsets.iter().all(|&o| o == first) {
+        format!("{} min past the hour", first / 60)
+    } else {
+        "at varying minutes past the hour".to_string()
+    };
+    Some(Warning::new("unaligned_timestamps", format!(
+        "{} of {} {} timestamps are {}; they may carry a local offset instead of UTC",
+        offsets.len(), total, source, detail
+    )))
+}
+
+/// Warnings for inputs to `merge_hourly` whose timestamps aren't whole UTC hours.
+pub fn timestamp_warnings(weather: &OpenWeatherOneCallResponse, prices: &SmardApiResponse) -> Vec<Warning> {
+    [
+        unaligned_timestamps("weather", weather.hourly.iter().map(|hour| hour.dt)),
+        unaligned_timestamps("price", prices.data.iter().map(|dp| dp.timestamp)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// Fills `ghi` on each merged hour from a solar provider's forecast for the same hour.
+pub fn attach_irradiance(points: &mut [HourlyPoint], irradiance: &[IrradiancePoint]) {
+    for point in points.iter_mut() {
+        point.ghi = irradiance.iter()
+            .find(|ir| hour_start(ir.timestamp) == point.timestamp)
+            .map(|ir| ir.ghi);
+    }
+}
+
+// --- Grafana Export ---
+
+pub const GRAFANA_METRICS: [&str; 10] = [
+    "price", "temp", "clouds", "pop", "rain_1h", "snow_1h", "pressure", "dew_point", "visibility", "ghi",
+];
+
+fn metric_value(point: &HourlyPoint, metric: &str) -> Option<f64> {
+    match metric {
+        "price" => point.price,
+        "temp" => Some(point.temp),
+        "clouds" => Some(point.clouds as f64),
+        "pop" => Some(point.pop),
+        "rain_1h" => point.rain_1h,
+        "snow_1h" => point.snow_1h,
+        "pressure" => point.pressure.map(f64::from),
+        "dew_point" => point.dew_point,
+        "visibility" => point.visibility.map(f64::from),
+        "ghi" => point.ghi,
+        _ => None,
+    }
+}
+
+/// Builds the `[{"target": ..., "datapoints": [[value, ts_ms], ...]}, ...]` shape the
+/// Grafana JSON/SimpleJSON datasource expects, one target per metric. Missing values are
+/// emitted as `null` so panels show a gap rather than a wrong zero.
+pub fn to_grafana_timeseries(points: &[HourlyPoint], metric_names: &[String]) -> Result<serde_json::Value, CollectorError> {
+    let targets = metric_names.iter()
+        .map(|metric| {
+            if !GRAFANA_METRICS.contains(&metric.as_str()) {
+                return Err(CollectorError::InvalidInput(format!(
+                    "Unknown metric {:?}, expected one of {:?}",
+                    metric, GRAFANA_METRICS
+                )));
+            }
+            let datapoints: Vec<serde_json::Value> = points.iter()
+                .map(|p| serde_json::json!([metric_value(p, metric), p.timestamp * 1000]))
+                .collect();
+            Ok(serde_json::json!({ "target": metric, "datapoints": datapoints }))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(serde_json::Value::Array(targets))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn point(timestamp: i64, price: Option<f64>) -> HourlyPoint {
+        HourlyPoint {
+            timestamp,
+            temp: 12.5,
+            clouds: 40,
+            pop: 0.1,
+            rain_1h: None,
+            snow_1h: None,
+            pressure: Some(1015),
+            dew_point: None,
+            visibility: None,
+            description: "scattered clouds".to_string(),
+            ghi: None,
+            price,
+        }
+    }
+
+    #[test]
+    fn grafana_targets_use_milliseconds_and_null_gaps() {
+        let points = [point(1_717_200_000, Some(81.2)), point(1_717_203_600, None)];
+        let metrics = ["price".to_string(), "pressure".to_string()];
+        assert_eq!(
+            to_grafana_timeseries(&points, &metrics).unwrap(),
+            json!([
+                {"target": "price", "datapoints": [[81.2, 1_717_200_000_000_i64], [null, 1_717_203_600_000_i64]]},
+                {"target": "pressure", "datapoints": [[1015.0, 1_717_200_000_000_i64], [1015.0, 1_717_203_600_000_i64]]}
+            ])
+        );
+    }
+
+    #[test]
+    fn grafana_rejects_unknown_metrics() {
+        let result = to_grafana_timeseries(&[point(1_717_200_000, None)], &["humidity".to_string()]);
+        assert!(matches!(result, Err(CollectorError::InvalidInput(_))));
+    }
+}
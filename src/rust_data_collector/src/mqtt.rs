@@ -0,0 +1,208 @@
+// src/rust_data_collector/src/mqtt.rs
+
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use crate::{http, secrets, CollectorError};
+
+// --- MQTT Export ---
+
+// Publishes the fetched data to an MQTT broker for home-automation setups (Home Assistant,
+// Node-RED). Only what that needs is spoken: MQTT 3.1.1 over plain TCP, one connection per
+// run, QoS 0 and retained messages, so a dashboard subscribing later still gets the latest
+// data. Connection details come from the secrets file or the environment like the API keys:
+// MQTT_HOST ("host" or "host:port", default port 1883), MQTT_TOPIC (the prefix, e.g.
+// "energy") and optionally MQTT_USERNAME and MQTT_PASSWORD.
+
+const DEFAULT_PORT: u16 = 1883;
+const KEEP_ALIVE_SECS: u16 = 60;
+// Largest remaining length the four-byte encoding can express.
+const MAX_REMAINING_LENGTH: usize = 268_435_455;
+
+#[derive(Debug, Clone)]
+pub struct MqttConfig {
+    pub host: String,
+    pub topic: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+impl MqttConfig {
+    pub fn load(secrets_file: Option<&str>) -> Result<Self, CollectorError> {
+        Ok(MqttConfig {
+            host: secrets::get_secret("MQTT_HOST", secrets_file)?,
+            topic: secrets::get_secret("MQTT_TOPIC", secrets_file)?.trim_end_matches('/').to_string(),
+            username: secrets::get_secret("MQTT_USERNAME", secrets_file).ok(),
+            password: secrets::get_secret("MQTT_PASSWORD", secrets_file).ok(),
+        })
+    }
+
+    fn address(&self) -> String {
+        if self.host.contains(':') {
+            self.host.clone()
+        } else {
+            format!("{}:{}", self.host, DEFAULT_PORT)
+        }
+    }
+}
+
+fn push_str(packet: &mut Vec<u8>, value: &str) -> Result<(), CollectorError> {
+    let len = u16::try_from(value.len())
+        .map_err(|_| CollectorError::InvalidInput(format!("MQTT string of {} bytes is too long", value.len())))?;
+    packet.extend_from_slice(&len.to_be_bytes());
+    packet.extend_from_slice(value.as_bytes());
+    Ok(())
+}
+
+// Fixed header: packet type and flags, then the remaining length, 7 bits per byte.
+fn packet(first_byte: u8, body: &[u8]) -> Result<Vec<u8>, CollectorError> {
+    if body.len() > MAX_REMAINING_LENGTH {
+        return Err(CollectorError::InvalidInput(format!("MQTT message of {} bytes is too large", body.len())));
+    }
+    let mut out = vec![first_byte];
+    let mut len = body.len();
+    loop {
+        let byte = (len % 128) as u8;
+        len /= 128;
+        out.push(if len > 0 { byte | 0x80 } else { byte });
+        if len == 0 {
+            break;
+        }
+    }
+    out.extend_from_slice(body);
+    Ok(out)
+}
+
+fn connect_packet(config: &MqttConfig) -> Result<Vec<u8>, CollectorError> {
+    let mut flags = 0x02; // Clean session
+    if config.username.is_some() {
+        flags |= 0x80;
+    }
+    if config.password.is_some() {
+        flags |= 0x40;
+    }
+    let mut body = Vec::new();
+    push_str(&mut body, "MQTT")?;
+    body.push(4); // Protocol level 3.1.1
+    body.push(flags);
+    body.extend_from_slice(&KEEP_ALIVE_SECS.to_be_bytes());
+    push_str(&mut body, &format!("rust_data_collector-{}", std::process::id()))?;
+    for value in [&config.username, &config.password].into_iter().flatten() {
+        push_str(&mut body, value)?;
+    }
+    packet(0x10, &body)
+}
+
+// QoS 0, retained.
+fn publish_packet(topic: &str, payload: &[u8]) -> Result<Vec<u8>, CollectorError> {
+    let mut body = Vec::new();
+    push_str(&mut body, topic)?;
+    body.extend_from_slice(payload);
+    packet(0x31, &body)
+}
+
+/// Publishes each `(subtopic, payload)` under the configured prefix, e.g. "energy/prices".
+pub fn publish(config: &MqttConfig, messages: &[(&str, String)]) -> Result<(), CollectorError> {
+    if messages.is_empty() {
+        return Ok(());
+    }
+    let address = config.address().to_socket_addrs()?.next()
+        .ok_or_else(|| CollectorError::InvalidInput(format!("MQTT_HOST {:?} doesn't resolve", config.host)))?;
+    let mut stream = TcpStream::connect_timeout(&address, Duration::from_secs(http::DEFAULT_CONNECT_TIMEOUT_SECS))?;
+    stream.set_read_timeout(Some(Duration::from_secs(http::DEFAULT_TIMEOUT_SECS)))?;
+    stream.set_write_timeout(Some(Duration::from_secs(http::DEFAULT_TIMEOUT_SECS)))?;
+
+    stream.write_all(&connect_packet(config)?)?;
+    let mut connack = [0u8; 4];
+    stream.read_exact(&mut connack)?;
+    if connack[0] != 0x20 || connack[3] != 0 {
+        return Err(CollectorError::Io(io::Error::new(
+            io::ErrorKind::ConnectionRefused,
+            format!("MQTT broker {} refused the connection (CONNACK {:02x?})", config.host, connack),
+        )));
+    }
+    for (subtopic, payload) in messages {
+        stream.write_all(&publish_packet(&format!("{}/{}", config.topic, subtopic), payload.as_bytes())?)?;
+    }
+    stream.write_all(&[0xE0, 0x00])?; // DISCONNECT
+    stream.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    // Reads one packet: first byte and body.
+    fn read_packet(stream: &mut TcpStream) -> (u8, Vec<u8>) {
+        let mut byte = [0u8; 1];
+        stream.read_exact(&mut byte).unwrap();
+        let first = byte[0];
+        let (mut len, mut shift) = (0usize, 0);
+        loop {
+            stream.read_exact(&mut byte).unwrap();
+            len |= ((byte[0] & 0x7f) as usize) << shift;
+            shift += 7;
+            if byte[0] & 0x80 == 0 {
+                break;
+            }
+        }
+        let mut body = vec![0u8; len];
+        stream.read_exact(&mut body).unwrap();
+        (first, body)
+    }
+
+    #[test]
+    fn remaining_length_uses_continuation_bytes() {
+        assert_eq!(packet(0xE0, &[]).unwrap(), vec![0xE0, 0x00]);
+        assert_eq!(&packet(0x31, &[0; 321]).unwrap()[..3], &[0x31, 0xC1, 0x02]);
+    }
+
+    #[test]
+    fn publishes_retained_messages_to_a_broker() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let config = MqttConfig {
+            host: listener.local_addr().unwrap().to_string(),
+            topic: "energy".to_string(),
+            username: Some("collector".to_string()),
+            password: Some("secret".to_string()),
+        };
+        let broker = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let (connect, body) = read_packet(&mut stream);
+            assert_eq!((connect, &body[..7], body[7]), (0x10, &b"\x00\x04MQTT\x04"[..], 0xC2));
+            stream.write_all(&[0x20, 0x02, 0x00, 0x00]).unwrap();
+            let mut received = Vec::new();
+            loop {
+                let (first, body) = read_packet(&mut stream);
+                if first == 0xE0 {
+                    return received;
+                }
+                assert_eq!(first, 0x31);
+                let topic_len = u16::from_be_bytes([body[0], body[1]]) as usize;
+                let topic = String::from_utf8(body[2..2 + topic_len].to_vec()).unwrap();
+                received.push((topic, String::from_utf8(body[2 + topic_len..].to_vec()).unwrap()));
+            }
+        });
+        publish(&config, &[("prices", "{\"data\":[]}".to_string()), ("hourly", "[]".to_string())]).unwrap();
+        assert_eq!(broker.join().unwrap(), vec![
+            ("energy/prices".to_string(), "{\"data\":[]}".to_string()),
+            ("energy/hourly".to_string(), "[]".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn refused_connection_is_an_error() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let config = MqttConfig { host: listener.local_addr().unwrap().to_string(), topic: "energy".to_string(), username: None, password: None };
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            read_packet(&mut stream);
+            // Return code 5: not authorized.
+            stream.write_all(&[0x20, 0x02, 0x00, 0x05]).unwrap();
+        });
+        assert!(matches!(publish(&config, &[("prices", String::new())]), Err(CollectorError::Io(_))));
+    }
+}
@@ -0,0 +1,167 @@
+// src/rust_data_collector/src/outlook.rs
+
+use chrono::{DateTime, Duration, Utc};
+use pyo3::prelude::*;
+
+use crate::prices::{current_price, most_expensive_contiguous_window, optimal_start};
+use crate::quality::Warning;
+use crate::solar::IrradiancePoint;
+use crate::timestamp::to_unix_seconds;
+use crate::{OpenWeatherOneCallResponse, SmardDataPoint};
+
+// --- Energy Outlook ---
+
+// Everything a dashboard shows on its front page, in one object: prices, the best and
+// worst hours to run things, grid carbon, PV and weather over the next 24 hours. Prices
+// and weather are required; carbon intensity and PV only add to it, so when they can't be
+// fetched their fields stay `None` and the reason is in `warnings`.
+
+pub const OUTLOOK_HORIZON: Duration = Duration::hours(24);
+pub const DEFAULT_WINDOW_HOURS: u32 = 3;
+
+const HOUR_MS: i64 = 3_600_000;
+
+// The PV system to forecast for. Solcast models the array from its peak power.
+#[pyclass(get_all)]
+#[derive(Debug, Clone, Copy)]
+pub struct PanelSpec {
+    pub capacity_kw: f64, // Installed peak power, kWp
+}
+
+#[pymethods]
+impl PanelSpec {
+    #[new]
+    fn py_new(capacity_kw: f64) -> PyResult<Self> {
+        if !capacity_kw.is_finite() || capacity_kw <= 0.0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "capacity_kw must be a positive number, got {}",
+                capacity_kw
+            )));
+        }
+        Ok(PanelSpec { capacity_kw })
+    }
+
+    fn __repr__(&self) -> String {
+        format!("PanelSpec(capacity_kw={})", self.capacity_kw)
+    }
+}
+
+#[pyclass(get_all)]
+#[derive(Debug, Clone)]
+pub struct Outlook {
+    pub generated_at: i64, // Milliseconds since epoch
+    // Prices in EUR/MWh; the 24h statistics cover the hours from the current one on.
+    pub current_price: Option<f64>,
+    pub price_min_24h: Option<f64>,
+    pub price_max_24h: Option<f64>,
+    pub price_mean_24h: Option<f64>,
+    // `(start_ms, mean EUR/MWh)` of the cheapest / dearest run of `window_hours` hours.
+    pub window_hours: u32,
+    pub cheapest_window: Option<(i64, f64)>,
+    pub most_expensive_window: Option<(i64, f64)>,
+    // gCO2/kWh. SMARD only publishes realised generation, so there is no forecast: the
+    // newest value, and the mean over the past 24h for comparison.
+    pub current_carbon_intensity: Option<f64>,
+    pub carbon_intensity_mean_24h: Option<f64>,
+    // `(unix_seconds, kW)` of the hour with the highest PV estimate; needs a `PanelSpec`.
+    pub pv_peak: Option<(i64, f64)>,
+    pub current_temp: Option<f64>, // °C
+    pub weather_description: Option<String>,
+    pub temp_min_24h: Option<f64>,
+    pub temp_max_24h: Option<f64>,
+    pub max_pop_24h: Option<f64>, // Highest chance of precipitation, 0..1
+    pub warnings: Vec<Warning>,   // `{"code", "message"}` dicts in Python
+}
+
+#[pymethods]
+impl Outlook {
+    fn __repr__(&self) -> String {
+        format!(
+            "Outlook(current_price={:?}, price_mean_24h={:?}, cheapest_window={:?}, most_expensive_window={:?}, current_carbon_intensity={:?}, pv_peak={:?}, current_temp={:?}, warnings={})",
+            self.current_price, self.price_mean_24h, self.cheapest_window, self.most_expensive_window,
+            self.current_carbon_intensity, self.pv_peak, self.current_temp, self.warnings.len()
+        )
+    }
+}
+
+fn min_max_mean(values: &[f64]) -> (Option<f64>, Option<f64>, Option<f64>) {
+    if values.is_empty() {
+        return (None, None, None);
+    }
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    (Some(min), Some(max), Some(values.iter().sum::<f64>() / values.len() as f64))
+}
+
+/// The outlook at `now` from already fetched data. `carbon` is `(timestamp_ms, gCO2/kWh)`
+/// and may be empty, `pv` is `None` without a panel; `warnings` are passed through.
+pub fn build_outlook(
+    weather: &OpenWeatherOneCallResponse,
+    prices: &[SmardDataPoint],
+    carbon: &[(i64, Option<f64>)],
+    pv: Option<&[IrradiancePoint]>,
+    now: DateTime<Utc>,
+    window_hours: u32,
+    warnings: Vec<Warning>,
+) -> Outlook {
+    let now_ms = now.timestamp_millis();
+    let hour_ms = now_ms - now_ms.rem_euclid(HOUR_MS);
+    let horizon_ms = hour_ms + OUTLOOK_HORIZON.num_milliseconds();
+
+    let upcoming: Vec<SmardDataPoint> = prices.iter()
+        .filter(|dp| dp.timestamp >= hour_ms && dp.timestamp < horizon_ms)
+        .cloned()
+        .collect();
+    let values: Vec<f64> = upcoming.iter().filter_map(|dp| dp.value).collect();
+    let (price_min_24h, price_max_24h, price_mean_24h) = min_max_mean(&values);
+    let cheapest_window = optimal_start(&upcoming, window_hours, hour_ms, horizon_ms, 1.0)
+        .ok()
+        .map(|(start, cost)| (start, cost * 1000.0 / window_hours as f64)); // EUR for 1 kW -> mean EUR/MWh
+    let most_expensive_window = most_expensive_contiguous_window(&upcoming, window_hours, now);
+
+    let carbon_points: Vec<SmardDataPoint> = carbon.iter()
+        .map(|&(timestamp, value)| SmardDataPoint { timestamp, value })
+        .collect();
+    let past_day: Vec<f64> = carbon.iter()
+        .filter(|(ts, _)| *ts > now_ms - OUTLOOK_HORIZON.num_milliseconds() && *ts <= now_ms)
+        .filter_map(|(_, value)| *value)
+        .collect();
+
+    let now_s = now.timestamp();
+    let horizon_s = now_s + OUTLOOK_HORIZON.num_seconds();
+    let pv_peak = pv.and_then(|points| {
+        points.iter()
+            .filter(|p| p.timestamp + 3600 > now_s && p.timestamp < horizon_s)
+            .filter_map(|p| Some((p.timestamp, p.pv_estimate_kw?)))
+            .reduce(|best, p| if p.1 > best.1 { p } else { best })
+    });
+
+    let hours: Vec<_> = weather.hourly.iter()
+        .filter(|h| to_unix_seconds(h.dt) + 3600 > now_s && to_unix_seconds(h.dt) < horizon_s)
+        .collect();
+    let temps: Vec<f64> = hours.iter().map(|h| h.temp).collect();
+    let (temp_min_24h, temp_max_24h, _) = min_max_mean(&temps);
+
+    Outlook {
+        generated_at: now_ms,
+        current_price: current_price(prices, now),
+        price_min_24h,
+        price_max_24h,
+        price_mean_24h,
+        window_hours,
+        cheapest_window,
+        most_expensive_window,
+        current_carbon_intensity: current_price(&carbon_points, now),
+        carbon_intensity_mean_24h: min_max_mean(&past_day).2,
+        pv_peak,
+        current_temp: weather.current.as_ref().map(|c| c.main.temp).or_else(|| hours.first().map(|h| h.temp)),
+        weather_description: weather.current.as_ref()
+            .and_then(|c| c.weather.first())
+            .or_else(|| hours.first().and_then(|h| h.weather.first()))
+            .map(|w| w.description.clone()),
+        temp_min_24h,
+        temp_max_24h,
+        max_pop_24h: hours.iter().map(|h| h.pop).reduce(f64::max),
+        warnings,
+    }
+}
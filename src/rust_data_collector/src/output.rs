@@ -0,0 +1,127 @@
+// src/rust_data_collector/src/output.rs
+
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::CollectorError;
+
+// --- Output Targets ---
+
+// Where a document is written: a file, or stdout for shell pipelines (`"-"`, as in most
+// Unix tools). While anything is being produced for stdout, diagnostics stay off it, see
+// `log_line`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutputTarget {
+    Stdout,
+    File(PathBuf),
+}
+
+impl OutputTarget {
+    pub fn parse(target: &str) -> Self {
+        if target == "-" {
+            OutputTarget::Stdout
+        } else {
+            OutputTarget::File(PathBuf::from(target))
+        }
+    }
+
+    /// Writes `contents` in full. On stdout it's newline-terminated and flushed, so the
+    /// next tool in the pipe sees complete lines.
+    pub fn write(&self, contents: &str) -> Result<(), CollectorError> {
+        match self {
+            OutputTarget::Stdout => {
+                let mut stdout = std::io::stdout().lock();
+                stdout.write_all(contents.as_bytes())?;
+                if !contents.ends_with('\n') {
+                    stdout.write_all(b"\n")?;
+                }
+                stdout.flush()?;
+            }
+            OutputTarget::File(path) => fs::write(path, contents)?,
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for OutputTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputTarget::Stdout => f.write_str("stdout"),
+            OutputTarget::File(path) => write!(f, "{:?}", path),
+        }
+    }
+}
+
+/// `value` as pretty-printed JSON for people to read, or on one line, which is markedly
+/// smaller for long series and what line-based tools expect.
+pub fn to_json<T: serde::Serialize>(value: &T, pretty: bool) -> Result<String, CollectorError> {
+    let json = if pretty { serde_json::to_string_pretty(value) } else { serde_json::to_string(value) };
+    json.map_err(|e| CollectorError::Parse { source: e, body: String::new() })
+}
+
+impl From<PathBuf> for OutputTarget {
+    fn from(path: PathBuf) -> Self {
+        OutputTarget::File(path)
+    }
+}
+
+// --- Diagnostics ---
+
+// Number of live `QuietStdout` guards; a counter so overlapping calls on different
+// threads don't switch each other's quiet mode off.
+static QUIET_DEPTH: AtomicUsize = AtomicUsize::new(0);
+
+pub struct QuietStdout(());
+
+/// Keeps stdout free of diagnostics until the guard is dropped: debug and progress lines
+/// are dropped, warnings and errors go to stderr instead.
+pub fn quiet_stdout() -> QuietStdout {
+    QUIET_DEPTH.fetch_add(1, Ordering::SeqCst);
+    QuietStdout(())
+}
+
+impl Drop for QuietStdout {
+    fn drop(&mut self) {
+        QUIET_DEPTH.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// What `log!` expands to.
+pub fn log_line(line: &str) {
+    if QUIET_DEPTH.load(Ordering::SeqCst) == 0 {
+        println!("{}", line);
+    } else if line.starts_with("WARNING") || line.starts_with("ERROR") {
+        eprintln!("{}", line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dash_means_stdout() {
+        assert_eq!(OutputTarget::parse("-"), OutputTarget::Stdout);
+        assert_eq!(OutputTarget::parse("out/-"), OutputTarget::File(PathBuf::from("out/-")));
+        assert_eq!(OutputTarget::parse("prices.json").to_string(), "\"prices.json\"");
+        assert_eq!(OutputTarget::Stdout.to_string(), "stdout");
+    }
+
+    #[test]
+    fn file_target_writes_contents_as_given() {
+        let path = std::env::temp_dir().join(format!("output_target_{}.json", std::process::id()));
+        OutputTarget::parse(path.to_str().unwrap()).write("{\"data\":[]}").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"data\":[]}");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn compact_json_is_one_line() {
+        let value = serde_json::json!({"data": [{"timestamp": 0, "value": 1.5}]});
+        assert_eq!(to_json(&value, false).unwrap(), "{\"data\":[{\"timestamp\":0,\"value\":1.5}]}");
+        assert!(to_json(&value, true).unwrap().contains('\n'));
+    }
+}
@@ -0,0 +1,972 @@
+// src/rust_data_collector/src/pipeline.rs
+
+use chrono::{DateTime, Utc};
+use std::cell::RefCell;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use crate::merge::{self, HourlyPoint};
+use crate::output::{to_json, OutputTarget};
+use crate::smard::SmardSeriesKind;
+use crate::{
+    backfill, get_openweather_data, history, get_smard_day_ahead_prices, prices, quality, resample, store, units, CollectorError, OneCallOptions,
+    OpenWeatherOneCallResponse, SmardApiResponse, SMARD_BASE_URL, SMARD_PRICE_FILTER, SMARD_RESOLUTION,
+};
+
+// --- Fetch / Transform / Sink Pipeline ---
+
+// Everything a pipeline run has gathered so far. Sources fill in their part, transforms
+// rewrite it in place, and sinks write out whichever parts they care about.
+#[derive(Debug, Default)]
+pub struct Dataset {
+    pub weather: Option<OpenWeatherOneCallResponse>,
+    pub prices: Option<SmardApiResponse>,
+    pub hourly: Vec<HourlyPoint>,
+    pub warnings: Vec<quality::Warning>,
+    pub config_hash: Option<String>, // See `provenance::RunConfig`
+}
+
+// `describe` is phrased as an action ("fetch SMARD data"), so errors read as
+// "Failed to <describe>".
+pub trait Source {
+    fn describe(&self) -> String;
+    fn fetch(&self, data: &mut Dataset) -> Result<(), CollectorError>;
+}
+
+pub trait Transform {
+    fn describe(&self) -> String;
+    fn apply(&self, data: &mut Dataset) -> Result<(), CollectorError>;
+}
+
+pub trait Sink {
+    fn describe(&self) -> String;
+    fn write(&self, data: &Dataset) -> Result<(), CollectorError>;
+}
+
+// A sink for an output format, written part by part. Each part it doesn't store is left
+// to the default, which writes nothing, and every `OutputSink` is a `Sink` that writes the
+// parts present in weather, prices, aligned-hours order and then calls `finish`, where a
+// format that needs all parts at once (a combined document) writes what it buffered.
+pub trait OutputSink {
+    fn describe(&self) -> String;
+
+    fn write_weather(&self, _weather: &OpenWeatherOneCallResponse) -> Result<(), CollectorError> {
+        Ok(())
+    }
+
+    fn write_prices(&self, _prices: &SmardApiResponse) -> Result<(), CollectorError> {
+        Ok(())
+    }
+
+    fn write_merged(&self, _hourly: &[HourlyPoint]) -> Result<(), CollectorError> {
+        Ok(())
+    }
+
+    fn finish(&self) -> Result<(), CollectorError> {
+        Ok(())
+    }
+}
+
+impl<T: OutputSink> Sink for T {
+    fn describe(&self) -> String {
+        OutputSink::describe(self)
+    }
+
+    fn write(&self, data: &Dataset) -> Result<(), CollectorError> {
+        if let Some(weather) = &data.weather {
+            self.write_weather(weather)?;
+        }
+        if let Some(prices) = &data.prices {
+            self.write_prices(prices)?;
+        }
+        self.write_merged(&data.hourly)?;
+        self.finish()
+    }
+}
+
+/// A failed stage and why; `stage` is that stage's `describe()`.
+#[derive(Debug)]
+pub struct PipelineError {
+    pub stage: String,
+    pub error: CollectorError,
+}
+
+impl PipelineError {
+    pub fn into_py_err(self) -> pyo3::PyErr {
+        self.error.into_py_err(&format!("Failed to {}", self.stage))
+    }
+}
+
+/// Runs all sources, then all transforms, then all sinks, each in the order added.
+/// Nothing is written unless every source and transform succeeded.
+#[derive(Default)]
+pub struct Pipeline {
+    sources: Vec<Box<dyn Source>>,
+    transforms: Vec<Box<dyn Transform>>,
+    sinks: Vec<Box<dyn Sink>>,
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Pipeline::default()
+    }
+
+    pub fn source(mut self, source: impl Source + 'static) -> Self {
+        self.sources.push(Box::new(source));
+        self
+    }
+
+    pub fn transform(mut self, transform: impl Transform + 'static) -> Self {
+        self.transforms.push(Box::new(transform));
+        self
+    }
+
+    pub fn sink(mut self, sink: impl Sink + 'static) -> Self {
+        self.sinks.push(Box::new(sink));
+        self
+    }
+
+    pub fn boxed_sinks(mut self, sinks: Vec<Box<dyn Sink>>) -> Self {
+        self.sinks.extend(sinks);
+        self
+    }
+
+    pub fn run(&self) -> Result<Dataset, PipelineError> {
+        let mut data = Dataset::default();
+        for source in &self.sources {
+            log!("DEBUG (Rust): Pipeline: {}", source.describe());
+            source.fetch(&mut data).map_err(|error| PipelineError { stage: source.describe(), error })?;
+        }
+        for transform in &self.transforms {
+            log!("DEBUG (Rust): Pipeline: {}", transform.describe());
+            transform.apply(&mut data).map_err(|error| PipelineError { stage: transform.describe(), error })?;
+        }
+        for sink in &self.sinks {
+            log!("DEBUG (Rust): Pipeline: {}", sink.describe());
+            sink.write(&data).map_err(|error| PipelineError { stage: sink.describe(), error })?;
+        }
+        Ok(data)
+    }
+}
+
+// --- Sources ---
+
+pub struct OpenWeatherSource {
+    pub api_key: String,
+    pub lat: f64,
+    pub lon: f64,
+    pub options: OneCallOptions,
+}
+
+impl Source for OpenWeatherSource {
+    fn describe(&self) -> String {
+        "fetch OpenWeatherMap data".to_string()
+    }
+
+    fn fetch(&self, data: &mut Dataset) -> Result<(), CollectorError> {
+        data.weather = Some(get_openweather_data(&self.api_key, self.lat, self.lon, &self.options)?);
+        Ok(())
+    }
+}
+
+// Day-ahead prices for `region` over a millisecond window.
+pub struct SmardSource {
+    pub region: String,
+    pub start_ms: i64,
+    pub end_ms: i64,
+}
+
+impl Source for SmardSource {
+    fn describe(&self) -> String {
+        "fetch SMARD data".to_string()
+    }
+
+    fn fetch(&self, data: &mut Dataset) -> Result<(), CollectorError> {
+        data.prices = Some(get_smard_day_ahead_prices(
+            SMARD_BASE_URL,
+            SMARD_PRICE_FILTER,
+            &self.region,
+            SMARD_RESOLUTION,
+            self.start_ms,
+            self.end_ms,
+        )?);
+        Ok(())
+    }
+}
+
+// Observed past weather for a window (Unix seconds), one timemachine call per hour.
+pub struct TimemachineSource {
+    pub api_key: String,
+    pub lat: f64,
+    pub lon: f64,
+    pub start: i64,
+    pub end: i64,
+}
+
+impl Source for TimemachineSource {
+    fn describe(&self) -> String {
+        "fetch OpenWeatherMap history".to_string()
+    }
+
+    fn fetch(&self, data: &mut Dataset) -> Result<(), CollectorError> {
+        data.weather = Some(history::get_hourly_history(&self.api_key, self.lat, self.lon, self.start, self.end)?);
+        Ok(())
+    }
+}
+
+// Day-ahead prices older than the index covers, assembled from SMARD's historical segments.
+pub struct SmardHistoricalSource {
+    pub region: String,
+    pub start_ms: i64,
+    pub end_ms: i64,
+}
+
+impl Source for SmardHistoricalSource {
+    fn describe(&self) -> String {
+        "fetch SMARD history".to_string()
+    }
+
+    fn fetch(&self, data: &mut Dataset) -> Result<(), CollectorError> {
+        data.prices = Some(backfill::get_smard_historical(
+            SMARD_BASE_URL,
+            SMARD_PRICE_FILTER,
+            SmardSeriesKind::Price,
+            &self.region,
+            SMARD_RESOLUTION,
+            self.start_ms,
+            self.end_ms,
+            backfill::DEFAULT_SEGMENT_CONCURRENCY,
+            &mut |done, total| log!("DEBUG (Rust): SMARD history segment {}/{}", done, total),
+        )?);
+        Ok(())
+    }
+}
+
+// --- Transforms ---
+
+pub struct RoundPrices(pub u32);
+
+impl Transform for RoundPrices {
+    fn describe(&self) -> String {
+        format!("round prices to {} decimals", self.0)
+    }
+
+    fn apply(&self, data: &mut Dataset) -> Result<(), CollectorError> {
+        if let Some(p) = data.prices.as_mut() {
+            prices::round_prices(&mut p.data, self.0);
+        }
+        Ok(())
+    }
+}
+
+// For a price source whose filter doesn't publish EUR/MWh: converts the prices so the
+// rest of the pipeline sees EUR/MWh, and the saved prices record the original unit.
+pub struct DeclarePriceUnit(pub units::PriceUnit);
+
+impl Transform for DeclarePriceUnit {
+    fn describe(&self) -> String {
+        format!("convert prices from {} to EUR/MWh", self.0.as_str())
+    }
+
+    fn apply(&self, data: &mut Dataset) -> Result<(), CollectorError> {
+        if let Some(p) = data.prices.as_mut() {
+            units::normalize_price_unit(p, self.0);
+        }
+        Ok(())
+    }
+}
+
+// Puts the prices on a coarser or finer grid, e.g. quarter hours onto hours. Goes before
+// `Align`, which then pairs weather with the resampled prices.
+pub struct ResamplePrices {
+    pub step_ms: i64,
+    pub method: resample::ResampleMethod,
+}
+
+impl Transform for ResamplePrices {
+    fn describe(&self) -> String {
+        format!("resample prices to {} ms ({:?})", self.step_ms, self.method)
+    }
+
+    fn apply(&self, data: &mut Dataset) -> Result<(), CollectorError> {
+        if let Some(p) = data.prices.as_mut() {
+            p.data = resample::resample(&p.data, self.step_ms, self.method)?;
+        }
+        Ok(())
+    }
+}
+
+// Trailing moving average over the last `window` priced points, each included, so a
+// smoothed hour only ever depends on earlier ones. Unpriced hours stay unpriced and don't
+// count towards the window.
+pub struct SmoothPrices(pub usize);
+
+impl Transform for SmoothPrices {
+    fn describe(&self) -> String {
+        format!("smooth prices over {} points", self.0)
+    }
+
+    fn apply(&self, data: &mut Dataset) -> Result<(), CollectorError> {
+        if self.0 == 0 {
+            return Err(CollectorError::InvalidInput("smoothing window must be at least 1 point".to_string()));
+        }
+        if let Some(p) = data.prices.as_mut() {
+            let mut recent = std::collections::VecDeque::with_capacity(self.0);
+            for dp in p.data.iter_mut() {
+                let Some(value) = dp.value else {
+                    continue;
+                };
+                if recent.len() == self.0 {
+                    recent.pop_front();
+                }
+                recent.push_back(value);
+                dp.value = Some(recent.iter().sum::<f64>() / recent.len() as f64);
+            }
+        }
+        Ok(())
+    }
+}
+
+// Folds the fetched prices into a previously saved `smard_prices.json`, deduplicating
+// by timestamp, so the saved file keeps growing instead of being replaced. Growth stops at
+// `store::DEFAULT_MAX_POINTS`, a year of hours: beyond that the oldest points are dropped.
+pub struct MergeStoredPrices(pub PathBuf);
+
+impl Transform for MergeStoredPrices {
+    fn describe(&self) -> String {
+        format!("merge stored SMARD data from {:?}", self.0)
+    }
+
+    fn apply(&self, data: &mut Dataset) -> Result<(), CollectorError> {
+        let Some(fetched) = data.prices.as_ref() else {
+            return Ok(());
+        };
+        let mut series = store::SeriesStore::new(store::DEFAULT_MAX_POINTS, None);
+        if self.0.exists() {
+            series.merge_file(&self.0.to_string_lossy())?;
+        }
+        let added = series.merge(&fetched.data);
+        log!("DEBUG (Rust): Incremental SMARD fetch added {} new points.", added);
+        data.prices = Some(series.to_response());
+        Ok(())
+    }
+}
+
+// Drops forecast hours outside a window (Unix seconds); the forecast always starts now.
+pub struct TrimWeather {
+    pub start: i64,
+    pub end: i64,
+}
+
+impl Transform for TrimWeather {
+    fn describe(&self) -> String {
+        "trim weather to the window".to_string()
+    }
+
+    fn apply(&self, data: &mut Dataset) -> Result<(), CollectorError> {
+        if let Some(weather) = data.weather.as_mut() {
+            // Keep the hour the window starts in.
+            weather.hourly.retain(|hour| hour.dt > self.start - 3600 && hour.dt <= self.end);
+        }
+        Ok(())
+    }
+}
+
+// Records the run's `provenance::RunConfig` hash on the dataset and in both saved files.
+// Goes after the other transforms, so a source that rebuilds a struct can't drop it.
+pub struct StampConfigHash(pub String);
+
+impl Transform for StampConfigHash {
+    fn describe(&self) -> String {
+        "stamp the config hash".to_string()
+    }
+
+    fn apply(&self, data: &mut Dataset) -> Result<(), CollectorError> {
+        if let Some(weather) = data.weather.as_mut() {
+            weather.config_hash = Some(self.0.clone());
+        }
+        if let Some(prices) = data.prices.as_mut() {
+            prices.config_hash = Some(self.0.clone());
+        }
+        data.config_hash = Some(self.0.clone());
+        Ok(())
+    }
+}
+
+// Aligns weather hours with prices into `Dataset::hourly`, warning about timestamps that
+// aren't whole UTC hours.
+pub struct Align;
+
+impl Transform for Align {
+    fn describe(&self) -> String {
+        "align weather and prices".to_string()
+    }
+
+    fn apply(&self, data: &mut Dataset) -> Result<(), CollectorError> {
+        if let (Some(weather), Some(p)) = (&data.weather, &data.prices) {
+            data.warnings.extend(merge::timestamp_warnings(weather, p));
+            data.hourly = merge::merge_hourly(weather, p);
+        }
+        Ok(())
+    }
+}
+
+// Adds a warning (not an error) when the newest price is too old.
+pub struct CheckStaleness {
+    pub now: DateTime<Utc>,
+    pub max_staleness_hours: i64,
+}
+
+impl Transform for CheckStaleness {
+    fn describe(&self) -> String {
+        "check SMARD data staleness".to_string()
+    }
+
+    fn apply(&self, data: &mut Dataset) -> Result<(), CollectorError> {
+        if let Some(p) = &data.prices {
+            if let Some(warning) = quality::check_staleness(&p.data, self.now, self.max_staleness_hours) {
+                data.warnings.push(warning);
+            }
+        }
+        Ok(())
+    }
+}
+
+// Adds warnings (not errors) when the weather looks degenerate or time-shifted, see
+// `quality`. The aligned-hour checks need `Align` to run first.
+pub struct CheckPlausibility {
+    pub lat: f64,
+    pub lon: f64,
+}
+
+impl Transform for CheckPlausibility {
+    fn describe(&self) -> String {
+        "check weather plausibility".to_string()
+    }
+
+    fn apply(&self, data: &mut Dataset) -> Result<(), CollectorError> {
+        if let Some(weather) = &data.weather {
+            data.warnings.extend(quality::check_weather_plausibility(&weather.hourly));
+        }
+        data.warnings.extend(quality::check_irradiance_plausibility(&data.hourly, self.lat, self.lon));
+        data.warnings.extend(quality::check_diurnal_offset(&data.hourly, self.lat, self.lon));
+        Ok(())
+    }
+}
+
+// --- Sinks ---
+
+// Raw One Call response, as `weather_data.json`.
+pub struct WeatherJsonSink {
+    pub target: OutputTarget,
+    pub pretty: bool,
+}
+
+impl OutputSink for WeatherJsonSink {
+    fn describe(&self) -> String {
+        "write weather data".to_string()
+    }
+
+    fn write_weather(&self, weather: &OpenWeatherOneCallResponse) -> Result<(), CollectorError> {
+        self.target.write(&to_json(weather, self.pretty)?)?;
+        log!("OpenWeatherMap data saved to {}", self.target);
+        Ok(())
+    }
+}
+
+// SMARD-shaped price file, as `smard_prices.json`.
+pub struct PricesJsonSink {
+    pub target: OutputTarget,
+    pub pretty: bool,
+}
+
+impl OutputSink for PricesJsonSink {
+    fn describe(&self) -> String {
+        "write SMARD data".to_string()
+    }
+
+    fn write_prices(&self, prices: &SmardApiResponse) -> Result<(), CollectorError> {
+        self.target.write(&to_json(prices, self.pretty)?)?;
+        log!("SMARD data saved to {}", self.target);
+        Ok(())
+    }
+}
+
+/// Weather, prices and aligned hours as one `{"weather", "smard", "merged", "config_hash"}`
+/// document.
+pub fn combined_document(data: &Dataset) -> serde_json::Value {
+    serde_json::json!({
+        "weather": data.weather,
+        "smard": data.prices,
+        "merged": data.hourly,
+        "config_hash": data.config_hash,
+    })
+}
+
+// `combined_document`, by default on a single line so it pipes straight into `jq`. The
+// parts are collected as they come and written together in `finish`.
+pub struct CombinedJsonSink {
+    pub target: OutputTarget,
+    pub pretty: bool,
+    parts: RefCell<CombinedParts>,
+}
+
+#[derive(Default)]
+struct CombinedParts {
+    weather: Option<serde_json::Value>,
+    smard: Option<serde_json::Value>,
+    merged: serde_json::Value,
+    config_hash: Option<String>,
+}
+
+impl CombinedJsonSink {
+    pub fn new(target: OutputTarget, pretty: bool) -> Self {
+        CombinedJsonSink { target, pretty, parts: RefCell::default() }
+    }
+}
+
+fn to_value<T: serde::Serialize>(value: &T) -> Result<serde_json::Value, CollectorError> {
+    serde_json::to_value(value).map_err(|e| CollectorError::Parse { source: e, body: String::new() })
+}
+
+impl OutputSink for CombinedJsonSink {
+    fn describe(&self) -> String {
+        "write combined JSON".to_string()
+    }
+
+    fn write_weather(&self, weather: &OpenWeatherOneCallResponse) -> Result<(), CollectorError> {
+        let mut parts = self.parts.borrow_mut();
+        parts.weather = Some(to_value(weather)?);
+        parts.config_hash = parts.config_hash.take().or_else(|| weather.config_hash.clone());
+        Ok(())
+    }
+
+    fn write_prices(&self, prices: &SmardApiResponse) -> Result<(), CollectorError> {
+        let mut parts = self.parts.borrow_mut();
+        parts.smard = Some(to_value(prices)?);
+        parts.config_hash = parts.config_hash.take().or_else(|| prices.config_hash.clone());
+        Ok(())
+    }
+
+    fn write_merged(&self, hourly: &[HourlyPoint]) -> Result<(), CollectorError> {
+        self.parts.borrow_mut().merged = to_value(&hourly)?;
+        Ok(())
+    }
+
+    // Same document as `combined_document`; the config hash is the one stamped on the parts.
+    fn finish(&self) -> Result<(), CollectorError> {
+        let parts = self.parts.take();
+        let document = serde_json::json!({
+            "weather": parts.weather,
+            "smard": parts.smard,
+            "merged": parts.merged,
+            "config_hash": parts.config_hash,
+        });
+        self.target.write(&to_json(&document, self.pretty)?)?;
+        log!("Combined data saved to {}", self.target);
+        Ok(())
+    }
+}
+
+// Aligned hours as CSV, one row per hour; empty cells where a value is missing.
+pub struct HourlyCsvSink(pub OutputTarget);
+
+fn csv_cell(value: Option<f64>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+impl OutputSink for HourlyCsvSink {
+    fn describe(&self) -> String {
+        "write hourly CSV".to_string()
+    }
+
+    fn write_merged(&self, hourly: &[HourlyPoint]) -> Result<(), CollectorError> {
+        let mut csv = String::from("timestamp,temp,clouds,pop,rain_1h,snow_1h,pressure,dew_point,visibility,ghi,price,description\n");
+        for p in hourly {
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{},{},{},{},{},{},{},\"{}\"",
+                p.timestamp, p.temp, p.clouds, p.pop,
+                csv_cell(p.rain_1h), csv_cell(p.snow_1h),
+                csv_cell(p.pressure.map(f64::from)), csv_cell(p.dew_point), csv_cell(p.visibility.map(f64::from)),
+                csv_cell(p.ghi), csv_cell(p.price),
+                p.description.replace('"', "\"\"")
+            );
+        }
+        self.0.write(&csv)?;
+        Ok(())
+    }
+}
+
+// Everything as an Excel workbook; needs `Align` for the merged sheet.
+#[cfg(feature = "xlsx")]
+pub struct XlsxSink(pub PathBuf);
+
+#[cfg(feature = "xlsx")]
+impl Sink for XlsxSink {
+    fn describe(&self) -> String {
+        "write Excel workbook".to_string()
+    }
+
+    fn write(&self, data: &Dataset) -> Result<(), CollectorError> {
+        let (Some(weather), Some(p)) = (&data.weather, &data.prices) else {
+            return Ok(());
+        };
+        crate::xlsx::export_xlsx(&self.0, weather, p, &data.hourly)
+    }
+}
+
+// Pushes the newest aligned hour and the current price to WebSocket clients.
+#[cfg(feature = "live")]
+pub struct LiveSink {
+    pub now: DateTime<Utc>,
+}
+
+#[cfg(feature = "live")]
+impl Sink for LiveSink {
+    fn describe(&self) -> String {
+        "publish live update".to_string()
+    }
+
+    fn write(&self, data: &Dataset) -> Result<(), CollectorError> {
+        crate::live::publish(&crate::live::LiveUpdate {
+            fetched_at: self.now.timestamp(),
+            current_price: data.prices.as_ref().and_then(|p| prices::current_price(&p.data, self.now)),
+            latest: crate::live::latest_point(&data.hourly, self.now.timestamp()),
+        });
+        Ok(())
+    }
+}
+
+// Weather, prices and aligned hours upserted into a SQLite database.
+#[cfg(feature = "sqlite")]
+pub struct SqliteSink(pub PathBuf);
+
+// Each part is upserted in its own transaction.
+#[cfg(feature = "sqlite")]
+impl OutputSink for SqliteSink {
+    fn describe(&self) -> String {
+        "write SQLite database".to_string()
+    }
+
+    fn write_weather(&self, weather: &OpenWeatherOneCallResponse) -> Result<(), CollectorError> {
+        crate::sqlite::export_sqlite(&self.0, Some(weather), None, &[])
+    }
+
+    fn write_prices(&self, prices: &SmardApiResponse) -> Result<(), CollectorError> {
+        crate::sqlite::export_sqlite(&self.0, None, Some(prices), &[])
+    }
+
+    fn write_merged(&self, hourly: &[HourlyPoint]) -> Result<(), CollectorError> {
+        crate::sqlite::export_sqlite(&self.0, None, None, hourly)?;
+        log!("Data saved to SQLite database {:?}", self.0);
+        Ok(())
+    }
+}
+
+// Prices and aligned hours pushed to an InfluxDB bucket.
+#[cfg(feature = "influx")]
+pub struct InfluxSink {
+    pub config: crate::influx::InfluxConfig,
+    pub region: String,
+}
+
+// Prices and aligned hours go out as separate writes; the raw weather isn't stored.
+#[cfg(feature = "influx")]
+impl OutputSink for InfluxSink {
+    fn describe(&self) -> String {
+        "write to InfluxDB".to_string()
+    }
+
+    fn write_prices(&self, prices: &SmardApiResponse) -> Result<(), CollectorError> {
+        crate::influx::write_lines(&self.config, crate::influx::line_protocol(Some(prices), &self.region, &[]))
+    }
+
+    fn write_merged(&self, hourly: &[HourlyPoint]) -> Result<(), CollectorError> {
+        crate::influx::write_lines(&self.config, crate::influx::line_protocol(None, &self.region, hourly))?;
+        log!("Data written to InfluxDB bucket {}", self.config.bucket);
+        Ok(())
+    }
+}
+
+// Weather, prices and aligned hours published to an MQTT broker, under the configured topic
+// prefix as "weather", "prices" and "hourly".
+#[cfg(feature = "mqtt")]
+pub struct MqttSink {
+    pub config: crate::mqtt::MqttConfig,
+    pub pretty: bool,
+    messages: RefCell<Vec<(&'static str, String)>>,
+}
+
+#[cfg(feature = "mqtt")]
+impl MqttSink {
+    pub fn new(config: crate::mqtt::MqttConfig, pretty: bool) -> Self {
+        MqttSink { config, pretty, messages: RefCell::new(Vec::new()) }
+    }
+
+    fn push<T: serde::Serialize>(&self, subtopic: &'static str, value: &T) -> Result<(), CollectorError> {
+        self.messages.borrow_mut().push((subtopic, to_json(value, self.pretty)?));
+        Ok(())
+    }
+}
+
+// The parts are collected and published over one connection once all are in.
+#[cfg(feature = "mqtt")]
+impl OutputSink for MqttSink {
+    fn describe(&self) -> String {
+        "publish to MQTT".to_string()
+    }
+
+    fn write_weather(&self, weather: &OpenWeatherOneCallResponse) -> Result<(), CollectorError> {
+        self.push("weather", weather)
+    }
+
+    fn write_prices(&self, prices: &SmardApiResponse) -> Result<(), CollectorError> {
+        self.push("prices", prices)
+    }
+
+    fn write_merged(&self, hourly: &[HourlyPoint]) -> Result<(), CollectorError> {
+        self.push("hourly", &hourly)
+    }
+
+    fn finish(&self) -> Result<(), CollectorError> {
+        crate::mqtt::publish(&self.config, &self.messages.take())?;
+        log!("Data published to MQTT topic {}/#", self.config.topic);
+        Ok(())
+    }
+}
+
+// --- Sink Selection ---
+
+// What a named sink needs to know about the run it is writing for.
+pub struct SinkSettings<'a> {
+    pub out_dir: &'a Path,
+    pub pretty: bool,
+    pub region: &'a str,
+    pub secrets_file: Option<&'a str>,
+}
+
+/// Sink names `fetch_and_save_data` accepts, in this build.
+pub fn sink_names() -> Vec<&'static str> {
+    let mut names = vec!["json", "csv", "stdout"];
+    if cfg!(feature = "sqlite") {
+        names.push("sqlite");
+    }
+    if cfg!(feature = "influx") {
+        names.push("influx");
+    }
+    if cfg!(feature = "mqtt") {
+        names.push("mqtt");
+    }
+    if cfg!(feature = "xlsx") {
+        names.push("xlsx");
+    }
+    names
+}
+
+/// The sinks for `name`: "json" is the two files in `out_dir` (`weather_data.json`,
+/// `smard_prices.json`), "csv" `hourly.csv`, "sqlite" `energy_data.sqlite`, "xlsx"
+/// `energy_data.xlsx`, "stdout" the combined document, "influx" the bucket and "mqtt" the
+/// broker configured in the secrets. All but "json" write the aligned hours, so the pipeline needs `Align`.
+pub fn named_sinks(name: &str, settings: &SinkSettings) -> Result<Vec<Box<dyn Sink>>, CollectorError> {
+    let file = |file_name: &str| settings.out_dir.join(file_name);
+    let sinks: Vec<Box<dyn Sink>> = match name {
+        "json" => vec![
+            Box::new(WeatherJsonSink { target: file("weather_data.json").into(), pretty: settings.pretty }),
+            Box::new(PricesJsonSink { target: file("smard_prices.json").into(), pretty: settings.pretty }),
+        ],
+        "csv" => vec![Box::new(HourlyCsvSink(file("hourly.csv").into()))],
+        "stdout" => vec![Box::new(CombinedJsonSink::new(OutputTarget::Stdout, settings.pretty))],
+        #[cfg(feature = "sqlite")]
+        "sqlite" => vec![Box::new(SqliteSink(file("energy_data.sqlite")))],
+        #[cfg(feature = "influx")]
+        "influx" => vec![Box::new(InfluxSink {
+            config: crate::influx::InfluxConfig::load(settings.secrets_file)?,
+            region: settings.region.to_string(),
+        })],
+        #[cfg(feature = "mqtt")]
+        "mqtt" => vec![Box::new(MqttSink::new(crate::mqtt::MqttConfig::load(settings.secrets_file)?, settings.pretty))],
+        #[cfg(feature = "xlsx")]
+        "xlsx" => vec![Box::new(XlsxSink(file("energy_data.xlsx")))],
+        _ => {
+            return Err(CollectorError::InvalidInput(format!(
+                "Unknown sink {:?}, expected one of {:?}",
+                name,
+                sink_names()
+            )))
+        }
+    };
+    Ok(sinks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SmardDataPoint;
+
+    const QUARTER_MS: i64 = 900_000;
+
+    // Prices handed in by the test instead of fetched.
+    struct FixedPrices(Vec<Option<f64>>, i64);
+
+    impl Source for FixedPrices {
+        fn describe(&self) -> String {
+            "load fixed prices".to_string()
+        }
+
+        fn fetch(&self, data: &mut Dataset) -> Result<(), CollectorError> {
+            let points = self.0.iter().enumerate()
+                .map(|(i, &value)| SmardDataPoint { timestamp: i as i64 * self.1, value })
+                .collect();
+            data.prices = Some(SmardApiResponse { data: points, source_unit: None, config_hash: None });
+            Ok(())
+        }
+    }
+
+    fn prices(data: &Dataset) -> Vec<(i64, Option<f64>)> {
+        data.prices.as_ref().unwrap().data.iter().map(|dp| (dp.timestamp, dp.value)).collect()
+    }
+
+    #[test]
+    fn quarter_hours_aggregate_to_hourly_means() {
+        let data = Pipeline::new()
+            .source(FixedPrices(vec![Some(80.0), Some(90.0), Some(100.0), Some(110.0), Some(60.0), None], QUARTER_MS))
+            .transform(ResamplePrices { step_ms: 4 * QUARTER_MS, method: resample::ResampleMethod::Mean })
+            .run()
+            .unwrap();
+        assert_eq!(prices(&data), vec![(0, Some(95.0)), (4 * QUARTER_MS, Some(60.0))]);
+    }
+
+    #[test]
+    fn smoothing_averages_the_trailing_priced_points() {
+        let data = Pipeline::new()
+            .source(FixedPrices(vec![Some(10.0), Some(20.0), None, Some(60.0), Some(10.0)], 3_600_000))
+            .transform(SmoothPrices(2))
+            .run()
+            .unwrap();
+        let values: Vec<_> = prices(&data).into_iter().map(|(_, v)| v).collect();
+        assert_eq!(values, vec![Some(10.0), Some(15.0), None, Some(40.0), Some(35.0)]);
+    }
+
+    #[test]
+    fn failing_transform_names_its_stage() {
+        let error = Pipeline::new()
+            .source(FixedPrices(vec![Some(10.0)], 3_600_000))
+            .transform(SmoothPrices(0))
+            .run()
+            .unwrap_err();
+        assert_eq!(error.stage, "smooth prices over 0 points");
+        assert!(matches!(error.error, CollectorError::InvalidInput(_)));
+    }
+
+    // Records which parts it was handed.
+    struct PartsSeen(std::cell::RefCell<Vec<&'static str>>);
+
+    impl OutputSink for PartsSeen {
+        fn describe(&self) -> String {
+            "record parts".to_string()
+        }
+
+        fn write_prices(&self, _prices: &SmardApiResponse) -> Result<(), CollectorError> {
+            self.0.borrow_mut().push("prices");
+            Ok(())
+        }
+
+        fn write_merged(&self, _hourly: &[HourlyPoint]) -> Result<(), CollectorError> {
+            self.0.borrow_mut().push("merged");
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn output_sink_gets_the_parts_present() {
+        let sink = PartsSeen(Default::default());
+        let mut data = Dataset::default();
+        Sink::write(&sink, &data).unwrap();
+        FixedPrices(vec![Some(80.0)], 3_600_000).fetch(&mut data).unwrap();
+        Sink::write(&sink, &data).unwrap();
+        assert_eq!(*sink.0.borrow(), vec!["merged", "prices", "merged"]);
+    }
+
+    #[test]
+    fn incremental_merge_dedupes_the_overlap() {
+        let stored = std::env::temp_dir().join(format!("merge_stored_{}.json", std::process::id()));
+        let mut data = Dataset::default();
+        FixedPrices(vec![Some(10.0), Some(20.0), Some(30.0)], 3_600_000).fetch(&mut data).unwrap();
+        std::fs::write(&stored, to_json(data.prices.as_ref().unwrap(), false).unwrap()).unwrap();
+
+        // The next fetch re-reads the last two hours (one revised) and adds one.
+        let fetched = [(3_600_000, Some(20.0)), (7_200_000, Some(31.0)), (10_800_000, Some(40.0))];
+        data.prices.as_mut().unwrap().data = fetched.iter().map(|&(timestamp, value)| SmardDataPoint { timestamp, value }).collect();
+        MergeStoredPrices(stored.clone()).apply(&mut data).unwrap();
+        std::fs::remove_file(&stored).unwrap();
+
+        assert_eq!(prices(&data), vec![(0, Some(10.0)), (3_600_000, Some(20.0)), (7_200_000, Some(31.0)), (10_800_000, Some(40.0))]);
+    }
+
+
+    // A fresh directory under the system temp dir, unique to this test.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("pipeline_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn sinks_mix_by_name() {
+        let dir = temp_dir("mixed_sinks");
+        let settings = SinkSettings { out_dir: &dir, pretty: false, region: "DE", secrets_file: None };
+        let mut run = Pipeline::new().source(FixedPrices(vec![Some(80.0), None], 3_600_000));
+        for name in ["json", "csv"] {
+            run = run.boxed_sinks(named_sinks(name, &settings).unwrap());
+        }
+        run.run().unwrap();
+
+        let saved: SmardApiResponse = serde_json::from_str(&std::fs::read_to_string(dir.join("smard_prices.json")).unwrap()).unwrap();
+        assert_eq!(saved.data.len(), 2);
+        assert!(std::fs::read_to_string(dir.join("hourly.csv")).unwrap().starts_with("timestamp,temp,"));
+        // No weather source, so no weather file.
+        assert!(!dir.join("weather_data.json").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(matches!(named_sinks("parquet", &settings), Err(CollectorError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn combined_sink_writes_the_combined_document() {
+        let dir = temp_dir("combined_sink");
+        let path = dir.join("combined.json");
+        let data = Pipeline::new()
+            .source(FixedPrices(vec![Some(80.0), Some(90.0)], 3_600_000))
+            .transform(StampConfigHash("0123456789abcdef".to_string()))
+            .sink(CombinedJsonSink::new(path.clone().into(), false))
+            .run()
+            .unwrap();
+        let written: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(written, combined_document(&data));
+        assert_eq!(written["config_hash"], "0123456789abcdef");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn sqlite_sink_upserts_each_part() {
+        let dir = temp_dir("sqlite_sink");
+        let settings = SinkSettings { out_dir: &dir, pretty: false, region: "DE", secrets_file: None };
+        for value in [80.0, 85.0] {
+            Pipeline::new()
+                .source(FixedPrices(vec![Some(value), Some(90.0)], 3_600_000))
+                .boxed_sinks(named_sinks("sqlite", &settings).unwrap())
+                .run()
+                .unwrap();
+        }
+        let conn = rusqlite::Connection::open(dir.join("energy_data.sqlite")).unwrap();
+        let rows: (i64, f64) = conn.query_row("SELECT COUNT(*), SUM(price) FROM prices", [], |row| Ok((row.get(0)?, row.get(1)?))).unwrap();
+        assert_eq!(rows, (2, 175.0));
+        drop(conn);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+}
@@ -0,0 +1,123 @@
+// src/rust_data_collector/src/preflight.rs
+
+use pyo3::prelude::*;
+use std::fs;
+use std::path::Path;
+
+use crate::health;
+
+// --- Pre-Flight Configuration Check ---
+
+// Regions and resolutions SMARD publishes the chart data for.
+pub const SMARD_REGIONS: [&str; 11] = [
+    "DE", "AT", "LU", "DE-LU", "DE-AT-LU", "50Hertz", "Amprion", "TenneT", "TransnetBW", "APG", "Creos",
+];
+pub const SMARD_RESOLUTIONS: [&str; 6] = ["quarterhour", "hour", "day", "week", "month", "year"];
+
+#[pyclass(get_all)]
+#[derive(Debug, Clone)]
+pub struct ValidationReport {
+    pub ok: bool,
+    pub problems: Vec<String>,                // One human-readable line per failed check
+    pub health: Option<health::HealthReport>, // None when connectivity wasn't checked
+}
+
+#[pymethods]
+impl ValidationReport {
+    fn __repr__(&self) -> String {
+        format!("ValidationReport(ok={}, problems={:?})", self.ok, self.problems)
+    }
+}
+
+// OpenWeatherMap keys are 32 hex characters.
+fn check_api_key(api_key: Option<&str>) -> Option<String> {
+    match api_key {
+        None => Some("OPENWEATHER_API_KEY is not set".to_string()),
+        Some(key) if key.len() != 32 || !key.chars().all(|c| c.is_ascii_hexdigit()) => Some(format!(
+            "OPENWEATHER_API_KEY doesn't look like an OpenWeatherMap key (expected 32 hex characters, got {} characters)",
+            key.len()
+        )),
+        Some(_) => None,
+    }
+}
+
+// The run creates `data_dir` if needed, so a missing directory is fine as long as its
+// nearest existing ancestor is writable. Probed with a throwaway file, not by permissions,
+// which don't tell the whole story on network drives and read-only mounts.
+fn check_data_dir(data_dir: &str) -> Option<String> {
+    let mut dir = Path::new(data_dir);
+    while !dir.exists() {
+        match dir.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => dir = parent,
+            _ => {
+                dir = Path::new(".");
+                break;
+            }
+        }
+    }
+    if !dir.is_dir() {
+        return Some(format!("data_dir {:?} is not a directory ({:?} is a file)", data_dir, dir));
+    }
+    let probe = dir.join(format!(".rust_data_collector_probe_{}", std::process::id()));
+    match fs::write(&probe, b"") {
+        Ok(()) => {
+            let _ = fs::remove_file(&probe);
+            None
+        }
+        Err(e) => Some(format!("data_dir {:?} is not writable: {}", data_dir, e)),
+    }
+}
+
+fn check_coordinates(lat: f64, lon: f64, problems: &mut Vec<String>) {
+    if !(-90.0..=90.0).contains(&lat) {
+        problems.push(format!("lat {} is out of range (-90 to 90)", lat));
+    }
+    if !(-180.0..=180.0).contains(&lon) {
+        problems.push(format!("lon {} is out of range (-180 to 180)", lon));
+    }
+}
+
+/// Runs every check and collects what failed instead of stopping at the first problem.
+/// `units` must be "metric", the only units the collector stores. With `check_connectivity`
+/// the health check is run too, which costs one OpenWeatherMap call.
+#[allow(clippy::too_many_arguments)]
+pub fn validate_config(
+    api_key: Option<&str>,
+    data_dir: &str,
+    lat: f64,
+    lon: f64,
+    region: &str,
+    units: &str,
+    resolution: &str,
+    check_connectivity: bool,
+) -> ValidationReport {
+    let mut problems = Vec::new();
+    problems.extend(check_api_key(api_key));
+    problems.extend(check_data_dir(data_dir));
+    check_coordinates(lat, lon, &mut problems);
+    if !SMARD_REGIONS.contains(&region) {
+        problems.push(format!("Unknown SMARD region {:?} (expected one of {})", region, SMARD_REGIONS.join(", ")));
+    }
+    if units != "metric" {
+        problems.push(format!("Unsupported units {:?}; the collector stores metric values only", units));
+    }
+    if !SMARD_RESOLUTIONS.contains(&resolution) {
+        problems.push(format!(
+            "Unknown SMARD resolution {:?} (expected one of {})",
+            resolution, SMARD_RESOLUTIONS.join(", ")
+        ));
+    }
+
+    let health = check_connectivity.then(|| health::run_health_check(api_key, lat, lon));
+    if let Some(report) = &health {
+        // A missing key is already reported above.
+        if let (Some(error), Some(_)) = (&report.openweather_error, api_key) {
+            problems.push(format!("OpenWeatherMap check failed: {}", error));
+        }
+        if let Some(error) = &report.smard_error {
+            problems.push(format!("SMARD check failed: {}", error));
+        }
+    }
+
+    ValidationReport { ok: problems.is_empty(), problems, health }
+}
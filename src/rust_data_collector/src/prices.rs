@@ -0,0 +1,611 @@
+// src/rust_data_collector/src/prices.rs
+
+use chrono::{DateTime, Duration, NaiveDate, Offset, TimeZone, Timelike, Utc};
+use chrono_tz::Europe::Berlin;
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::timestamp::normalize_timestamp;
+use crate::{CollectorError, SmardDataPoint};
+
+// --- Price Series Helpers ---
+
+/// Price of the hour containing `now`: the latest point at or before `now` that has a value.
+pub fn current_price(points: &[SmardDataPoint], now: DateTime<Utc>) -> Option<f64> {
+    let now_ms = now.timestamp_millis();
+    points.iter()
+        .filter(|dp| dp.timestamp <= now_ms)
+        .filter_map(|dp| dp.value.map(|v| (dp.timestamp, v)))
+        .max_by_key(|(ts, _)| *ts)
+        .map(|(_, v)| v)
+}
+
+// Upcoming hourly tariff transition: when the price next changes and by how much.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PriceChange {
+    pub timestamp: i64, // Milliseconds since epoch, start of the first hour at the new price
+    pub current_price: f64,
+    pub next_price: f64,
+    pub delta: f64,
+}
+
+/// The first hour after `now` whose price differs from the current hour's. Hours at the
+/// same price are skipped, so this is the next real change. `None` if the current price is
+/// unknown or the series ends before the price changes.
+pub fn next_price_change(points: &[SmardDataPoint], now: DateTime<Utc>) -> Option<PriceChange> {
+    let now_ms = now.timestamp_millis();
+    let current = current_price(points, now)?;
+
+    let mut upcoming: Vec<(i64, f64)> = points.iter()
+        .filter(|dp| dp.timestamp > now_ms)
+        .filter_map(|dp| dp.value.map(|v| (dp.timestamp, v)))
+        .collect();
+    upcoming.sort_by_key(|(ts, _)| *ts);
+
+    upcoming.into_iter()
+        .find(|(_, price)| *price != current)
+        .map(|(timestamp, next_price)| PriceChange {
+            timestamp,
+            current_price: current,
+            next_price,
+            delta: next_price - current,
+        })
+}
+
+/// Where the price of the hour containing `at` sits among all priced points, from 0.0
+/// (cheapest) to 1.0 (dearest). Ties count half, so a flat series ranks 0.5 everywhere.
+/// `None` if no price is known at or before `at` (the same lookup as `current_price`).
+pub fn price_percentile_rank(points: &[SmardDataPoint], at: DateTime<Utc>) -> Option<f64> {
+    let price = current_price(points, at)?;
+    let values: Vec<f64> = points.iter().filter_map(|dp| dp.value).collect();
+    if values.len() < 2 {
+        return Some(0.5);
+    }
+    let below = values.iter().filter(|v| **v < price).count() as f64;
+    let equal = values.iter().filter(|v| **v == price).count() as f64;
+    // The hour itself is one of the ties; rank it against the other points only.
+    Some((below + (equal - 1.0) / 2.0) / (values.len() - 1) as f64)
+}
+
+// Saved prices default to this many decimals; full f64 noise like 0.10000000000000001
+// only makes dashboards and diffs harder to read.
+pub const DEFAULT_PRICE_DECIMALS: u32 = 2;
+
+pub fn round_to(value: f64, decimals: u32) -> f64 {
+    let factor = 10f64.powi(decimals as i32);
+    (value * factor).round() / factor
+}
+
+/// Rounds every price value in place. Timestamps are left untouched.
+pub fn round_prices(points: &mut [SmardDataPoint], decimals: u32) {
+    for dp in points.iter_mut() {
+        dp.value = dp.value.map(|v| round_to(v, decimals));
+    }
+}
+
+// --- Appliance Scheduling ---
+
+const HOUR_MS: i64 = 3_600_000;
+
+// Why `optimal_start` found nothing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NoStartWindow {
+    ZeroDuration,
+    InvalidPower { power_kw: f64 },
+    DurationExceedsWindow { duration_hours: u32, available_hours: i64 },
+    NoContiguousPrices { duration_hours: u32 },
+}
+
+impl std::fmt::Display for NoStartWindow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NoStartWindow::ZeroDuration => write!(f, "duration must be at least one hour"),
+            NoStartWindow::InvalidPower { power_kw } => write!(f, "power must be a positive number of kW, got {}", power_kw),
+            NoStartWindow::DurationExceedsWindow { duration_hours, available_hours } => write!(
+                f,
+                "a {}h run does not fit between not_before and not_after ({}h available)",
+                duration_hours, available_hours
+            ),
+            NoStartWindow::NoContiguousPrices { duration_hours } => {
+                write!(f, "no {} consecutive priced hours in the allowed window", duration_hours)
+            }
+        }
+    }
+}
+
+/// Cheapest start for a run of `duration_hours` whole hours that starts at or after
+/// `not_before_ms` and finishes by `not_after_ms`. Returns the start (ms) and the cost in
+/// EUR of drawing `power_kw` (positive and finite) for the whole run. Only hourly points
+/// that are contiguous and priced are considered; ties go to the earliest start.
+pub fn optimal_start(
+    points: &[SmardDataPoint],
+    duration_hours: u32,
+    not_before_ms: i64,
+    not_after_ms: i64,
+    power_kw: f64,
+) -> Result<(i64, f64), NoStartWindow> {
+    if duration_hours == 0 {
+        return Err(NoStartWindow::ZeroDuration);
+    }
+    if !(power_kw.is_finite() && power_kw > 0.0) {
+        return Err(NoStartWindow::InvalidPower { power_kw });
+    }
+    let duration_ms = duration_hours as i64 * HOUR_MS;
+    let available_hours = (not_after_ms - not_before_ms).max(0) / HOUR_MS;
+    if not_after_ms - not_before_ms < duration_ms {
+        return Err(NoStartWindow::DurationExceedsWindow { duration_hours, available_hours });
+    }
+
+    let priced: BTreeMap<i64, f64> = points.iter()
+        .filter(|dp| dp.timestamp >= not_before_ms && dp.timestamp + HOUR_MS <= not_after_ms)
+        .filter_map(|dp| dp.value.map(|v| (dp.timestamp, v)))
+        .collect();
+
+    let mut best: Option<(i64, f64)> = None;
+    for &start in priced.keys() {
+        // Sum of EUR/MWh over the run, if every hour of it is priced.
+        let run: Option<f64> = (0..duration_hours as i64)
+            .map(|h| priced.get(&(start + h * HOUR_MS)).copied())
+            .sum();
+        if let Some(price_sum) = run {
+            let cost = price_sum / 1000.0 * power_kw; // EUR/MWh -> EUR/kWh, times kW for 1h each
+            if best.is_none_or(|(_, best_cost)| cost < best_cost) {
+                best = Some((start, cost));
+            }
+        }
+    }
+    best.ok_or(NoStartWindow::NoContiguousPrices { duration_hours })
+}
+
+// --- Battery Charge / Discharge Hours ---
+
+// Both sides of a simple arbitrage schedule: charge in the cheapest upcoming hours,
+// discharge in the most expensive ones. An hour is upcoming until it has ended, so the
+// current hour counts. Unpriced hours are ignored.
+
+fn upcoming_prices(points: &[SmardDataPoint], now: DateTime<Utc>) -> BTreeMap<i64, f64> {
+    let now_ms = now.timestamp_millis();
+    points.iter()
+        .filter(|dp| dp.timestamp + HOUR_MS > now_ms)
+        .filter_map(|dp| dp.value.map(|v| (dp.timestamp, v)))
+        .collect()
+}
+
+// `n` upcoming hours by price, ascending or descending, earliest first among equal prices.
+fn ranked_hours(points: &[SmardDataPoint], n: usize, now: DateTime<Utc>, descending: bool) -> Vec<(i64, f64)> {
+    let mut hours: Vec<(i64, f64)> = upcoming_prices(points, now).into_iter().collect();
+    // Stable sort on chronological input keeps ties in time order.
+    hours.sort_by(|a, b| {
+        let order = a.1.total_cmp(&b.1);
+        if descending { order.reverse() } else { order }
+    });
+    hours.truncate(n);
+    hours
+}
+
+/// The `n` cheapest upcoming hours as `(timestamp, price)`, cheapest first. Equal prices
+/// are ordered earliest first.
+pub fn cheapest_hours(points: &[SmardDataPoint], n: usize, now: DateTime<Utc>) -> Vec<(i64, f64)> {
+    ranked_hours(points, n, now, false)
+}
+
+/// The `n` most expensive upcoming hours as `(timestamp, price)`, dearest first. Equal
+/// prices are ordered earliest first.
+pub fn most_expensive_hours(points: &[SmardDataPoint], n: usize, now: DateTime<Utc>) -> Vec<(i64, f64)> {
+    ranked_hours(points, n, now, true)
+}
+
+/// Start and mean price of the most expensive run of `hours` consecutive upcoming priced
+/// hours, e.g. to discharge a battery into. The earliest start wins a tie; `None` if no
+/// such run exists.
+pub fn most_expensive_contiguous_window(points: &[SmardDataPoint], hours: u32, now: DateTime<Utc>) -> Option<(i64, f64)> {
+    if hours == 0 {
+        return None;
+    }
+    let priced = upcoming_prices(points, now);
+    let mut best: Option<(i64, f64)> = None;
+    for &start in priced.keys() {
+        let run: Option<f64> = (0..hours as i64)
+            .map(|h| priced.get(&(start + h * HOUR_MS)).copied())
+            .sum();
+        if let Some(price_sum) = run {
+            let mean = price_sum / hours as f64;
+            if best.is_none_or(|(_, best_mean)| mean > best_mean) {
+                best = Some((start, mean));
+            }
+        }
+    }
+    best
+}
+
+// --- German Local-Day Bucketing ---
+
+// SMARD timestamps are UTC, but prices are traded per German local day, and that day is
+// 23 hours long on the last Sunday in March and 25 hours in October. Grouping on
+// `timestamp / 86_400_000` (or in a fixed UTC+1) is off on those days; everything here goes
+// through Europe/Berlin instead.
+
+/// Start of `date` in Berlin, as UTC. Local midnight always exists in Germany (the DST
+/// switches happen at 02:00/03:00), so `earliest` only guards against a future rule change.
+pub fn berlin_midnight(date: NaiveDate) -> DateTime<Utc> {
+    Berlin.from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap())
+        .earliest()
+        .unwrap()
+        .with_timezone(&Utc)
+}
+
+/// Length of a German local day in hours: 24, or 23/25 on DST switch days.
+pub fn hours_in_local_day(date: NaiveDate) -> i64 {
+    (berlin_midnight(date + Duration::days(1)) - berlin_midnight(date)).num_hours()
+}
+
+/// Local date and hour of a SMARD timestamp. On the October switch day hour 2 occurs twice;
+/// `utc_offset_secs` (7200 for CEST, 3600 for CET) tells the two apart.
+pub fn local_hour(timestamp_ms: i64) -> Option<(NaiveDate, u32, i32)> {
+    let local = normalize_timestamp(timestamp_ms)?.with_timezone(&Berlin);
+    let offset = local.offset().fix().local_minus_utc();
+    Some((local.date_naive(), local.hour(), offset))
+}
+
+// One German local day of prices.
+#[pyclass(get_all)]
+#[derive(Debug, Clone)]
+pub struct DailyPrices {
+    pub date: String,         // YYYY-MM-DD, Europe/Berlin
+    pub hours_in_day: i64,    // 23, 24 or 25
+    pub priced_hours: usize,  // Hours in the series that carry a value
+    pub mean: Option<f64>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+#[pymethods]
+impl DailyPrices {
+    fn __repr__(&self) -> String {
+        format!(
+            "DailyPrices(date={:?}, hours_in_day={}, priced_hours={}, mean={:?}, min={:?}, max={:?})",
+            self.date, self.hours_in_day, self.priced_hours, self.mean, self.min, self.max
+        )
+    }
+}
+
+/// Groups points by German local day, oldest first. A day is complete when `priced_hours`
+/// equals `hours_in_day`.
+pub fn daily_prices(points: &[SmardDataPoint]) -> Vec<DailyPrices> {
+    let mut by_day: BTreeMap<NaiveDate, Vec<f64>> = BTreeMap::new();
+    for dp in points {
+        let Some((date, _, _)) = local_hour(dp.timestamp) else {
+            continue;
+        };
+        let values = by_day.entry(date).or_default();
+        if let Some(v) = dp.value {
+            values.push(v);
+        }
+    }
+    by_day.into_iter()
+        .map(|(date, values)| DailyPrices {
+            date: date.to_string(),
+            hours_in_day: hours_in_local_day(date),
+            priced_hours: values.len(),
+            mean: (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64),
+            min: values.iter().copied().reduce(f64::min),
+            max: values.iter().copied().reduce(f64::max),
+        })
+        .collect()
+}
+
+// --- aWATTar Market Data Format ---
+
+// `{"object": "list", "data": [{"start_timestamp", "end_timestamp", "marketprice", "unit"}]}`,
+// as served by api.awattar.de/v1/marketdata and read by many home-energy tools. Times are
+// epoch milliseconds, prices EUR/MWh, one entry per 60-minute slot.
+const AWATTAR_UNIT: &str = "Eur/MWh";
+const AWATTAR_SLOT_MS: i64 = 3_600_000;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct AwattarPrice {
+    start_timestamp: i64,
+    end_timestamp: i64,
+    marketprice: f64,
+    unit: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct AwattarMarketData {
+    object: String,
+    data: Vec<AwattarPrice>,
+}
+
+/// Priced points in timestamp order as aWATTar market data; unpriced hours are left out,
+/// as aWATTar does.
+pub fn to_awattar_format(points: &[SmardDataPoint]) -> serde_json::Value {
+    let mut data: Vec<AwattarPrice> = points.iter()
+        .filter_map(|dp| {
+            let start = normalize_timestamp(dp.timestamp)?.timestamp_millis();
+            Some(AwattarPrice {
+                start_timestamp: start,
+                end_timestamp: start + AWATTAR_SLOT_MS,
+                marketprice: dp.value?,
+                unit: AWATTAR_UNIT.to_string(),
+            })
+        })
+        .collect();
+    data.sort_by_key(|p| p.start_timestamp);
+    serde_json::to_value(AwattarMarketData { object: "list".to_string(), data })
+        .expect("aWATTar market data always serializes")
+}
+
+/// The reverse of `to_awattar_format`. Prices in another unit are rejected rather than
+/// silently mixed with EUR/MWh.
+pub fn from_awattar_format(value: &serde_json::Value) -> Result<Vec<SmardDataPoint>, CollectorError> {
+    let market: AwattarMarketData = serde_json::from_value(value.clone())
+        .map_err(|e| CollectorError::Parse { source: e, body: value.to_string() })?;
+    market.data.into_iter()
+        .map(|p| {
+            if !p.unit.eq_ignore_ascii_case(AWATTAR_UNIT) {
+                return Err(CollectorError::InvalidInput(format!(
+                    "aWATTar price at {} is in {:?}, expected {}",
+                    p.start_timestamp, p.unit, AWATTAR_UNIT
+                )));
+            }
+            Ok(SmardDataPoint { timestamp: p.start_timestamp, value: Some(p.marketprice) })
+        })
+        .collect()
+}
+
+// --- Price Bands ---
+
+// Five bands for colouring prices on dashboards. The four thresholds are the lower bounds
+// of Cheap, Normal, Expensive and VeryExpensive in EUR/MWh, ascending.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceBand {
+    VeryCheap,
+    Cheap,
+    Normal,
+    Expensive,
+    VeryExpensive,
+}
+
+impl PriceBand {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PriceBand::VeryCheap => "very_cheap",
+            PriceBand::Cheap => "cheap",
+            PriceBand::Normal => "normal",
+            PriceBand::Expensive => "expensive",
+            PriceBand::VeryExpensive => "very_expensive",
+        }
+    }
+}
+
+/// The band `value` falls into; a value equal to a threshold belongs to the band above it.
+pub fn classify_price(value: f64, thresholds: &[f64; 4]) -> Result<PriceBand, CollectorError> {
+    if thresholds.iter().any(|t| !t.is_finite()) || thresholds.windows(2).any(|w| w[0] > w[1]) {
+        return Err(CollectorError::InvalidInput(format!(
+            "price thresholds must be finite and ascending, got {:?}",
+            thresholds
+        )));
+    }
+    let bands = [PriceBand::VeryCheap, PriceBand::Cheap, PriceBand::Normal, PriceBand::Expensive, PriceBand::VeryExpensive];
+    Ok(bands[thresholds.iter().filter(|t| value >= **t).count()])
+}
+
+/// Thresholds at the series' 20th, 40th, 60th and 80th percentiles (linear interpolation),
+/// so each band holds about a fifth of the priced hours. `None` without any prices.
+pub fn quantile_thresholds(points: &[SmardDataPoint]) -> Option<[f64; 4]> {
+    let mut values: Vec<f64> = points.iter().filter_map(|dp| dp.value).collect();
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(f64::total_cmp);
+    let quantile = |q: f64| {
+        let pos = q * (values.len() - 1) as f64;
+        let (lower, upper) = (values[pos.floor() as usize], values[pos.ceil() as usize]);
+        lower + (upper - lower) * pos.fract()
+    };
+    Some([quantile(0.2), quantile(0.4), quantile(0.6), quantile(0.8)])
+}
+
+// --- Stepped Tariffs ---
+
+// Block tariffs bill a fixed rate per spot price range rather than the spot price itself.
+// Each band is `(threshold, tariff)`: from `threshold` (EUR/MWh, inclusive) up to the next
+// band's threshold, the tariff applies, in whatever unit the contract states.
+
+/// Checks that `bands` is non-empty, finite, and strictly ascending by threshold, so every
+/// price falls into exactly one band.
+pub fn validate_tariff_bands(bands: &[(f64, f64)]) -> Result<(), CollectorError> {
+    if bands.is_empty() {
+        return Err(CollectorError::InvalidInput("at least one tariff band is required".to_string()));
+    }
+    if let Some((threshold, tariff)) = bands.iter().find(|(threshold, tariff)| !threshold.is_finite() || !tariff.is_finite()) {
+        return Err(CollectorError::InvalidInput(format!(
+            "tariff band ({}, {}) must have a finite threshold and tariff",
+            threshold, tariff
+        )));
+    }
+    if let Some(w) = bands.windows(2).find(|w| w[0].0 >= w[1].0) {
+        return Err(CollectorError::InvalidInput(format!(
+            "tariff bands must be sorted by threshold without repeats, but {} is followed by {}",
+            w[0].0, w[1].0
+        )));
+    }
+    Ok(())
+}
+
+/// The billed tariff for each point: that of the band with the highest threshold at or
+/// below the price. Prices below the first threshold get the first band's tariff; missing
+/// prices stay missing.
+pub fn apply_tariff_bands(points: &[SmardDataPoint], bands: &[(f64, f64)]) -> Result<Vec<(i64, Option<f64>)>, CollectorError> {
+    validate_tariff_bands(bands)?;
+    Ok(points
+        .iter()
+        .map(|dp| {
+            let tariff = dp.value.map(|price| {
+                let above = bands.iter().filter(|(threshold, _)| price >= *threshold).count();
+                bands[above.saturating_sub(1)].1
+            });
+            (dp.timestamp, tariff)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(values: &[Option<f64>]) -> Vec<SmardDataPoint> {
+        values.iter()
+            .enumerate()
+            .map(|(i, &value)| SmardDataPoint { timestamp: 1_717_200_000_000 + i as i64 * HOUR_MS, value })
+            .collect()
+    }
+
+    #[test]
+    fn optimal_start_picks_the_cheapest_contiguous_run() {
+        let series = points(&[Some(90.0), Some(40.0), None, Some(30.0), Some(35.0), Some(80.0)]);
+        let start = series[0].timestamp;
+        let end = start + 6 * HOUR_MS;
+        // Hours 1-2 would be cheap but hour 2 is unpriced, so 3-4 wins: (30 + 35) EUR/MWh at 2 kW.
+        let (best, cost) = optimal_start(&series, 2, start, end, 2.0).unwrap();
+        assert_eq!(best, start + 3 * HOUR_MS);
+        assert!((cost - 0.13).abs() < 1e-12, "{}", cost);
+        // From hour 4 on, the only two-hour run left is 4-5, which ends right at not_after.
+        assert_eq!(optimal_start(&series, 2, start + 4 * HOUR_MS, end, 1.0).map(|(s, _)| s), Ok(start + 4 * HOUR_MS));
+    }
+
+    #[test]
+    fn optimal_start_explains_why_nothing_fits() {
+        let series = points(&[Some(90.0), None, Some(30.0)]);
+        let start = series[0].timestamp;
+        let end = start + 3 * HOUR_MS;
+        assert_eq!(optimal_start(&series, 0, start, end, 1.0), Err(NoStartWindow::ZeroDuration));
+        assert_eq!(
+            optimal_start(&series, 4, start, end, 1.0),
+            Err(NoStartWindow::DurationExceedsWindow { duration_hours: 4, available_hours: 3 })
+        );
+        assert_eq!(optimal_start(&series, 2, start, end, 1.0), Err(NoStartWindow::NoContiguousPrices { duration_hours: 2 }));
+        assert_eq!(optimal_start(&series, 1, start, end, -1.0), Err(NoStartWindow::InvalidPower { power_kw: -1.0 }));
+        assert!(matches!(optimal_start(&series, 1, start, end, f64::NAN), Err(NoStartWindow::InvalidPower { .. })));
+    }
+
+    #[test]
+    fn rounding_changes_values_not_timestamps() {
+        let mut series = points(&[Some(0.1 + 0.2), Some(87.456), Some(-3.005_1), None]);
+        let timestamps: Vec<i64> = series.iter().map(|dp| dp.timestamp).collect();
+        round_prices(&mut series, DEFAULT_PRICE_DECIMALS);
+        let values: Vec<Option<f64>> = series.iter().map(|dp| dp.value).collect();
+        assert_eq!(values, [Some(0.3), Some(87.46), Some(-3.01), None]);
+        assert_eq!(series.iter().map(|dp| dp.timestamp).collect::<Vec<_>>(), timestamps);
+        assert_eq!(serde_json::to_string(&series[0]).unwrap(), r#"{"timestamp":1717200000000,"value":0.3}"#);
+    }
+
+    #[test]
+    fn rounding_to_more_decimals_keeps_precision() {
+        assert_eq!(round_to(0.123_456, 4), 0.1235);
+        assert_eq!(round_to(42.0, 0), 42.0);
+    }
+
+    #[test]
+    fn awattar_payload_roundtrips() {
+        // Shape of api.awattar.de/v1/marketdata.
+        let payload = serde_json::json!({
+            "object": "list",
+            "data": [
+                {"start_timestamp": 1717200000000_i64, "end_timestamp": 1717203600000_i64, "marketprice": 81.42, "unit": "Eur/MWh"},
+                {"start_timestamp": 1717203600000_i64, "end_timestamp": 1717207200000_i64, "marketprice": -3.1, "unit": "Eur/MWh"}
+            ]
+        });
+        let parsed = from_awattar_format(&payload).unwrap();
+        let values: Vec<(i64, Option<f64>)> = parsed.iter().map(|dp| (dp.timestamp, dp.value)).collect();
+        assert_eq!(values, [(1_717_200_000_000, Some(81.42)), (1_717_203_600_000, Some(-3.1))]);
+        assert_eq!(to_awattar_format(&parsed), payload);
+    }
+
+    #[test]
+    fn awattar_export_sorts_and_skips_unpriced_hours() {
+        let series = [
+            SmardDataPoint { timestamp: 1_717_203_600_000, value: Some(90.0) },
+            SmardDataPoint { timestamp: 1_717_200_000_000, value: Some(80.0) },
+            SmardDataPoint { timestamp: 1_717_207_200_000, value: None },
+        ];
+        let data = to_awattar_format(&series)["data"].clone();
+        let starts: Vec<i64> = data.as_array().unwrap().iter().map(|p| p["start_timestamp"].as_i64().unwrap()).collect();
+        assert_eq!(starts, [1_717_200_000_000, 1_717_203_600_000]);
+    }
+
+    #[test]
+    fn awattar_import_rejects_other_units() {
+        let payload = serde_json::json!({
+            "object": "list",
+            "data": [{"start_timestamp": 1717200000000_i64, "end_timestamp": 1717203600000_i64, "marketprice": 8.1, "unit": "ct/kWh"}]
+        });
+        assert!(matches!(from_awattar_format(&payload), Err(CollectorError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn awattar_import_accepts_unit_in_any_case() {
+        let payload = serde_json::json!({
+            "object": "list",
+            "data": [{"start_timestamp": 1717200000000_i64, "end_timestamp": 1717203600000_i64, "marketprice": 81.4, "unit": "EUR/MWH"}]
+        });
+        let points = from_awattar_format(&payload).unwrap();
+        assert_eq!((points[0].timestamp, points[0].value), (1_717_200_000_000, Some(81.4)));
+    }
+
+    #[test]
+    fn awattar_import_rejects_malformed_payload() {
+        let payload = serde_json::json!({"object": "list", "data": [{"marketprice": 81.4}]});
+        assert!(matches!(from_awattar_format(&payload), Err(CollectorError::Parse { .. })));
+    }
+
+    // Every hour of the Berlin local day `date`, priced 1.0, 2.0, ... in order.
+    fn local_day(date: NaiveDate) -> Vec<SmardDataPoint> {
+        let start = berlin_midnight(date).timestamp_millis();
+        (0..hours_in_local_day(date))
+            .map(|h| SmardDataPoint { timestamp: start + h * HOUR_MS, value: Some(h as f64 + 1.0) })
+            .collect()
+    }
+
+    #[test]
+    fn october_switch_day_has_25_hours() {
+        let date = NaiveDate::from_ymd_opt(2024, 10, 27).unwrap();
+        assert_eq!(hours_in_local_day(date), 25);
+        assert_eq!(berlin_midnight(date), Utc.with_ymd_and_hms(2024, 10, 26, 22, 0, 0).unwrap());
+        // 02:00 occurs twice, first in CEST, then in CET.
+        let points = local_day(date);
+        assert_eq!(local_hour(points[2].timestamp), Some((date, 2, 7200)));
+        assert_eq!(local_hour(points[3].timestamp), Some((date, 2, 3600)));
+
+        let days = daily_prices(&points);
+        assert_eq!(days.len(), 1);
+        assert_eq!((days[0].date.as_str(), days[0].hours_in_day, days[0].priced_hours), ("2024-10-27", 25, 25));
+        assert_eq!((days[0].min, days[0].max, days[0].mean), (Some(1.0), Some(25.0), Some(13.0)));
+    }
+
+    #[test]
+    fn march_switch_day_has_23_hours() {
+        let date = NaiveDate::from_ymd_opt(2024, 3, 31).unwrap();
+        assert_eq!(hours_in_local_day(date), 23);
+        assert_eq!(berlin_midnight(date), Utc.with_ymd_and_hms(2024, 3, 30, 23, 0, 0).unwrap());
+        // 02:00 doesn't exist: 01:00 CET is followed by 03:00 CEST.
+        let points = local_day(date);
+        assert_eq!(local_hour(points[1].timestamp), Some((date, 1, 3600)));
+        assert_eq!(local_hour(points[2].timestamp), Some((date, 3, 7200)));
+
+        let mut with_next_day = points;
+        with_next_day.push(SmardDataPoint { timestamp: berlin_midnight(date.succ_opt().unwrap()).timestamp_millis(), value: None });
+        let days = daily_prices(&with_next_day);
+        assert_eq!(days.len(), 2);
+        assert_eq!((days[0].hours_in_day, days[0].priced_hours, days[0].max), (23, 23, Some(23.0)));
+        assert_eq!((days[1].date.as_str(), days[1].priced_hours), ("2024-04-01", 0));
+    }
+
+    #[test]
+    fn ordinary_day_has_24_hours() {
+        assert_eq!(hours_in_local_day(NaiveDate::from_ymd_opt(2024, 6, 1).unwrap()), 24);
+    }
+
+}
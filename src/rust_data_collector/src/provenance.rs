@@ -0,0 +1,86 @@
+// src/rust_data_collector/src/provenance.rs
+
+use serde::Serialize;
+
+use crate::{OneCallOptions, SMARD_PRICE_FILTER, SMARD_RESOLUTION};
+
+// --- Configuration Hash ---
+
+// Saved files carry a hash of the settings that produced them, so data can be matched to
+// its configuration later. It covers everything that changes what is fetched or how it is
+// processed, but not when: a relative window is recorded as such ("last 48h"), so
+// identical scheduled runs share a hash. The input is this struct as compact JSON, whose
+// field order is fixed by the declaration, and the hash is FNV-1a, which (unlike std's
+// `DefaultHasher`) is the same on every platform and Rust release.
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RunConfig {
+    pub crate_version: &'static str,
+    pub lat: f64,
+    pub lon: f64,
+    pub units: &'static str, // What One Call is asked for; the only one the parsers accept
+    pub lang: &'static str,  // One Call's default, as no `lang` is sent
+    pub weather_api: String, // e.g. "onecall/3.0" or "onecall/3.0/timemachine"
+    pub blocks: Vec<&'static str>,
+    pub fallback_to_v2_5: bool,
+    pub region: String,
+    pub price_filter: &'static str,
+    pub resolution: &'static str,
+    pub window: String, // "last 48h", "incremental", "next 48h" or "<start>/<end>"
+    pub price_decimals: Option<u32>,
+    // Declared unit of the price filter; left out when it's EUR/MWh, keeping older hashes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price_unit: Option<&'static str>,
+}
+
+impl RunConfig {
+    /// The fixed parts for a run at `lat`/`lon` with One Call `options`; the rest is set
+    /// by the caller.
+    pub fn new(lat: f64, lon: f64, options: &OneCallOptions, region: &str, window: impl Into<String>) -> Self {
+        let b = &options.blocks;
+        let blocks = [("current", b.current), ("minutely", b.minutely), ("hourly", b.hourly), ("daily", b.daily), ("alerts", b.alerts)]
+            .into_iter()
+            .filter_map(|(name, on)| on.then_some(name))
+            .collect();
+        RunConfig {
+            crate_version: env!("CARGO_PKG_VERSION"),
+            lat,
+            lon,
+            units: "metric",
+            lang: "en",
+            weather_api: format!("onecall/{}", options.api_version.as_str()),
+            blocks,
+            fallback_to_v2_5: options.fallback_to_v2_5,
+            region: region.to_string(),
+            price_filter: SMARD_PRICE_FILTER,
+            resolution: SMARD_RESOLUTION,
+            window: window.into(),
+            price_decimals: None,
+            price_unit: None,
+        }
+    }
+
+    /// 16 hex digits; equal configurations always give the same hash.
+    pub fn hash(&self) -> String {
+        let canonical = serde_json::to_string(self).expect("RunConfig always serializes");
+        format!("{:016x}", fnv1a(canonical.as_bytes()))
+    }
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn declared_price_unit_changes_the_hash_only_when_set() {
+        let config = RunConfig::new(52.52, 13.405, &OneCallOptions::default(), "DE", "last 48h");
+        assert!(!serde_json::to_string(&config).unwrap().contains("price_unit"));
+        let declared = RunConfig { price_unit: Some("ct/kWh"), ..config.clone() };
+        assert_ne!(config.hash(), declared.hash());
+        assert_eq!(config.hash(), config.clone().hash());
+    }
+}
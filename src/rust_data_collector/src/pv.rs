@@ -0,0 +1,99 @@
+// src/rust_data_collector/src/pv.rs
+
+use chrono::{DateTime, NaiveDateTime};
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use std::fs;
+
+use crate::timestamp::{hour_start, to_unix_seconds};
+use crate::CollectorError;
+
+// --- PV Forecast Validation ---
+
+// Error metrics of a PV forecast against measured output, in kW.
+#[pyclass(get_all)]
+#[derive(Debug, Clone, Copy)]
+pub struct PvAccuracy {
+    pub mae: f64,
+    pub rmse: f64,
+    pub bias: f64, // mean(forecast - actual); positive means the forecast runs high
+    pub matched_hours: usize,
+}
+
+#[pymethods]
+impl PvAccuracy {
+    fn __repr__(&self) -> String {
+        format!(
+            "PvAccuracy(mae={:.3}, rmse={:.3}, bias={:.3}, matched_hours={})",
+            self.mae, self.rmse, self.bias, self.matched_hours
+        )
+    }
+}
+
+// Accepts epoch seconds or milliseconds (told apart by magnitude), RFC3339, or a naive
+// "YYYY-MM-DD HH:MM[:SS]" taken as UTC. Returns Unix seconds.
+fn parse_csv_timestamp(raw: &str) -> Option<i64> {
+    if let Ok(epoch) = raw.parse::<i64>() {
+        return Some(to_unix_seconds(epoch));
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc3339(raw) {
+        return Some(dt.timestamp());
+    }
+    ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|fmt| NaiveDateTime::parse_from_str(raw, fmt).ok())
+        .map(|dt| dt.and_utc().timestamp())
+}
+
+/// Reads measured PV output from a `timestamp,kW` CSV (one optional header row).
+/// Returns `(unix_seconds, kw)` pairs in file order.
+pub fn load_actual_pv(path: &str) -> Result<Vec<(i64, f64)>, CollectorError> {
+    let content = fs::read_to_string(path)?;
+    let mut actuals = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut fields = line.split([',', ';']).map(str::trim);
+        let (raw_ts, raw_kw) = match (fields.next(), fields.next()) {
+            (Some(ts), Some(kw)) => (ts, kw),
+            _ => {
+                return Err(CollectorError::InvalidInput(format!(
+                    "{} line {}: expected `timestamp,kW`, got {:?}", path, i + 1, line
+                )))
+            }
+        };
+        match (parse_csv_timestamp(raw_ts), raw_kw.parse::<f64>()) {
+            (Some(ts), Ok(kw)) => actuals.push((ts, kw)),
+            // A non-numeric first row is the header.
+            _ if i == 0 => continue,
+            _ => {
+                return Err(CollectorError::InvalidInput(format!(
+                    "{} line {}: could not parse timestamp {:?} / kW {:?}", path, i + 1, raw_ts, raw_kw
+                )))
+            }
+        }
+    }
+    Ok(actuals)
+}
+
+/// Compares forecast and measured PV output hour by hour. Both are `(unix_seconds, kw)`;
+/// timestamps are matched on the start of their hour and unmatched hours are ignored.
+/// `None` if the two series share no hours.
+pub fn compare_pv_forecast(forecast: &[(i64, f64)], actuals: &[(i64, f64)]) -> Option<PvAccuracy> {
+    let actual_by_hour: HashMap<i64, f64> = actuals.iter().map(|&(ts, kw)| (hour_start(ts), kw)).collect();
+    let errors: Vec<f64> = forecast.iter()
+        .filter_map(|&(ts, kw)| actual_by_hour.get(&hour_start(ts)).map(|actual| kw - actual))
+        .collect();
+    if errors.is_empty() {
+        return None;
+    }
+    let n = errors.len() as f64;
+    Some(PvAccuracy {
+        mae: errors.iter().map(|e| e.abs()).sum::<f64>() / n,
+        rmse: (errors.iter().map(|e| e * e).sum::<f64>() / n).sqrt(),
+        bias: errors.iter().sum::<f64>() / n,
+        matched_hours: errors.len(),
+    })
+}
@@ -0,0 +1,425 @@
+// src/rust_data_collector/src/quality.rs
+
+use chrono::{DateTime, Duration, Utc};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use crate::merge::HourlyPoint;
+use crate::timestamp::normalize_timestamp;
+use crate::{OpenWeatherHourlyForecast, SmardDataPoint};
+
+// --- Warnings ---
+
+// A non-fatal finding about fetched data. `code` is stable and meant for programs (e.g. to
+// pick an icon or silence one kind); `message` is for people.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning {
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl Warning {
+    pub fn new(code: &'static str, message: impl Into<String>) -> Self {
+        Warning { code, message: message.into() }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+// Python sees `{"code": ..., "message": ...}`.
+impl IntoPy<PyObject> for Warning {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        let dict = PyDict::new_bound(py);
+        dict.set_item("code", self.code).expect("str keys always insert");
+        dict.set_item("message", self.message).expect("str keys always insert");
+        dict.into_py(py)
+    }
+}
+
+// --- Data Quality Checks ---
+
+// Default for how old the newest SMARD point may be before we flag the feed as stale.
+pub const DEFAULT_MAX_STALENESS_HOURS: i64 = 3;
+
+/// Age of the newest point carrying a value relative to `now`, or `None` if there is none.
+/// A negative age means the series already reaches into the future (e.g. day-ahead prices).
+pub fn latest_data_age(points: &[SmardDataPoint], now: DateTime<Utc>) -> Option<Duration> {
+    let newest_ms = points.iter()
+        .filter(|dp| dp.value.is_some())
+        .map(|dp| dp.timestamp)
+        .max()?;
+    let newest = normalize_timestamp(newest_ms)?;
+    Some(now - newest)
+}
+
+// SMARD sometimes stops updating while still answering 200, so a "successful" fetch can
+// end hours in the past. Returns a warning when that happens.
+pub fn check_staleness(points: &[SmardDataPoint], now: DateTime<Utc>, max_staleness_hours: i64) -> Option<Warning> {
+    match latest_data_age(points, now) {
+        None => Some(Warning::new("no_prices", "SMARD returned no priced data points in the requested window")),
+        Some(age) if age > Duration::hours(max_staleness_hours) => Some(Warning::new("stale_prices", format!(
+            "Newest SMARD price is {} hours old (limit {} hours); the upstream feed may have stopped updating",
+            age.num_hours(),
+            max_staleness_hours
+        ))),
+        Some(_) => None,
+    }
+}
+
+// --- Weather Plausibility ---
+
+// OpenWeatherMap answers for any coordinate, so a swapped lat/lon or a point in the open
+// ocean still "succeeds". These checks look at the output for signs the input was off.
+// They only produce warnings; the data is kept either way.
+
+// Beyond the recorded extremes on land (about -89 °C and +57 °C) with some margin for
+// surface temperatures; anything outside is a unit or parsing problem.
+pub const PLAUSIBLE_TEMP_RANGE_C: (f64, f64) = (-90.0, 60.0);
+
+// A real forecast never keeps temperature and cloud cover exactly constant this long.
+const FLATLINE_HOURS: usize = 12;
+
+pub fn check_weather_plausibility(hourly: &[OpenWeatherHourlyForecast]) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+    if hourly.is_empty() {
+        warnings.push(Warning::new("no_hourly_forecast", "OpenWeatherMap returned no hourly forecast"));
+        return warnings;
+    }
+    let (low, high) = PLAUSIBLE_TEMP_RANGE_C;
+    if let Some(hour) = hourly.iter().find(|h| !(low..=high).contains(&h.temp)) {
+        warnings.push(Warning::new("implausible_temperature", format!(
+            "Implausible temperature {} °C at dt={} (expected {} to {} °C); check the units and coordinates",
+            hour.temp, hour.dt, low, high
+        )));
+    }
+    if let Some(hour) = hourly.iter().find(|h| !(0..=100).contains(&h.clouds.all)) {
+        warnings.push(Warning::new(
+            "implausible_clouds",
+            format!("Implausible cloud cover {}% at dt={}", hour.clouds.all, hour.dt),
+        ));
+    }
+    let first = &hourly[0];
+    if hourly.len() >= FLATLINE_HOURS && hourly.iter().all(|h| h.temp == first.temp && h.clouds.all == first.clouds.all) {
+        warnings.push(Warning::new("constant_forecast", format!(
+            "Weather forecast is constant for all {} hours ({} °C, {}% clouds); the coordinates may be outside the model's coverage",
+            hourly.len(), first.temp, first.clouds.all
+        )));
+    }
+    warnings
+}
+
+// Inside the polar circles the sun can stay down all day, so zero noon irradiance is real.
+const POLAR_CIRCLE_LAT: f64 = 66.5;
+
+/// Warns when every hour within an hour of local solar noon has zero irradiance, which a
+/// plausible location never shows. Hours without `ghi` are ignored.
+pub fn check_irradiance_plausibility(points: &[HourlyPoint], lat: f64, lon: f64) -> Option<Warning> {
+    if lat.abs() >= POLAR_CIRCLE_LAT {
+        return None;
+    }
+    // Solar noon in UTC hours, ignoring the equation of time (±16 min).
+    let solar_noon_utc = (12.0 - lon / 15.0).rem_euclid(24.0);
+    let noon_ghi: Vec<f64> = points.iter()
+        .filter(|p| {
+            let hour = (p.timestamp.rem_euclid(86_400) as f64) / 3600.0 + 0.5; // middle of the hour
+            let distance = (hour - solar_noon_utc).abs();
+            distance.min(24.0 - distance) <= 1.0
+        })
+        .filter_map(|p| p.ghi)
+        .collect();
+    (!noon_ghi.is_empty() && noon_ghi.iter().all(|ghi| *ghi <= 0.0)).then(|| Warning::new("zero_noon_irradiance", format!(
+        "Irradiance is zero in all {} forecast hours around solar noon; check the coordinates ({}, {})",
+        noon_ghi.len(), lat, lon
+    )))
+}
+
+// --- Source Offsets ---
+
+// A provider whose local-time stamps were read as UTC lines up with everything else
+// shifted by the UTC offset, and the merge can't see it: every hour still finds a partner.
+// The sun gives a reference that doesn't depend on any source: irradiance peaks at solar
+// noon, temperature one to four hours later. A source whose daily peak is off on every day
+// by more than the weather can explain is flagged. Temperature varies too much to catch a
+// one or two hour shift; irradiance catches those.
+
+// (code, what, lowest and highest plausible peak in hours after solar noon, minimum daily range)
+const DIURNAL_CHECKS: [(&str, &str, f64, f64, f64); 2] = [
+    ("irradiance_offset", "Irradiance", -1.5, 1.5, 50.0),
+    ("temperature_offset", "Temperature", -1.0, 6.0, 2.0),
+];
+
+// Hours after solar noon of the daily peaks of `value`, one per solar day that has at
+// least 20 hours and varies by at least `min_range`.
+fn daily_peak_offsets(points: &[HourlyPoint], lon: f64, value: impl Fn(&HourlyPoint) -> Option<f64>, min_range: f64) -> Vec<f64> {
+    // Solar noon in UTC hours, ignoring the equation of time (±16 min).
+    let solar_noon_utc = (12.0 - lon / 15.0).rem_euclid(24.0);
+    let solar_midnight_s = ((solar_noon_utc - 12.0) * 3600.0) as i64;
+    let mut days: HashMap<i64, Vec<(f64, f64)>> = HashMap::new();
+    for p in points {
+        if let Some(v) = value(p) {
+            let since_midnight = p.timestamp - solar_midnight_s;
+            let hour = (since_midnight.rem_euclid(86_400) as f64) / 3600.0 + 0.5; // middle of the hour, solar time
+            days.entry(since_midnight.div_euclid(86_400)).or_default().push((hour - 12.0, v));
+        }
+    }
+    let mut offsets: Vec<f64> = days.into_values()
+        .filter(|hours| hours.len() >= 20)
+        .filter_map(|hours| {
+            let (lo, hi) = hours.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &(_, v)| (lo.min(v), hi.max(v)));
+            let peak = hours.iter().copied().reduce(|best, h| if h.1 > best.1 { h } else { best })?;
+            (hi - lo >= min_range).then_some(peak.0)
+        })
+        .collect();
+    offsets.sort_by(f64::total_cmp);
+    offsets
+}
+
+/// Warns when irradiance or temperature peaks at an implausible time of day on every day
+/// with enough data (at least two), which points to timestamps shifted by a UTC offset.
+/// Inside the polar circles the sun gives no usable reference.
+pub fn check_diurnal_offset(points: &[HourlyPoint], lat: f64, lon: f64) -> Vec<Warning> {
+    if lat.abs() >= POLAR_CIRCLE_LAT {
+        return Vec::new();
+    }
+    let values: [fn(&HourlyPoint) -> Option<f64>; 2] = [|p| p.ghi, |p| Some(p.temp)];
+    DIURNAL_CHECKS.iter()
+        .zip(values)
+        .filter_map(|(&(code, what, earliest, latest, min_range), value)| {
+            let offsets = daily_peak_offsets(points, lon, value, min_range);
+            if offsets.len() < 2 {
+                return None;
+            }
+            let all_early = offsets.iter().all(|&o| o < earliest);
+            let all_late = offsets.iter().all(|&o| o > latest);
+            if !(all_early || all_late) {
+                return None;
+            }
+            let median = offsets[offsets.len() / 2];
+            Some(Warning::new(code, format!(
+                "{} peaks {:.0} h {} solar noon on all {} days; its timestamps look shifted, e.g. local time read as UTC",
+                what, median.abs(), if median < 0.0 { "before" } else { "after" }, offsets.len()
+            )))
+        })
+        .collect()
+}
+
+// --- Duplicate Forecast Hours ---
+
+// OpenWeatherMap occasionally repeats an hourly `dt`, which breaks anything keyed by
+// timestamp downstream. By default the first entry wins; the later one can be preferred
+// when it's known to be the corrected value.
+static KEEP_LAST_DUPLICATE: AtomicBool = AtomicBool::new(false);
+
+pub fn set_keep_last_duplicate(keep_last: bool) {
+    KEEP_LAST_DUPLICATE.store(keep_last, Ordering::Relaxed);
+}
+
+/// Drops hourly entries whose `dt` already occurred, keeping the first or the last of each
+/// group as `keep_last` says, in the original order. Returns the duplicated `dt`s.
+pub fn dedupe_hourly(hourly: &mut Vec<OpenWeatherHourlyForecast>, keep_last: bool) -> Vec<i64> {
+    let mut kept_at: HashMap<i64, usize> = HashMap::new();
+    let mut duplicated = Vec::new();
+    for (i, hour) in hourly.iter().enumerate() {
+        if let Some(first) = kept_at.insert(hour.dt, i) {
+            if !keep_last {
+                kept_at.insert(hour.dt, first);
+            }
+            if !duplicated.contains(&hour.dt) {
+                duplicated.push(hour.dt);
+            }
+        }
+    }
+    if duplicated.is_empty() {
+        return duplicated;
+    }
+    let mut i = 0;
+    hourly.retain(|hour| {
+        let keep = kept_at[&hour.dt] == i;
+        i += 1;
+        keep
+    });
+    duplicated
+}
+
+/// `dedupe_hourly` with the configured policy, logging each duplicated hour.
+pub fn drop_duplicate_hours(hourly: &mut Vec<OpenWeatherHourlyForecast>) {
+    let keep_last = KEEP_LAST_DUPLICATE.load(Ordering::Relaxed);
+    for dt in dedupe_hourly(hourly, keep_last) {
+        log!(
+            "WARNING (Rust): OpenWeatherMap returned hour dt={} more than once; keeping the {} entry.",
+            dt,
+            if keep_last { "last" } else { "first" }
+        );
+    }
+}
+
+// --- Hour Ordering ---
+
+/// Sorts hourly entries by `dt`, ascending. OpenWeatherMap normally sends them in order but
+/// occasionally doesn't, and the merge and every consumer of the saved file expect a
+/// monotonic index. The sort is stable, so run it after `drop_duplicate_hours`.
+pub fn sort_hourly(hourly: &mut [OpenWeatherHourlyForecast]) {
+    if hourly.windows(2).any(|w| w[0].dt > w[1].dt) {
+        log!("WARNING (Rust): OpenWeatherMap hourly entries arrived out of order; sorting them by time.");
+        hourly.sort_by_key(|hour| hour.dt);
+    }
+}
+
+// --- Forecast Confidence ---
+
+// A deliberately simple reliability heuristic, not a calibrated error model: confidence
+// starts at 1.0 for the current hour and halves every `half_life` hours ahead. It only has
+// to be monotonic so consumers can weight near hours above distant ones. 24h roughly
+// matches how quickly hourly cloud-cover forecasts lose skill. Past hours count as 1.0.
+pub const DEFAULT_CONFIDENCE_HALF_LIFE_HOURS: f64 = 24.0;
+
+// f64 bits, so the half-life can be changed from Python without a lock.
+static CONFIDENCE_HALF_LIFE_BITS: AtomicU64 = AtomicU64::new(DEFAULT_CONFIDENCE_HALF_LIFE_HOURS.to_bits());
+
+pub fn set_confidence_half_life(hours: f64) {
+    CONFIDENCE_HALF_LIFE_BITS.store(hours.to_bits(), Ordering::Relaxed);
+}
+
+pub fn confidence_half_life() -> f64 {
+    f64::from_bits(CONFIDENCE_HALF_LIFE_BITS.load(Ordering::Relaxed))
+}
+
+/// `0.5^(hours_ahead / half_life_hours)`, in (0, 1].
+pub fn forecast_confidence(hours_ahead: f64, half_life_hours: f64) -> f64 {
+    0.5f64.powf(hours_ahead.max(0.0) / half_life_hours)
+}
+
+/// Sets `confidence` on each forecast hour from its distance to `now`, using the
+/// configured half-life.
+pub fn attach_confidence(hourly: &mut [OpenWeatherHourlyForecast], now: DateTime<Utc>) {
+    let half_life = confidence_half_life();
+    for hour in hourly.iter_mut() {
+        let hours_ahead = (hour.dt - now.timestamp()) as f64 / 3600.0;
+        hour.confidence = Some(forecast_confidence(hours_ahead, half_life));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap()
+    }
+
+    fn hour(dt: i64, temp: f64) -> OpenWeatherHourlyForecast {
+        serde_json::from_value(serde_json::json!({
+            "dt": dt, "temp": temp, "weather": [{"description": "clear sky", "icon": "01d"}], "pop": 0.0, "clouds": {"all": 0}
+        }))
+        .unwrap()
+    }
+
+    fn prices_until(newest: DateTime<Utc>) -> Vec<SmardDataPoint> {
+        (0..24)
+            .map(|h| SmardDataPoint { timestamp: (newest - Duration::hours(h)).timestamp_millis(), value: Some(80.0) })
+            .collect()
+    }
+
+    #[test]
+    fn latest_data_age_ignores_unpublished_hours() {
+        let mut points = prices_until(now() - Duration::hours(2));
+        points.push(SmardDataPoint { timestamp: (now() + Duration::hours(10)).timestamp_millis(), value: None });
+        assert_eq!(latest_data_age(&points, now()), Some(Duration::hours(2)));
+        assert_eq!(latest_data_age(&[], now()), None);
+    }
+
+    #[test]
+    fn stale_series_warns() {
+        let stale = prices_until(now() - Duration::hours(7));
+        let warning = check_staleness(&stale, now(), DEFAULT_MAX_STALENESS_HOURS).expect("stale series flagged");
+        assert_eq!(warning.code, "stale_prices");
+        assert!(warning.message.contains("7 hours old"), "{}", warning.message);
+    }
+
+    #[test]
+    fn fresh_and_day_ahead_series_pass() {
+        assert_eq!(check_staleness(&prices_until(now() - Duration::hours(1)), now(), DEFAULT_MAX_STALENESS_HOURS), None);
+        assert_eq!(check_staleness(&prices_until(now() + Duration::hours(12)), now(), DEFAULT_MAX_STALENESS_HOURS), None);
+    }
+
+    #[test]
+    fn unpriced_series_warns() {
+        let empty = [SmardDataPoint { timestamp: now().timestamp_millis(), value: None }];
+        assert_eq!(check_staleness(&empty, now(), DEFAULT_MAX_STALENESS_HOURS).map(|w| w.code), Some("no_prices"));
+    }
+
+    #[test]
+    fn confidence_halves_every_half_life_and_never_exceeds_one() {
+        assert_eq!(forecast_confidence(0.0, 24.0), 1.0);
+        assert_eq!(forecast_confidence(-5.0, 24.0), 1.0);
+        assert!((forecast_confidence(24.0, 24.0) - 0.5).abs() < 1e-12);
+        assert!((forecast_confidence(48.0, 24.0) - 0.25).abs() < 1e-12);
+        let curve: Vec<f64> = (0..48).map(|h| forecast_confidence(h as f64, 12.0)).collect();
+        assert!(curve.windows(2).all(|w| w[1] < w[0]));
+    }
+
+    #[test]
+    fn attached_confidence_falls_with_lead_time() {
+        let start = now().timestamp();
+        let mut hourly: Vec<OpenWeatherHourlyForecast> = (0..3).map(|h| hour(start + h * 3600 * 24, 20.0)).collect();
+        attach_confidence(&mut hourly, now());
+        let confidence: Vec<f64> = hourly.iter().map(|h| h.confidence.unwrap()).collect();
+        assert_eq!(confidence[0], 1.0);
+        assert!(confidence[1] < confidence[0] && confidence[2] < confidence[1], "{:?}", confidence);
+    }
+
+    fn dts(hourly: &[OpenWeatherHourlyForecast]) -> Vec<i64> {
+        hourly.iter().map(|h| h.dt).collect()
+    }
+
+    #[test]
+    fn shuffled_hours_are_sorted_by_dt() {
+        let base = now().timestamp();
+        let mut hourly: Vec<_> = [3, 0, 4, 1, 2].iter().map(|&h| hour(base + h * 3600, h as f64)).collect();
+        sort_hourly(&mut hourly);
+        assert_eq!(dts(&hourly), (0..5).map(|h| base + h * 3600).collect::<Vec<_>>());
+        // Each entry moved with its own data.
+        assert!(hourly.iter().enumerate().all(|(h, entry)| entry.temp == h as f64));
+    }
+
+    #[test]
+    fn sorted_hours_are_left_as_they_are() {
+        let base = now().timestamp();
+        let mut hourly: Vec<_> = (0..3).map(|h| hour(base + h * 3600, 20.0)).collect();
+        sort_hourly(&mut hourly);
+        assert_eq!(dts(&hourly), vec![base, base + 3600, base + 7200]);
+    }
+
+    #[test]
+    fn duplicate_hours_keep_one_entry_each() {
+        let base = now().timestamp();
+        let fetched = || vec![hour(base, 1.0), hour(base + 3600, 2.0), hour(base, 3.0), hour(base + 3600, 4.0), hour(base, 5.0)];
+
+        let mut first = fetched();
+        assert_eq!(dedupe_hourly(&mut first, false), vec![base, base + 3600]);
+        assert_eq!(dts(&first), vec![base, base + 3600]);
+        assert_eq!(first.iter().map(|h| h.temp).collect::<Vec<_>>(), vec![1.0, 2.0]);
+
+        let mut last = fetched();
+        assert_eq!(dedupe_hourly(&mut last, true), vec![base, base + 3600]);
+        assert_eq!(dts(&last), vec![base + 3600, base]);
+        assert_eq!(last.iter().map(|h| h.temp).collect::<Vec<_>>(), vec![4.0, 5.0]);
+    }
+
+    #[test]
+    fn unique_hours_are_untouched() {
+        let base = now().timestamp();
+        let mut hourly = vec![hour(base, 1.0), hour(base + 3600, 2.0)];
+        assert!(dedupe_hourly(&mut hourly, false).is_empty());
+        assert_eq!(hourly.len(), 2);
+    }
+
+}
@@ -0,0 +1,85 @@
+// src/rust_data_collector/src/refresh.rs
+
+use pyo3::prelude::*;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+// --- Background Cache Refresh ---
+
+// A thread that runs a task now and then every `interval` until stopped. Stopping wakes
+// it from its wait at once, so shutting down only ever waits for a task already running,
+// which the HTTP timeouts bound. What to refresh, and what to do when that fails, is up
+// to the task.
+
+pub struct RefreshLoop {
+    stop: Arc<(Mutex<bool>, Condvar)>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl RefreshLoop {
+    pub fn spawn(interval: Duration, mut task: impl FnMut() + Send + 'static) -> Self {
+        let stop = Arc::new((Mutex::new(false), Condvar::new()));
+        let thread_stop = Arc::clone(&stop);
+        let thread = std::thread::spawn(move || {
+            let (stopped, wake) = &*thread_stop;
+            loop {
+                task();
+                let guard = wake.wait_timeout_while(stopped.lock().unwrap(), interval, |stopped| !*stopped).unwrap().0;
+                if *guard {
+                    return;
+                }
+            }
+        });
+        RefreshLoop { stop, thread: Some(thread) }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.thread.as_ref().is_some_and(|thread| !thread.is_finished())
+    }
+
+    /// Signals the thread and waits for it to exit. Later calls do nothing.
+    pub fn stop(&mut self) {
+        let Some(thread) = self.thread.take() else {
+            return;
+        };
+        let (stopped, wake) = &*self.stop;
+        *stopped.lock().unwrap() = true;
+        wake.notify_all();
+        if thread.join().is_err() {
+            log!("ERROR (Rust): Background refresh thread panicked.");
+        }
+    }
+}
+
+impl Drop for RefreshLoop {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Handle returned by `start_background_refresh`. The refresh runs until `stop()` is
+/// called or the interpreter exits.
+#[pyclass]
+pub struct BackgroundRefresh {
+    pub inner: RefreshLoop,
+    pub interval_secs: f64,
+}
+
+#[pymethods]
+impl BackgroundRefresh {
+    /// Stops the refresh and waits for a fetch in progress to finish. Safe to call twice.
+    fn stop(&mut self, py: Python<'_>) {
+        let inner = &mut self.inner;
+        py.allow_threads(|| inner.stop());
+    }
+
+    #[getter]
+    fn running(&self) -> bool {
+        self.inner.is_running()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("BackgroundRefresh(interval_secs={}, running={})", self.interval_secs, self.inner.is_running())
+    }
+}
@@ -0,0 +1,157 @@
+// src/rust_data_collector/src/resample.rs
+
+use std::collections::BTreeMap;
+
+use crate::{CollectorError, SmardDataPoint};
+
+// --- Resampling ---
+
+// Moves a `(timestamp_ms, value)` series onto another fixed grid, e.g. hourly SMARD prices
+// onto quarter hours or minutely weather onto hours, so mismatched sources can be merged.
+// Grid points are multiples of the step since the epoch, which for steps up to an hour is
+// also aligned in German local time. A point stands for the interval from its timestamp to
+// the next point.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResampleMethod {
+    /// Upsampling: every grid point takes the value of the latest point at or before it.
+    ForwardFill,
+    /// Upsampling: linear between the surrounding points; the last interval is held flat.
+    Interpolate,
+    /// Downsampling: mean of the points inside each grid interval.
+    Mean,
+    /// Downsampling: sum of the points inside each grid interval (energy, volumes).
+    Sum,
+}
+
+impl ResampleMethod {
+    pub fn parse(method: &str) -> Option<Self> {
+        match method {
+            "ffill" => Some(ResampleMethod::ForwardFill),
+            "interpolate" => Some(ResampleMethod::Interpolate),
+            "mean" => Some(ResampleMethod::Mean),
+            "sum" => Some(ResampleMethod::Sum),
+            _ => None,
+        }
+    }
+}
+
+fn floor_to(timestamp: i64, step: i64) -> i64 {
+    timestamp - timestamp.rem_euclid(step)
+}
+
+// Points that share a grid interval are aggregated; an interval whose points are all
+// unpriced stays `None`.
+fn downsample(points: &BTreeMap<i64, Option<f64>>, step: i64, sum: bool) -> Vec<SmardDataPoint> {
+    let mut buckets: BTreeMap<i64, Vec<f64>> = BTreeMap::new();
+    for (&timestamp, value) in points {
+        let bucket = buckets.entry(floor_to(timestamp, step)).or_default();
+        bucket.extend(*value);
+    }
+    buckets.into_iter()
+        .map(|(timestamp, values)| {
+            let total: f64 = values.iter().sum();
+            let value = match (values.is_empty(), sum) {
+                (true, _) => None,
+                (false, true) => Some(total),
+                (false, false) => Some(total / values.len() as f64),
+            };
+            SmardDataPoint { timestamp, value }
+        })
+        .collect()
+}
+
+// Grid points from the first point up to the end of the last point's interval, which is
+// taken to be as long as the shortest gap in the series.
+fn upsample(points: &BTreeMap<i64, Option<f64>>, step: i64, interpolate: bool) -> Vec<SmardDataPoint> {
+    let timestamps: Vec<i64> = points.keys().copied().collect();
+    let source_step = timestamps.windows(2).map(|w| w[1] - w[0]).min().unwrap_or(step);
+    let (Some(&first), Some(&last)) = (timestamps.first(), timestamps.last()) else {
+        return Vec::new();
+    };
+
+    let mut grid = Vec::new();
+    let mut t = floor_to(first, step);
+    while t < last + source_step {
+        let before = points.range(..=t).next_back();
+        let after = points.range(t + 1..).next();
+        let value = match (before, after) {
+            // Before the first point, which only the partial first grid interval can be.
+            (None, _) => None,
+            (Some((&t0, &v0)), Some((&t1, &v1))) if interpolate && t0 != t => match (v0, v1) {
+                (Some(v0), Some(v1)) => Some(v0 + (v1 - v0) * (t - t0) as f64 / (t1 - t0) as f64),
+                _ => None,
+            },
+            (Some((_, &value)), _) => value,
+        };
+        grid.push(SmardDataPoint { timestamp: t, value });
+        t += step;
+    }
+    grid
+}
+
+/// `points` on a grid of `step_ms`, in timestamp order. Duplicate timestamps keep the last
+/// value.
+pub fn resample(points: &[SmardDataPoint], step_ms: i64, method: ResampleMethod) -> Result<Vec<SmardDataPoint>, CollectorError> {
+    if step_ms <= 0 {
+        return Err(CollectorError::InvalidInput(format!("resample step must be positive, got {} ms", step_ms)));
+    }
+    let sorted: BTreeMap<i64, Option<f64>> = points.iter().map(|dp| (dp.timestamp, dp.value)).collect();
+    Ok(match method {
+        ResampleMethod::ForwardFill => upsample(&sorted, step_ms, false),
+        ResampleMethod::Interpolate => upsample(&sorted, step_ms, true),
+        ResampleMethod::Mean => downsample(&sorted, step_ms, false),
+        ResampleMethod::Sum => downsample(&sorted, step_ms, true),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: i64 = 3_600_000;
+    const QUARTER: i64 = 900_000;
+    const T0: i64 = 1_717_200_000_000; // 2024-06-01T00:00:00Z
+
+    fn series(step: i64, values: &[Option<f64>]) -> Vec<SmardDataPoint> {
+        values.iter().enumerate().map(|(i, &value)| SmardDataPoint { timestamp: T0 + i as i64 * step, value }).collect()
+    }
+
+    fn values(points: &[SmardDataPoint]) -> Vec<Option<f64>> {
+        points.iter().map(|dp| dp.value).collect()
+    }
+
+    #[test]
+    fn forward_fill_repeats_each_hour() {
+        let quarters = resample(&series(HOUR, &[Some(80.0), Some(100.0)]), QUARTER, ResampleMethod::ForwardFill).unwrap();
+        assert_eq!(quarters.len(), 8);
+        assert_eq!(quarters[7].timestamp, T0 + 7 * QUARTER);
+        assert_eq!(values(&quarters), [Some(80.0); 4].into_iter().chain([Some(100.0); 4]).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn interpolation_is_linear_and_holds_the_last_hour() {
+        let quarters = resample(&series(HOUR, &[Some(80.0), Some(100.0)]), QUARTER, ResampleMethod::Interpolate).unwrap();
+        assert_eq!(values(&quarters), [80.0, 85.0, 90.0, 95.0, 100.0, 100.0, 100.0, 100.0].map(Some));
+        // A gap can't be interpolated across.
+        let gapped = resample(&series(HOUR, &[Some(80.0), None]), QUARTER, ResampleMethod::Interpolate).unwrap();
+        assert_eq!(values(&gapped)[1], None);
+    }
+
+    #[test]
+    fn downsampling_means_or_sums_each_hour() {
+        let quarters = series(QUARTER, &[Some(1.0), Some(2.0), Some(3.0), Some(6.0), None, Some(4.0), None, None]);
+        let mean = resample(&quarters, HOUR, ResampleMethod::Mean).unwrap();
+        assert_eq!(mean.iter().map(|dp| dp.timestamp).collect::<Vec<_>>(), [T0, T0 + HOUR]);
+        assert_eq!(values(&mean), [Some(3.0), Some(4.0)]);
+        let sum = resample(&quarters, HOUR, ResampleMethod::Sum).unwrap();
+        assert_eq!(values(&sum), [Some(12.0), Some(4.0)]);
+        let unpriced = resample(&series(QUARTER, &[None, None]), HOUR, ResampleMethod::Mean).unwrap();
+        assert_eq!(values(&unpriced), [None]);
+    }
+
+    #[test]
+    fn rejects_a_non_positive_step() {
+        assert!(matches!(resample(&[], 0, ResampleMethod::Mean), Err(CollectorError::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,131 @@
+// src/rust_data_collector/src/rolling.rs
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::merge::HourlyPoint;
+use crate::output::to_json;
+use crate::storage::write_atomic;
+use crate::timestamp::hour_start;
+use crate::CollectorError;
+
+// --- Rolling Window File ---
+
+// One file that always holds the recent past and nothing older: each run merges the
+// freshly aligned hours into it and drops every hour that started more than
+// `window_hours` ago. Hours still ahead stay in as forecast and become the record for that
+// hour once it has passed, so runs need not be hourly to keep the window gapless.
+
+pub const ROLLING_FILE: &str = "rolling_window.json";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RollingWindow {
+    pub window_hours: u32,
+    pub updated_at: i64, // Milliseconds since epoch
+    pub hourly: Vec<HourlyPoint>,
+}
+
+/// `existing` and `fresh` combined, one point per hour in timestamp order, without hours
+/// that started before `now` minus `window_hours`. A fresh point replaces the stored one,
+/// except that a missing price never overwrites a known one.
+pub fn merge_window(existing: Vec<HourlyPoint>, fresh: Vec<HourlyPoint>, window_hours: u32, now: DateTime<Utc>) -> Vec<HourlyPoint> {
+    let mut hours: BTreeMap<i64, HourlyPoint> = BTreeMap::new();
+    for point in existing.into_iter().chain(fresh) {
+        let key = hour_start(point.timestamp);
+        let price = point.price.or_else(|| hours.get(&key).and_then(|old| old.price));
+        hours.insert(key, HourlyPoint { timestamp: key, price, ..point });
+    }
+    let cutoff = hour_start(now.timestamp()) - (i64::from(window_hours) - 1) * 3600;
+    hours.split_off(&cutoff).into_values().collect()
+}
+
+// A missing file starts a new window. One that isn't a rolling window (a crash mid-edit,
+// someone else's file) is moved aside to `<path>.corrupt` before a new window replaces it,
+// so its contents aren't lost; any other read error fails the run.
+fn read_window(path: &Path) -> Result<Vec<HourlyPoint>, CollectorError> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    match serde_json::from_str::<RollingWindow>(&content) {
+        Ok(window) => Ok(window.hourly),
+        Err(e) => {
+            let mut aside = path.as_os_str().to_owned();
+            aside.push(".corrupt");
+            fs::rename(path, &aside)?;
+            log!("WARNING (Rust): Starting a new rolling window, {:?} could not be read ({}) and was moved to {:?}.", path, e, aside);
+            Ok(Vec::new())
+        }
+    }
+}
+
+/// Read-merge-trim-write on the file at `path`; the write replaces the file atomically.
+/// Returns what the file now holds.
+pub fn update_window(
+    path: &Path,
+    fresh: Vec<HourlyPoint>,
+    window_hours: u32,
+    now: DateTime<Utc>,
+    pretty: bool,
+) -> Result<RollingWindow, CollectorError> {
+    if window_hours == 0 {
+        return Err(CollectorError::InvalidInput("window_hours must be at least 1".to_string()));
+    }
+    let hourly = merge_window(read_window(path)?, fresh, window_hours, now);
+    let window = RollingWindow { window_hours, updated_at: now.timestamp_millis(), hourly };
+    write_atomic(path, &to_json(&window, pretty)?)?;
+    Ok(window)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hour(timestamp: i64, price: Option<f64>) -> HourlyPoint {
+        HourlyPoint {
+            timestamp,
+            temp: 5.0,
+            clouds: 40,
+            pop: 0.0,
+            rain_1h: None,
+            snow_1h: None,
+            pressure: None,
+            dew_point: None,
+            visibility: None,
+            description: "scattered clouds".to_string(),
+            ghi: None,
+            price,
+        }
+    }
+
+    #[test]
+    fn unreadable_window_is_moved_aside() {
+        let dir = std::env::temp_dir().join(format!("rolling_corrupt_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(ROLLING_FILE);
+        fs::write(&path, "{\"window_hours\": 24, \"hourly\": [").unwrap();
+
+        let now = DateTime::from_timestamp(7200, 0).unwrap();
+        let window = update_window(&path, vec![hour(3600, Some(80.0))], 24, now, false).unwrap();
+        assert_eq!(window.hourly.len(), 1);
+        assert_eq!(fs::read_to_string(dir.join("rolling_window.json.corrupt")).unwrap(), "{\"window_hours\": 24, \"hourly\": [");
+        let saved: RollingWindow = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.hourly[0].price, Some(80.0));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn other_read_errors_fail_the_update() {
+        // A directory where the file should be can't be read as one.
+        let dir = std::env::temp_dir().join(format!("rolling_unreadable_{}", std::process::id()));
+        fs::create_dir_all(dir.join(ROLLING_FILE)).unwrap();
+        let now = DateTime::from_timestamp(7200, 0).unwrap();
+        assert!(matches!(update_window(&dir.join(ROLLING_FILE), Vec::new(), 24, now, false), Err(CollectorError::Io(_))));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
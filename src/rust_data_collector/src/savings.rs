@@ -0,0 +1,264 @@
+// src/rust_data_collector/src/savings.rs
+
+use pyo3::prelude::*;
+use std::collections::HashMap;
+
+use crate::timestamp::hour_start;
+use crate::CollectorError;
+
+// --- Self-Consumption Savings ---
+
+// Energy balance and cost over the hours where PV, load and price are all known. Each
+// hour, PV covers the load first; any surplus is exported at the feed-in tariff and any
+// shortfall is imported at the spot price.
+#[pyclass(get_all)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SavingsReport {
+    pub self_consumed_kwh: f64,
+    pub exported_kwh: f64,
+    pub imported_kwh: f64,
+    pub net_cost: f64, // EUR: import cost minus export revenue
+    pub savings: f64,  // EUR: cost of buying the whole load at spot price, minus net_cost
+    pub matched_hours: usize,
+}
+
+#[pymethods]
+impl SavingsReport {
+    fn __repr__(&self) -> String {
+        format!(
+            "SavingsReport(self_consumed_kwh={:.3}, exported_kwh={:.3}, imported_kwh={:.3}, net_cost={:.2}, savings={:.2}, matched_hours={})",
+            self.self_consumed_kwh, self.exported_kwh, self.imported_kwh, self.net_cost, self.savings, self.matched_hours
+        )
+    }
+}
+
+/// `pv_kw` and `load_kw` are hourly `(unix_seconds, kW)` averages, so each hour's kW is
+/// also its kWh. `prices` are SMARD `(timestamp_ms, EUR/MWh)` points and `feed_in_tariff`
+/// is EUR/kWh. Hours missing from any series are left out.
+pub fn estimate_savings(
+    pv_kw: &[(i64, f64)],
+    load_kw: &[(i64, f64)],
+    prices: &[(i64, Option<f64>)],
+    feed_in_tariff: f64,
+) -> SavingsReport {
+    let load_by_hour: HashMap<i64, f64> = load_kw.iter().map(|&(ts, kw)| (hour_start(ts), kw)).collect();
+    let price_by_hour: HashMap<i64, f64> = prices.iter()
+        .filter_map(|&(ts_ms, price)| Some((hour_start(ts_ms), price? / 1000.0))) // EUR/kWh
+        .collect();
+
+    let mut report = SavingsReport::default();
+    for &(ts, pv) in pv_kw {
+        let hour = hour_start(ts);
+        let (Some(&load), Some(&price)) = (load_by_hour.get(&hour), price_by_hour.get(&hour)) else {
+            continue;
+        };
+        let (pv, load) = (pv.max(0.0), load.max(0.0));
+        let self_consumed = pv.min(load);
+        let exported = pv - self_consumed;
+        let imported = load - self_consumed;
+        let net_cost = imported * price - exported * feed_in_tariff;
+
+        report.self_consumed_kwh += self_consumed;
+        report.exported_kwh += exported;
+        report.imported_kwh += imported;
+        report.net_cost += net_cost;
+        report.savings += load * price - net_cost;
+        report.matched_hours += 1;
+    }
+    report
+}
+
+// --- Load Shifting ---
+
+// What moving a fixed amount of energy from one hour to another would change, e.g.
+// running the dishwasher at 03:00 instead of 19:00. The load is assumed to fit in the hour.
+#[pyclass(get_all)]
+#[derive(Debug, Clone, Copy)]
+pub struct LoadShift {
+    pub old_cost: f64, // EUR in the original hour
+    pub new_cost: f64, // EUR in the target hour
+    pub savings: f64,  // EUR: old_cost - new_cost, negative if the move costs more
+}
+
+#[pymethods]
+impl LoadShift {
+    fn __repr__(&self) -> String {
+        format!("LoadShift(old_cost={:.4}, new_cost={:.4}, savings={:.4})", self.old_cost, self.new_cost, self.savings)
+    }
+}
+
+fn price_per_kwh_at(prices: &[(i64, Option<f64>)], ts: i64) -> Option<f64> {
+    let hour = hour_start(ts);
+    prices.iter()
+        .find(|&&(ts_ms, _)| hour_start(ts_ms) == hour)
+        .and_then(|&(_, price)| price)
+        .map(|price| price / 1000.0) // EUR/kWh
+}
+
+/// Cost of `load_kwh` in the hour containing `from_ts` against the hour containing `to_ts`
+/// (seconds or milliseconds), at SMARD `(timestamp_ms, EUR/MWh)` prices. Fails if either
+/// hour has no price.
+pub fn simulate_load_shift(
+    prices: &[(i64, Option<f64>)],
+    load_kwh: f64,
+    from_ts: i64,
+    to_ts: i64,
+) -> Result<LoadShift, CollectorError> {
+    if !load_kwh.is_finite() || load_kwh < 0.0 {
+        return Err(CollectorError::InvalidInput(format!("load_kwh must be a non-negative number, got {}", load_kwh)));
+    }
+    let price = |ts: i64| {
+        price_per_kwh_at(prices, ts)
+            .ok_or_else(|| CollectorError::InvalidInput(format!("no price for the hour starting at {} (unix seconds)", hour_start(ts))))
+    };
+    let old_cost = load_kwh * price(from_ts)?;
+    let new_cost = load_kwh * price(to_ts)?;
+    Ok(LoadShift { old_cost, new_cost, savings: old_cost - new_cost })
+}
+
+// --- Dynamic vs. Flat Tariff ---
+
+// What a load profile costs at spot prices against a fixed rate. The spot side is the
+// bare exchange price, so for a fair comparison `flat_rate` should likewise leave out the
+// grid fees, levies and taxes that both contracts pay.
+#[pyclass(get_all)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TariffComparison {
+    pub dynamic_cost: f64,        // EUR at spot prices
+    pub flat_cost: f64,           // EUR at the flat rate
+    pub savings: f64,             // EUR: flat_cost - dynamic_cost, negative if spot costs more
+    pub savings_pct: Option<f64>, // Of flat_cost; None when that is zero
+    pub matched_hours: usize,
+    // Load in hours without a price, left out of both costs.
+    pub unmatched_hours: usize,
+    pub unmatched_kwh: f64,
+}
+
+#[pymethods]
+impl TariffComparison {
+    fn __repr__(&self) -> String {
+        format!(
+            "TariffComparison(dynamic_cost={:.2}, flat_cost={:.2}, savings={:.2}, savings_pct={:?}, matched_hours={}, unmatched_hours={}, unmatched_kwh={:.3})",
+            self.dynamic_cost, self.flat_cost, self.savings, self.savings_pct, self.matched_hours, self.unmatched_hours, self.unmatched_kwh
+        )
+    }
+}
+
+/// `load_kwh` is an hourly `(unix_seconds, kWh)` profile, `prices` SMARD
+/// `(timestamp_ms, EUR/MWh)` points and `flat_rate` EUR/kWh. Only hours with a price count,
+/// on both sides, so the two costs cover the same consumption; the rest is reported as
+/// `unmatched_hours`/`unmatched_kwh`.
+pub fn compare_to_flat_tariff(
+    prices: &[(i64, Option<f64>)],
+    load_kwh: &[(i64, f64)],
+    flat_rate: f64,
+) -> Result<TariffComparison, CollectorError> {
+    if !flat_rate.is_finite() || flat_rate < 0.0 {
+        return Err(CollectorError::InvalidInput(format!("flat_rate must be a non-negative number, got {}", flat_rate)));
+    }
+    let price_by_hour: HashMap<i64, f64> = prices.iter()
+        .filter_map(|&(ts_ms, price)| Some((hour_start(ts_ms), price? / 1000.0))) // EUR/kWh
+        .collect();
+
+    let mut report = TariffComparison::default();
+    for &(ts, load) in load_kwh {
+        let load = load.max(0.0);
+        let Some(&price) = price_by_hour.get(&hour_start(ts)) else {
+            report.unmatched_hours += 1;
+            report.unmatched_kwh += load;
+            continue;
+        };
+        report.dynamic_cost += load * price;
+        report.flat_cost += load * flat_rate;
+        report.matched_hours += 1;
+    }
+    report.savings = report.flat_cost - report.dynamic_cost;
+    report.savings_pct = (report.flat_cost > 0.0).then(|| report.savings / report.flat_cost * 100.0);
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: i64 = 1_717_200_000; // 2024-06-01T00:00:00Z
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn savings_match_a_hand_worked_example() {
+        // Hour 1: 3 kW PV, 1 kW load at 100 EUR/MWh -> 1 self-consumed, 2 exported.
+        // Hour 2: 0.5 kW PV, 2 kW load at 200 EUR/MWh -> 0.5 self-consumed, 1.5 imported.
+        // Hour 3 has no price and is left out.
+        let pv = [(HOUR, 3.0), (HOUR + 3600, 0.5), (HOUR + 7200, 1.0)];
+        let load = [(HOUR, 1.0), (HOUR + 3600, 2.0), (HOUR + 7200, 1.0)];
+        let prices = [(HOUR * 1000, Some(100.0)), ((HOUR + 3600) * 1000, Some(200.0)), ((HOUR + 7200) * 1000, None)];
+        let report = estimate_savings(&pv, &load, &prices, 0.08);
+
+        assert_eq!(report.matched_hours, 2);
+        assert_close(report.self_consumed_kwh, 1.5);
+        assert_close(report.exported_kwh, 2.0);
+        assert_close(report.imported_kwh, 1.5);
+        // 1.5 kWh * 0.20 - 2 kWh * 0.08
+        assert_close(report.net_cost, 0.14);
+        // Whole load at spot: 1 * 0.10 + 2 * 0.20 = 0.50
+        assert_close(report.savings, 0.36);
+    }
+
+    #[test]
+    fn load_shift_compares_the_two_hours() {
+        // 19:00 at 250 EUR/MWh, 03:00 at 60 EUR/MWh; timestamps in seconds or milliseconds.
+        let prices = [(HOUR * 1000, Some(60.0)), ((HOUR + 16 * 3600) * 1000, Some(250.0))];
+        let shift = simulate_load_shift(&prices, 1.5, (HOUR + 16 * 3600 + 900) * 1000, HOUR + 1800).unwrap();
+        assert_close(shift.old_cost, 0.375);
+        assert_close(shift.new_cost, 0.09);
+        assert_close(shift.savings, 0.285);
+        let back = simulate_load_shift(&prices, 1.5, HOUR, HOUR + 16 * 3600).unwrap();
+        assert_close(back.savings, -0.285);
+    }
+
+    #[test]
+    fn load_shift_needs_both_prices_and_a_valid_load() {
+        let prices = [(HOUR * 1000, Some(60.0)), ((HOUR + 3600) * 1000, None)];
+        assert!(matches!(simulate_load_shift(&prices, 1.0, HOUR, HOUR + 3600), Err(CollectorError::InvalidInput(_))));
+        assert!(matches!(simulate_load_shift(&prices, -1.0, HOUR, HOUR), Err(CollectorError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn flat_tariff_comparison_matches_hand_calculation() {
+        // 2 kWh at 50 EUR/MWh and 1 kWh at 300 EUR/MWh against 0.15 EUR/kWh flat.
+        let prices = [(HOUR * 1000, Some(50.0)), ((HOUR + 3600) * 1000, Some(300.0))];
+        let load = [(HOUR, 2.0), (HOUR + 3600, 1.0)];
+        let report = compare_to_flat_tariff(&prices, &load, 0.15).unwrap();
+        assert_eq!(report.matched_hours, 2);
+        assert_close(report.dynamic_cost, 0.4);
+        assert_close(report.flat_cost, 0.45);
+        assert_close(report.savings, 0.05);
+        assert_close(report.savings_pct.unwrap(), 0.05 / 0.45 * 100.0);
+        assert_eq!((report.unmatched_hours, report.unmatched_kwh), (0, 0.0));
+    }
+
+    #[test]
+    fn unpriced_load_is_reported_not_costed() {
+        // The second hour is unpublished, the third outside the price series.
+        let prices = [(HOUR * 1000, Some(100.0)), ((HOUR + 3600) * 1000, None)];
+        let load = [(HOUR + 900, 1.0), (HOUR + 3600, 2.0), (HOUR + 7200, 0.5)];
+        let report = compare_to_flat_tariff(&prices, &load, 0.2).unwrap();
+        assert_eq!(report.matched_hours, 1);
+        assert_close(report.dynamic_cost, 0.1);
+        assert_close(report.flat_cost, 0.2);
+        assert_eq!(report.unmatched_hours, 2);
+        assert_close(report.unmatched_kwh, 2.5);
+    }
+
+    #[test]
+    fn flat_tariff_comparison_edge_cases() {
+        let report = compare_to_flat_tariff(&[(HOUR * 1000, Some(100.0))], &[(HOUR, 1.0)], 0.0).unwrap();
+        assert_eq!(report.savings_pct, None);
+        assert!(matches!(compare_to_flat_tariff(&[], &[], -0.1), Err(CollectorError::InvalidInput(_))));
+        assert!(compare_to_flat_tariff(&[], &[], f64::NAN).is_err());
+    }
+
+}
@@ -0,0 +1,46 @@
+// src/rust_data_collector/src/secrets.rs
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use crate::CollectorError;
+
+// --- API Keys / Tokens ---
+
+// Secrets can come from a file (Docker/Kubernetes secrets are usually mounted as files)
+// given explicitly or via SECRETS_FILE, falling back to the environment / .env. The file
+// is a flat JSON object or TOML table of strings, e.g. `OPENWEATHER_API_KEY = "..."`.
+// Secret values and file contents are never printed.
+
+pub const SECRETS_FILE_VAR: &str = "SECRETS_FILE";
+
+fn read_secrets_file(path: &str) -> Result<HashMap<String, String>, CollectorError> {
+    let content = fs::read_to_string(path)?;
+    let is_toml = Path::new(path).extension().is_some_and(|ext| ext == "toml");
+    // Deliberately no content in these errors: a parse error message can echo the value.
+    if is_toml {
+        toml::from_str(&content)
+            .map_err(|_| CollectorError::InvalidInput(format!("Secrets file {} is not a flat TOML table of strings", path)))
+    } else {
+        serde_json::from_str(&content)
+            .map_err(|_| CollectorError::InvalidInput(format!("Secrets file {} is not a flat JSON object of strings", path)))
+    }
+}
+
+/// Looks `name` up in `secrets_file` (or the file named by `SECRETS_FILE`), then in the
+/// environment. A secrets file that was asked for but can't be read is an error rather
+/// than a silent fallback.
+pub fn get_secret(name: &str, secrets_file: Option<&str>) -> Result<String, CollectorError> {
+    dotenv::dotenv().ok(); // Load .env file
+
+    let file = secrets_file.map(str::to_string).or_else(|| env::var(SECRETS_FILE_VAR).ok());
+    if let Some(path) = file {
+        if let Some(value) = read_secrets_file(&path)?.remove(name) {
+            log!("DEBUG (Rust): {} loaded from secrets file {}.", name, path);
+            return Ok(value);
+        }
+    }
+    env::var(name).map_err(|_| CollectorError::MissingApiKey(name.to_string()))
+}
@@ -0,0 +1,273 @@
+// src/rust_data_collector/src/smard.rs
+
+use serde::de::{self, DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use std::fmt;
+use std::io::Read;
+
+use crate::timestamp::to_unix_millis;
+use crate::{http, CollectorError, SmardApiResponse, SmardDataPoint};
+
+// --- Streaming SMARD Parsing ---
+
+// Deserializes `{"data":[...]}` while dropping points outside the (inclusive) millisecond
+// window as they are read. Timestamps in seconds are converted to milliseconds first. For
+// multi-MB historical bodies this means neither the raw text nor the full unfiltered series
+// is ever held in memory.
+#[derive(Clone, Copy)]
+struct WindowedResponse {
+    start_timestamp_ms: i64,
+    end_timestamp_ms: i64,
+}
+
+impl<'de> DeserializeSeed<'de> for WindowedResponse {
+    type Value = Vec<SmardDataPoint>;
+
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for WindowedResponse {
+    type Value = Vec<SmardDataPoint>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a SMARD response object with a `data` array")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut data = None;
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "data" {
+                data = Some(map.next_value_seed(WindowedPoints(self))?);
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        data.ok_or_else(|| de::Error::missing_field("data"))
+    }
+}
+
+struct WindowedPoints(WindowedResponse);
+
+impl<'de> DeserializeSeed<'de> for WindowedPoints {
+    type Value = Vec<SmardDataPoint>;
+
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de> Visitor<'de> for WindowedPoints {
+    type Value = Vec<SmardDataPoint>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an array of SMARD data points")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let WindowedResponse { start_timestamp_ms, end_timestamp_ms } = self.0;
+        let mut points = Vec::new();
+        while let Some(mut dp) = seq.next_element::<SmardDataPoint>()? {
+            // `index_*.json` counts in milliseconds, but not every SMARD source does.
+            dp.timestamp = to_unix_millis(dp.timestamp);
+            if dp.timestamp >= start_timestamp_ms && dp.timestamp <= end_timestamp_ms {
+                points.push(dp);
+            }
+        }
+        Ok(points)
+    }
+}
+
+/// Streams a SMARD `index_*.json` body from `reader`, keeping only points inside the window.
+/// The body isn't retained, so a parse error carries no raw response text. Read failures
+/// (including a `CappedReader` hitting its limit) come back as I/O errors, not parse errors.
+pub fn parse_smard_reader<R: Read>(
+    reader: R,
+    start_timestamp_ms: i64,
+    end_timestamp_ms: i64,
+) -> Result<SmardApiResponse, CollectorError> {
+    let seed = WindowedResponse { start_timestamp_ms, end_timestamp_ms };
+    let mut deserializer = serde_json::Deserializer::from_reader(reader);
+    let data = seed.deserialize(&mut deserializer)
+        .and_then(|data| deserializer.end().map(|_| data))
+        .map_err(|e| {
+            if e.is_io() {
+                http::from_io_error(e.into())
+            } else {
+                CollectorError::Parse { source: e, body: String::new() }
+            }
+        })?;
+    Ok(SmardApiResponse { data, source_unit: None, config_hash: None })
+}
+
+/// Same windowed parse over an in-memory body (fixtures, benchmarks, cached responses).
+pub fn parse_smard_response(
+    body: &str,
+    start_timestamp_ms: i64,
+    end_timestamp_ms: i64,
+) -> Result<SmardApiResponse, CollectorError> {
+    let seed = WindowedResponse { start_timestamp_ms, end_timestamp_ms };
+    let mut deserializer = serde_json::Deserializer::from_str(body);
+    let data = seed.deserialize(&mut deserializer)
+        .and_then(|data| deserializer.end().map(|_| data))
+        .map_err(|e| CollectorError::Parse { source: e, body: body.to_string() })?;
+    Ok(SmardApiResponse { data, source_unit: None, config_hash: None })
+}
+
+// --- Series Kinds ---
+
+// What a SMARD filter measures. Prices and quantities share the same response shape, so
+// nothing stops a generation filter from flowing into the price statistics except this.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmardSeriesKind {
+    Price,
+    Generation,
+    Load,
+}
+
+// Filter ids from smard.de's chart_data API, grouped by what they measure.
+const PRICE_FILTERS: &[&str] = &[
+    "1001", // Our default `SMARD_PRICE_FILTER`
+    "4169", "5078", "4996", "4997", "4170", "252", "253", "254", "255", "256", "257", "258", "259", "260",
+    "261", "262", "3791",
+];
+const GENERATION_FILTERS: &[&str] = &[
+    "1223", "1224", "1225", "1226", "1227", "1228", "4066", "4067", "4068", "4069", "4070", "4071",
+];
+const LOAD_FILTERS: &[&str] = &["410", "4359", "4387"];
+
+// The commonly used filters with a readable label, e.g. for a dropdown. Every id here is
+// also in one of the lists above.
+const FILTER_LABELS: &[(&str, &str)] = &[
+    ("1001", "Day-ahead auction price (default)"),
+    ("4169", "Day-ahead price: Germany/Luxembourg"),
+    ("5078", "Day-ahead price: DE/LU neighbours"),
+    ("4170", "Day-ahead price: Austria"),
+    ("4996", "Day-ahead price: Belgium"),
+    ("261", "Day-ahead price: Czech Republic"),
+    ("252", "Day-ahead price: Denmark 1"),
+    ("253", "Day-ahead price: Denmark 2"),
+    ("254", "Day-ahead price: France"),
+    ("262", "Day-ahead price: Hungary"),
+    ("255", "Day-ahead price: Italy (North)"),
+    ("256", "Day-ahead price: Netherlands"),
+    ("4997", "Day-ahead price: Norway 2"),
+    ("257", "Day-ahead price: Poland"),
+    ("260", "Day-ahead price: Slovenia"),
+    ("259", "Day-ahead price: Switzerland"),
+    ("4066", "Generation: Biomass"),
+    ("4069", "Generation: Hard coal"),
+    ("1226", "Generation: Hydropower"),
+    ("1223", "Generation: Lignite"),
+    ("4071", "Generation: Natural gas"),
+    ("1224", "Generation: Nuclear"),
+    ("1227", "Generation: Other conventional"),
+    ("1228", "Generation: Other renewables"),
+    ("4068", "Generation: Photovoltaics"),
+    ("4070", "Generation: Pumped storage"),
+    ("1225", "Generation: Wind offshore"),
+    ("4067", "Generation: Wind onshore"),
+    ("410", "Consumption: Total load"),
+    ("4359", "Consumption: Residual load"),
+    ("4387", "Consumption: Pumped storage"),
+];
+
+/// `(filter id, label)` for the commonly used SMARD filters, prices first.
+pub fn list_smard_filters() -> Vec<(&'static str, &'static str)> {
+    FILTER_LABELS.to_vec()
+}
+
+/// Readable label of a filter id, `None` if it isn't one of the common ones.
+pub fn filter_label(filter: &str) -> Option<&'static str> {
+    FILTER_LABELS.iter().find(|(id, _)| *id == filter).map(|(_, label)| *label)
+}
+
+impl SmardSeriesKind {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "price" => Some(SmardSeriesKind::Price),
+            "generation" => Some(SmardSeriesKind::Generation),
+            "load" => Some(SmardSeriesKind::Load),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SmardSeriesKind::Price => "price",
+            SmardSeriesKind::Generation => "generation",
+            SmardSeriesKind::Load => "load",
+        }
+    }
+
+    /// Unit of the values: EUR/MWh for prices, energy per period for quantities.
+    pub fn unit(self) -> &'static str {
+        match self {
+            SmardSeriesKind::Price => "EUR/MWh",
+            SmardSeriesKind::Generation | SmardSeriesKind::Load => "MWh",
+        }
+    }
+
+    /// The kind of a known filter id, `None` for filters not listed here.
+    pub fn of_filter(filter: &str) -> Option<Self> {
+        if PRICE_FILTERS.contains(&filter) {
+            Some(SmardSeriesKind::Price)
+        } else if GENERATION_FILTERS.contains(&filter) {
+            Some(SmardSeriesKind::Generation)
+        } else if LOAD_FILTERS.contains(&filter) {
+            Some(SmardSeriesKind::Load)
+        } else {
+            None
+        }
+    }
+
+    /// Rejects a known filter that measures something else. Unknown filters pass with a
+    /// warning, since SMARD adds series over time.
+    pub fn validate_filter(self, filter: &str) -> Result<(), CollectorError> {
+        match SmardSeriesKind::of_filter(filter) {
+            Some(actual) if actual != self => Err(CollectorError::InvalidInput(format!(
+                "SMARD filter {}{} is a {} series ({}), not {} ({})",
+                filter,
+                filter_label(filter).map(|label| format!(" ({})", label)).unwrap_or_default(),
+                actual.as_str(), actual.unit(), self.as_str(), self.unit()
+            ))),
+            Some(_) => Ok(()),
+            None => {
+                log!(
+                    "WARNING (Rust): Unknown SMARD filter {}; assuming it is a {} series as declared.",
+                    filter, self.as_str()
+                );
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START_MS: i64 = 1_717_200_000_000; // 2024-06-01T00:00:00Z
+    const END_MS: i64 = START_MS + 2 * 3_600_000;
+
+    #[test]
+    fn seconds_timestamps_are_read_as_milliseconds() {
+        let seconds = r#"{"data": [
+            {"timestamp": 1717196400, "value": 70.0},
+            {"timestamp": 1717200000, "value": 81.5},
+            {"timestamp": 1717203600, "value": null},
+            {"timestamp": 1717210800, "value": 90.0}
+        ]}"#;
+        let millis = r#"{"data": [
+            {"timestamp": 1717196400000, "value": 70.0},
+            {"timestamp": 1717200000000, "value": 81.5},
+            {"timestamp": 1717203600000, "value": null},
+            {"timestamp": 1717210800000, "value": 90.0}
+        ]}"#;
+        let from_seconds = parse_smard_response(seconds, START_MS, END_MS).unwrap();
+        let from_millis = parse_smard_reader(millis.as_bytes(), START_MS, END_MS).unwrap();
+        let points = |r: &SmardApiResponse| r.data.iter().map(|dp| (dp.timestamp, dp.value)).collect::<Vec<_>>();
+        assert_eq!(points(&from_seconds), [(START_MS, Some(81.5)), (START_MS + 3_600_000, None)]);
+        assert_eq!(points(&from_seconds), points(&from_millis));
+    }
+}
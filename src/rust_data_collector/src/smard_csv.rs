@@ -0,0 +1,133 @@
+// src/rust_data_collector/src/smard_csv.rs
+
+use chrono::{LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use chrono_tz::Europe::Berlin;
+use std::collections::BTreeMap;
+
+use crate::{circuit, http, validate_time_range, CollectorError, SmardApiResponse, SmardDataPoint};
+
+// --- SMARD CSV Download ---
+
+// SMARD's download manager exports any range in one request, which makes it the most
+// reliable route to deep history when the chart_data segments have gaps. The export is
+// meant for spreadsheets: semicolon-separated, German number format ("1.234,56", "-" for
+// no value) and German local times, e.g.
+//
+//   Datum von;Datum bis;Deutschland/Luxemburg [€/MWh] Originalauflösungen
+//   01.01.2024 00:00;01.01.2024 01:00;39,91
+//
+// Older exports split the columns as `Datum;Anfang;Ende;...` instead; both are read.
+// Only the first value column is kept.
+
+const DOWNLOAD_URL: &str = "https://www.smard.de/nip-download-manager/nip/download/market-data";
+
+// Day-ahead auction price for the DE-LU bidding zone.
+pub const DEFAULT_PRICE_MODULE_ID: u64 = 8004169;
+
+/// "1.234,56" -> 1234.56. `None` for SMARD's "-" and anything else that isn't a number.
+pub fn parse_german_number(raw: &str) -> Option<f64> {
+    let raw = raw.trim();
+    if raw.is_empty() || raw == "-" {
+        return None;
+    }
+    raw.replace('.', "").replace(',', ".").parse().ok()
+}
+
+fn parse_german_date(raw: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(raw.trim(), "%d.%m.%Y").ok()
+}
+
+// Start of the row as Berlin local time, and the index of the first value column.
+fn row_start(fields: &[&str]) -> Option<(NaiveDateTime, usize)> {
+    let first = fields.first()?.trim();
+    if let Ok(start) = NaiveDateTime::parse_from_str(first, "%d.%m.%Y %H:%M") {
+        // `Datum von;Datum bis;value...`
+        return Some((start, 2));
+    }
+    // `Datum;Anfang;Ende;value...`
+    let date = parse_german_date(first)?;
+    let time = NaiveTime::parse_from_str(fields.get(1)?.trim(), "%H:%M").ok()?;
+    Some((date.and_time(time), 3))
+}
+
+/// Points of a download-manager CSV inside `[start_ms, end_ms]`, in timestamp order. Rows
+/// before the first dated row are headers. In the repeated hour at the end of summer time
+/// the first occurrence is CEST and the second CET, as SMARD lists them in order.
+pub fn parse_smard_csv(body: &str, start_ms: i64, end_ms: i64) -> Result<SmardApiResponse, CollectorError> {
+    let mut points: BTreeMap<i64, Option<f64>> = BTreeMap::new();
+    let mut last_ms = i64::MIN;
+    let mut seen_data = false;
+    for (i, line) in body.trim_start_matches('\u{feff}').lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(';').collect();
+        let Some((local_start, value_column)) = row_start(&fields) else {
+            if seen_data {
+                return Err(CollectorError::InvalidInput(format!(
+                    "SMARD CSV line {}: expected a German date, got {:?}", i + 1, line
+                )));
+            }
+            continue;
+        };
+        seen_data = true;
+        let timestamp = match Berlin.from_local_datetime(&local_start) {
+            LocalResult::Single(t) => t.timestamp_millis(),
+            LocalResult::Ambiguous(cest, cet) => {
+                if cest.timestamp_millis() > last_ms { cest.timestamp_millis() } else { cet.timestamp_millis() }
+            }
+            LocalResult::None => {
+                return Err(CollectorError::InvalidInput(format!(
+                    "SMARD CSV line {}: {} does not exist in German local time", i + 1, local_start
+                )))
+            }
+        };
+        last_ms = timestamp;
+        if timestamp >= start_ms && timestamp <= end_ms {
+            points.insert(timestamp, fields.get(value_column).and_then(|raw| parse_german_number(raw)));
+        }
+    }
+    Ok(SmardApiResponse {
+        data: points.into_iter().map(|(timestamp, value)| SmardDataPoint { timestamp, value }).collect(),
+        source_unit: None,
+        config_hash: None,
+    })
+}
+
+/// A SMARD series over `[start_ms, end_ms]` from the CSV export. `module_id` is the
+/// download manager's id for the series (`DEFAULT_PRICE_MODULE_ID` for prices),
+/// which is not the same number as the chart_data filter.
+pub fn get_smard_csv_download(
+    module_id: u64,
+    region: &str,
+    resolution: &str,
+    start_ms: i64,
+    end_ms: i64,
+) -> Result<SmardApiResponse, CollectorError> {
+    validate_time_range(start_ms, end_ms)?;
+
+    let request = serde_json::json!({
+        "request_form": [{
+            "format": "CSV",
+            "moduleIds": [module_id],
+            "region": region,
+            "timestamp_from": start_ms,
+            "timestamp_to": end_ms,
+            "type": "discrete",
+            "language": "de",
+            "resolution": resolution,
+        }]
+    });
+    log!("DEBUG (Rust): SMARD CSV download for module {} ({}, {}) {} .. {}", module_id, region, resolution, start_ms, end_ms);
+    let body = circuit::SMARD.call(|| {
+        let response = http::client()?.post(DOWNLOAD_URL).json(&request).send()?;
+        let status = response.status();
+        let body = http::read_body(response)?;
+        if !status.is_success() {
+            log!("ERROR (Rust): SMARD CSV download returned non-success status {}. Full raw response: {}", status, body);
+            return Err(CollectorError::Status { status: status.as_u16(), body });
+        }
+        Ok(body)
+    })?;
+    parse_smard_csv(&body, start_ms, end_ms)
+}
@@ -0,0 +1,169 @@
+// src/rust_data_collector/src/solar.rs
+
+use chrono::{DateTime, Utc};
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{http, secrets, CollectorError};
+
+// --- Normalized Solar Data ---
+
+// Provider-agnostic hourly irradiance. Fields follow Open-Meteo's radiation variables
+// (shortwave = GHI, direct normal = DNI, diffuse = DHI), all in W/m², so the PV model
+// doesn't care which provider produced them.
+#[pyclass(get_all)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IrradiancePoint {
+    pub timestamp: i64, // Unix timestamp (seconds), start of the hour
+    pub ghi: f64,
+    pub dni: f64,
+    pub dhi: f64,
+    pub pv_estimate_kw: Option<f64>, // Only for providers that model the PV system itself
+}
+
+pub trait SolarProvider {
+    fn name(&self) -> &'static str;
+    /// The provider's documented horizon, see `weather::limit_forecast_hours`.
+    fn max_forecast_hours(&self) -> u32;
+    fn fetch_irradiance(&self, lat: f64, lon: f64, hours: u32) -> Result<Vec<IrradiancePoint>, CollectorError>;
+}
+
+// --- Solcast ---
+
+const SOLCAST_BASE_URL: &str = "https://api.solcast.com.au/data/forecast";
+pub const SOLCAST_MAX_FORECAST_HOURS: u32 = 14 * 24;
+
+// Solcast reports each period by its end time; we request hourly periods.
+#[derive(Debug, Deserialize)]
+struct SolcastRadiationPeriod {
+    period_end: DateTime<Utc>,
+    ghi: f64,
+    dni: f64,
+    dhi: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct SolcastRadiationResponse {
+    forecasts: Vec<SolcastRadiationPeriod>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SolcastPvPeriod {
+    period_end: DateTime<Utc>,
+    pv_power_rooftop: f64, // kW
+}
+
+#[derive(Debug, Deserialize)]
+struct SolcastPvResponse {
+    forecasts: Vec<SolcastPvPeriod>,
+}
+
+pub struct SolcastProvider {
+    token: String,
+    // Installed peak power of the array; when set, Solcast's PV estimate is fetched too.
+    pv_capacity_kw: Option<f64>,
+}
+
+impl SolcastProvider {
+    pub fn new(token: String, pv_capacity_kw: Option<f64>) -> Self {
+        SolcastProvider { token, pv_capacity_kw }
+    }
+
+    /// Reads `SOLCAST_TOKEN` from the secrets file or the environment.
+    pub fn from_secrets(pv_capacity_kw: Option<f64>, secrets_file: Option<&str>) -> Result<Self, CollectorError> {
+        let token = secrets::get_secret("SOLCAST_TOKEN", secrets_file)?;
+        Ok(SolcastProvider::new(token, pv_capacity_kw))
+    }
+
+    fn get<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T, CollectorError> {
+        log!("DEBUG (Rust): Solcast API Request URL: {}", url);
+        let response = http::client()?.get(url).bearer_auth(&self.token).send()?;
+        let status = response.status();
+        let content_type = http::content_type(&response);
+        let body = http::read_body(response)?;
+        if !status.is_success() {
+            log!("ERROR (Rust): Solcast API returned non-success status {}. Full raw response: {}", status, body);
+            return Err(CollectorError::Status { status: status.as_u16(), body });
+        }
+        http::expect_json(content_type.as_deref(), &body)?;
+        serde_json::from_str(&body).map_err(|e| CollectorError::Parse { source: e, body })
+    }
+}
+
+// End of an hourly period -> start of that hour in seconds
+fn period_start(period_end: &DateTime<Utc>) -> i64 {
+    period_end.timestamp() - 3600
+}
+
+impl SolarProvider for SolcastProvider {
+    fn name(&self) -> &'static str {
+        "solcast"
+    }
+
+    fn max_forecast_hours(&self) -> u32 {
+        SOLCAST_MAX_FORECAST_HOURS
+    }
+
+    fn fetch_irradiance(&self, lat: f64, lon: f64, hours: u32) -> Result<Vec<IrradiancePoint>, CollectorError> {
+        let url = format!(
+            "{}/radiation_and_weather?latitude={}&longitude={}&hours={}&output_parameters=ghi,dni,dhi&period=PT60M&format=json",
+            SOLCAST_BASE_URL, lat, lon, hours
+        );
+        let radiation: SolcastRadiationResponse = self.get(&url)?;
+        let pv: Option<SolcastPvResponse> = match self.pv_capacity_kw {
+            Some(capacity) => {
+                let url = format!(
+                    "{}/rooftop_pv_power?latitude={}&longitude={}&hours={}&capacity={}&period=PT60M&format=json",
+                    SOLCAST_BASE_URL, lat, lon, hours, capacity
+                );
+                Some(self.get(&url)?)
+            }
+            None => None,
+        };
+        Ok(solcast_points(&radiation, pv.as_ref()))
+    }
+}
+
+// Solcast's periods as normalized points, with the PV estimate attached by hour.
+fn solcast_points(radiation: &SolcastRadiationResponse, pv: Option<&SolcastPvResponse>) -> Vec<IrradiancePoint> {
+    let mut points: Vec<IrradiancePoint> = radiation.forecasts.iter()
+        .map(|p| IrradiancePoint {
+            timestamp: period_start(&p.period_end),
+            ghi: p.ghi,
+            dni: p.dni,
+            dhi: p.dhi,
+            pv_estimate_kw: None,
+        })
+        .collect();
+    for period in pv.iter().flat_map(|pv| pv.forecasts.iter()) {
+        let ts = period_start(&period.period_end);
+        if let Some(point) = points.iter_mut().find(|p| p.timestamp == ts) {
+            point.pv_estimate_kw = Some(period.pv_power_rooftop);
+        }
+    }
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solcast_periods_normalize_to_hour_starts() {
+        let radiation: SolcastRadiationResponse = serde_json::from_str(r#"{"forecasts": [
+            {"ghi": 412.0, "dni": 610.0, "dhi": 98.0, "period_end": "2024-06-01T11:00:00.0000000Z", "period": "PT60M"},
+            {"ghi": 455.0, "dni": 640.0, "dhi": 101.0, "period_end": "2024-06-01T12:00:00.0000000Z", "period": "PT60M"}
+        ]}"#).unwrap();
+        let pv: SolcastPvResponse = serde_json::from_str(r#"{"forecasts": [
+            {"pv_power_rooftop": 3.2, "period_end": "2024-06-01T12:00:00.0000000Z", "period": "PT60M"}
+        ]}"#).unwrap();
+
+        let points = solcast_points(&radiation, Some(&pv));
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].timestamp, 1_717_236_000); // 2024-06-01T10:00:00Z
+        assert_eq!((points[0].ghi, points[0].dni, points[0].dhi), (412.0, 610.0, 98.0));
+        assert_eq!(points[0].pv_estimate_kw, None);
+        assert_eq!(points[1].pv_estimate_kw, Some(3.2));
+        assert!(solcast_points(&radiation, None).iter().all(|p| p.pv_estimate_kw.is_none()));
+    }
+}
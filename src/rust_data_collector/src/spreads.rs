@@ -0,0 +1,82 @@
+// src/rust_data_collector/src/spreads.rs
+
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use std::thread;
+
+use crate::{get_smard_day_ahead_prices, http, CollectorError, SMARD_BASE_URL, SMARD_PRICE_FILTER};
+
+// --- Cross-Region Price Spreads ---
+
+// Price difference between two SMARD regions in one period; `spread` is a minus b.
+#[pyclass(get_all)]
+#[derive(Debug, Clone)]
+pub struct RegionSpread {
+    pub timestamp: i64, // Milliseconds since epoch
+    pub region_a: String,
+    pub region_b: String,
+    pub region_a_price: f64,
+    pub region_b_price: f64,
+    pub spread: f64,
+}
+
+#[pymethods]
+impl RegionSpread {
+    fn __repr__(&self) -> String {
+        format!(
+            "RegionSpread(timestamp={}, {}={}, {}={}, spread={})",
+            self.timestamp, self.region_a, self.region_a_price, self.region_b, self.region_b_price, self.spread
+        )
+    }
+}
+
+/// Fetches every region concurrently and returns a row per timestamp for each region
+/// pair (in the order given), sorted by time. Only periods where both regions have a
+/// price are compared, so regions with different coverage just yield fewer rows.
+pub fn fetch_region_spreads(
+    regions: &[String],
+    resolution: &str,
+    start_ms: i64,
+    end_ms: i64,
+) -> Result<Vec<RegionSpread>, CollectorError> {
+    if regions.len() < 2 {
+        return Err(CollectorError::InvalidInput("Need at least two regions to compare".to_string()));
+    }
+
+    let deadline = http::current_deadline();
+    let fetched: Vec<Result<HashMap<i64, f64>, CollectorError>> = thread::scope(|scope| {
+        let handles: Vec<_> = regions.iter()
+            .map(|region| {
+                scope.spawn(move || {
+                    let _deadline = http::with_deadline(deadline);
+                    let response = get_smard_day_ahead_prices(SMARD_BASE_URL, SMARD_PRICE_FILTER, region, resolution, start_ms, end_ms)?;
+                    Ok(response.data.iter()
+                        .filter_map(|dp| dp.value.map(|v| (dp.timestamp, v)))
+                        .collect())
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().expect("SMARD fetch thread panicked")).collect()
+    });
+    let prices_by_region = fetched.into_iter().collect::<Result<Vec<_>, _>>()?;
+
+    let mut rows = Vec::new();
+    for a in 0..regions.len() {
+        for b in (a + 1)..regions.len() {
+            for (&timestamp, &price_a) in &prices_by_region[a] {
+                if let Some(&price_b) = prices_by_region[b].get(&timestamp) {
+                    rows.push(RegionSpread {
+                        timestamp,
+                        region_a: regions[a].clone(),
+                        region_b: regions[b].clone(),
+                        region_a_price: price_a,
+                        region_b_price: price_b,
+                        spread: price_a - price_b,
+                    });
+                }
+            }
+        }
+    }
+    rows.sort_by_key(|row| row.timestamp);
+    Ok(rows)
+}
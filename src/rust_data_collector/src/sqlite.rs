@@ -0,0 +1,90 @@
+// src/rust_data_collector/src/sqlite.rs
+
+use rusqlite::{params, Connection};
+use std::path::Path;
+
+use crate::merge::HourlyPoint;
+use crate::{CollectorError, OpenWeatherOneCallResponse, SmardApiResponse};
+
+// --- SQLite Export ---
+
+// One table per dataset, keyed by timestamp, so repeated runs into the same file build up
+// a history: a newer run's row replaces the older one for the same hour. Weather times are
+// Unix seconds, price times milliseconds, as the providers send them.
+
+fn sqlite_error(e: rusqlite::Error) -> CollectorError {
+    CollectorError::Io(std::io::Error::other(e))
+}
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS weather (
+        dt INTEGER PRIMARY KEY,
+        temp REAL NOT NULL,
+        clouds INTEGER NOT NULL,
+        pop REAL NOT NULL,
+        description TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS prices (
+        timestamp INTEGER PRIMARY KEY,
+        price REAL
+    );
+    CREATE TABLE IF NOT EXISTS hourly (
+        timestamp INTEGER PRIMARY KEY,
+        temp REAL NOT NULL,
+        clouds INTEGER NOT NULL,
+        pop REAL NOT NULL,
+        rain_1h REAL,
+        snow_1h REAL,
+        pressure INTEGER,
+        dew_point REAL,
+        visibility INTEGER,
+        ghi REAL,
+        price REAL,
+        description TEXT NOT NULL
+    );
+";
+
+/// Upserts whichever parts are given into the database at `path`, creating it and its
+/// tables if needed. Everything is written in one transaction.
+pub fn export_sqlite(
+    path: &Path,
+    weather: Option<&OpenWeatherOneCallResponse>,
+    prices: Option<&SmardApiResponse>,
+    hourly: &[HourlyPoint],
+) -> Result<(), CollectorError> {
+    let mut conn = Connection::open(path).map_err(sqlite_error)?;
+    conn.execute_batch(SCHEMA).map_err(sqlite_error)?;
+    let tx = conn.transaction().map_err(sqlite_error)?;
+    if let Some(weather) = weather {
+        let mut insert = tx
+            .prepare("INSERT OR REPLACE INTO weather (dt, temp, clouds, pop, description) VALUES (?1, ?2, ?3, ?4, ?5)")
+            .map_err(sqlite_error)?;
+        for hour in &weather.hourly {
+            let description = hour.weather.first().map(|w| w.description.as_str()).unwrap_or_default();
+            insert.execute(params![hour.dt, hour.temp, hour.clouds.all, hour.pop, description]).map_err(sqlite_error)?;
+        }
+    }
+    if let Some(prices) = prices {
+        let mut insert = tx
+            .prepare("INSERT OR REPLACE INTO prices (timestamp, price) VALUES (?1, ?2)")
+            .map_err(sqlite_error)?;
+        for dp in &prices.data {
+            insert.execute(params![dp.timestamp, dp.value]).map_err(sqlite_error)?;
+        }
+    }
+    {
+        let mut insert = tx
+            .prepare(
+                "INSERT OR REPLACE INTO hourly (timestamp, temp, clouds, pop, rain_1h, snow_1h, pressure, dew_point, visibility, ghi, price, description)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            )
+            .map_err(sqlite_error)?;
+        for p in hourly {
+            insert.execute(params![
+                p.timestamp, p.temp, p.clouds, p.pop, p.rain_1h, p.snow_1h,
+                p.pressure, p.dew_point, p.visibility, p.ghi, p.price, p.description
+            ]).map_err(sqlite_error)?;
+        }
+    }
+    tx.commit().map_err(sqlite_error)
+}
@@ -0,0 +1,96 @@
+// src/rust_data_collector/src/storage.rs
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::CollectorError;
+
+// --- Output Directory Layout ---
+
+const DATE_FOLDER_FORMAT: &str = "%Y-%m-%d";
+
+/// Directory the output files for a run go into: `data_dir` itself, or
+/// `data_dir/YYYY-MM-DD` (UTC) when rotating by date. Created if missing.
+pub fn output_dir(data_dir: &str, rotate_by_date: bool, now: DateTime<Utc>) -> Result<PathBuf, CollectorError> {
+    let dir = if rotate_by_date {
+        Path::new(data_dir).join(now.format(DATE_FOLDER_FORMAT).to_string())
+    } else {
+        PathBuf::from(data_dir)
+    };
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// Deletes date folders in `data_dir` older than `days` days and returns what was removed.
+/// Anything not named like a date folder is left alone.
+pub fn prune_older_than(data_dir: &str, days: i64, now: DateTime<Utc>) -> Result<Vec<PathBuf>, CollectorError> {
+    let cutoff = (now - Duration::days(days)).date_naive();
+    let mut removed = Vec::new();
+    for entry in fs::read_dir(data_dir)? {
+        let path = entry?.path();
+        if !path.is_dir() {
+            continue;
+        }
+        let folder_date = path.file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| NaiveDate::parse_from_str(name, DATE_FOLDER_FORMAT).ok());
+        if let Some(date) = folder_date {
+            if date < cutoff {
+                fs::remove_dir_all(&path)?;
+                log!("DEBUG (Rust): Pruned old data folder {:?}", path);
+                removed.push(path);
+            }
+        }
+    }
+    Ok(removed)
+}
+
+// --- Incremental Fetch State ---
+
+// Newest stored timestamp (ms) per series, so incremental runs know where the previous run
+// stopped. By default it lives in `data_dir` itself, not in a date folder; cron setups that
+// share or move data directories can point it elsewhere.
+const FETCH_STATE_FILE: &str = ".fetch_state.json";
+
+// Re-fetched before the last stored point, in case SMARD revised the latest hours.
+pub const INCREMENTAL_OVERLAP: Duration = Duration::hours(2);
+
+pub fn default_fetch_state_path(data_dir: &str) -> PathBuf {
+    Path::new(data_dir).join(FETCH_STATE_FILE)
+}
+
+fn read_fetch_state(state_file: &Path) -> HashMap<String, i64> {
+    fs::read_to_string(state_file).ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Last stored timestamp (ms) for `series`, or `None` on a first run or unreadable state.
+pub fn last_stored_timestamp(state_file: &Path, series: &str) -> Option<i64> {
+    read_fetch_state(state_file).get(series).copied()
+}
+
+pub fn save_last_stored_timestamp(state_file: &Path, series: &str, timestamp_ms: i64) -> Result<(), CollectorError> {
+    let mut state = read_fetch_state(state_file);
+    state.insert(series.to_string(), timestamp_ms);
+    let json = serde_json::to_string_pretty(&state)
+        .map_err(|e| CollectorError::Parse { source: e, body: String::new() })?;
+    // A run killed mid-write must not leave a truncated file that makes the next run start
+    // from scratch.
+    write_atomic(state_file, &json)
+}
+
+/// Write-then-rename, as for the call budget state: readers see the old file or the new
+/// one, never a partial write. Creates the parent directory if needed.
+pub fn write_atomic(path: &Path, contents: &str) -> Result<(), CollectorError> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
@@ -0,0 +1,186 @@
+// src/rust_data_collector/src/store.rs
+
+use chrono::Duration;
+use pyo3::prelude::*;
+use std::collections::BTreeMap;
+use std::fs;
+
+use crate::{clock, output, CollectorError, SmardApiResponse, SmardDataPoint};
+
+// --- Accumulating Series Store ---
+
+pub const DEFAULT_MAX_POINTS: usize = 24 * 365; // one year of hourly prices
+
+// Holds a growing price series across successive (overlapping) fetches. Points are keyed
+// by timestamp, so re-fetched hours update in place instead of duplicating, and the map
+// keeps everything sorted. Only this in-memory working set is capped: once `max_points`
+// is exceeded, or points are older than `max_age`, the oldest points are dropped.
+// Files written with `save` keep whatever they were given.
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct SeriesStore {
+    points: BTreeMap<i64, Option<f64>>,
+    max_points: usize,
+    max_age: Option<Duration>,
+}
+
+impl SeriesStore {
+    pub fn new(max_points: usize, max_age: Option<Duration>) -> Self {
+        SeriesStore { points: BTreeMap::new(), max_points: max_points.max(1), max_age }
+    }
+
+    /// Inserts new timestamps and updates known ones; returns how many new timestamps the
+    /// store now holds. New points evicted straight away (past `max_points` or older than
+    /// `max_age`) don't count. A null value never overwrites a value we already have.
+    pub fn merge(&mut self, new_points: &[SmardDataPoint]) -> usize {
+        let mut added = Vec::new();
+        for dp in new_points {
+            match self.points.get_mut(&dp.timestamp) {
+                Some(existing) => {
+                    if dp.value.is_some() {
+                        *existing = dp.value;
+                    }
+                }
+                None => {
+                    self.points.insert(dp.timestamp, dp.value);
+                    added.push(dp.timestamp);
+                }
+            }
+        }
+        self.evict();
+        added.iter().filter(|ts| self.points.contains_key(ts)).count()
+    }
+
+    fn evict(&mut self) {
+        while self.points.len() > self.max_points {
+            self.points.pop_first();
+        }
+        if let Some(max_age) = self.max_age {
+            let cutoff_ms = (clock::now() - max_age).timestamp_millis();
+            self.points = self.points.split_off(&cutoff_ms);
+        }
+    }
+
+    pub fn to_response(&self) -> SmardApiResponse {
+        SmardApiResponse {
+            data: self.points.iter()
+                .map(|(&timestamp, &value)| SmardDataPoint { timestamp, value })
+                .collect(),
+            source_unit: None,
+            config_hash: None,
+        }
+    }
+
+    /// Writes the store in the same format as `smard_prices.json`, pretty-printed or on one
+    /// line, to `path` or, for `"-"`, to stdout.
+    pub fn save(&self, path: &str, pretty: bool) -> Result<(), CollectorError> {
+        let target = output::OutputTarget::parse(path);
+        let _quiet = (target == output::OutputTarget::Stdout).then(output::quiet_stdout);
+        target.write(&output::to_json(&self.to_response(), pretty)?)
+    }
+
+    pub fn merge_file(&mut self, path: &str) -> Result<usize, CollectorError> {
+        let body = fs::read_to_string(path)?;
+        let response: SmardApiResponse = serde_json::from_str(&body)
+            .map_err(|e| CollectorError::Parse { source: e, body })?;
+        Ok(self.merge(&response.data))
+    }
+}
+
+#[pymethods]
+impl SeriesStore {
+    #[new]
+    #[pyo3(signature = (max_points = DEFAULT_MAX_POINTS, max_age_hours = None))]
+    fn py_new(max_points: usize, max_age_hours: Option<i64>) -> Self {
+        SeriesStore::new(max_points, max_age_hours.map(Duration::hours))
+    }
+
+    /// Merges `(timestamp_ms, value)` pairs; returns how many new timestamps were kept.
+    #[pyo3(name = "merge")]
+    fn py_merge(&mut self, points: Vec<(i64, Option<f64>)>) -> usize {
+        let points: Vec<SmardDataPoint> = points.into_iter()
+            .map(|(timestamp, value)| SmardDataPoint { timestamp, value })
+            .collect();
+        self.merge(&points)
+    }
+
+    /// Merges a saved `smard_prices.json`-style file.
+    #[pyo3(name = "merge_file")]
+    fn py_merge_file(&mut self, path: &str) -> PyResult<usize> {
+        self.merge_file(path).map_err(|e| e.into_py_err("Failed to merge series file"))
+    }
+
+    /// Compact (`pretty=False`) suits a store that is saved over and over. `path="-"`
+    /// writes to stdout, for piping into `jq`.
+    #[pyo3(name = "save", signature = (path, pretty = true))]
+    fn py_save(&self, path: &str, pretty: bool) -> PyResult<()> {
+        self.save(path, pretty).map_err(|e| e.into_py_err("Failed to save series store"))
+    }
+
+    /// All points as sorted `(timestamp_ms, value)` pairs.
+    fn points(&self) -> Vec<(i64, Option<f64>)> {
+        self.points.iter().map(|(&ts, &v)| (ts, v)).collect()
+    }
+
+    fn __len__(&self) -> usize {
+        self.points.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR_MS: i64 = 3_600_000;
+
+    fn points(values: &[(i64, Option<f64>)]) -> Vec<SmardDataPoint> {
+        values.iter().map(|&(hour, value)| SmardDataPoint { timestamp: hour * HOUR_MS, value }).collect()
+    }
+
+    fn stored(store: &SeriesStore) -> Vec<(i64, Option<f64>)> {
+        store.points.iter().map(|(&ts, &v)| (ts / HOUR_MS, v)).collect()
+    }
+
+    #[test]
+    fn overlapping_fetch_updates_in_place() {
+        let mut store = SeriesStore::new(DEFAULT_MAX_POINTS, None);
+        assert_eq!(store.merge(&points(&[(0, Some(80.0)), (1, None), (2, Some(90.0))])), 3);
+        assert_eq!(store.merge(&points(&[(1, Some(85.0)), (2, Some(95.0)), (3, Some(70.0))])), 1);
+        assert_eq!(stored(&store), vec![(0, Some(80.0)), (1, Some(85.0)), (2, Some(95.0)), (3, Some(70.0))]);
+    }
+
+    #[test]
+    fn null_does_not_overwrite_a_known_value() {
+        let mut store = SeriesStore::new(DEFAULT_MAX_POINTS, None);
+        store.merge(&points(&[(0, Some(80.0)), (1, Some(85.0))]));
+        assert_eq!(store.merge(&points(&[(0, None), (1, None)])), 0);
+        assert_eq!(stored(&store), vec![(0, Some(80.0)), (1, Some(85.0))]);
+    }
+
+    #[test]
+    fn max_points_keeps_the_newest() {
+        let mut store = SeriesStore::new(3, None);
+        store.merge(&points(&[(0, Some(1.0)), (1, Some(2.0))]));
+        assert_eq!(store.merge(&points(&[(2, Some(3.0)), (3, Some(4.0)), (4, Some(5.0))])), 3);
+        assert_eq!(stored(&store), vec![(2, Some(3.0)), (3, Some(4.0)), (4, Some(5.0))]);
+    }
+
+    #[test]
+    fn max_age_drops_points_older_than_now_minus_max_age() {
+        let _clock = clock::pin(chrono::DateTime::from_timestamp_millis(100 * HOUR_MS).unwrap());
+        let mut store = SeriesStore::new(DEFAULT_MAX_POINTS, Some(Duration::hours(24)));
+        // Hour 76 is exactly 24 hours old and stays; anything before it goes.
+        assert_eq!(store.merge(&points(&[(50, Some(1.0)), (75, Some(2.0)), (76, Some(3.0)), (99, Some(4.0))])), 2);
+        assert_eq!(stored(&store), vec![(76, Some(3.0)), (99, Some(4.0))]);
+    }
+
+    #[test]
+    fn evicted_points_are_not_counted_as_added() {
+        let mut store = SeriesStore::new(2, None);
+        store.merge(&points(&[(10, Some(1.0)), (11, Some(2.0))]));
+        // Both are older than what the store already holds, so they're dropped again.
+        assert_eq!(store.merge(&points(&[(0, Some(3.0)), (1, Some(4.0))])), 0);
+        assert_eq!(store.merge(&points(&[(12, Some(5.0))])), 1);
+        assert_eq!(stored(&store), vec![(11, Some(2.0)), (12, Some(5.0))]);
+    }
+}
@@ -0,0 +1,42 @@
+// src/rust_data_collector/src/summary.rs
+
+use chrono::{DateTime, Utc};
+use pyo3::prelude::*;
+
+use crate::prices::{cheapest_hours, current_price};
+use crate::{OpenWeatherOneCallResponse, SmardDataPoint};
+
+// --- Status Summary ---
+
+// The few numbers a status widget shows. Any of them can be missing, e.g. before SMARD
+// has published the current hour.
+#[pyclass(get_all)]
+#[derive(Debug, Clone, Copy)]
+pub struct Summary {
+    pub current_temp: Option<f64>,         // °C
+    pub current_price: Option<f64>,        // EUR/MWh
+    pub cheapest_upcoming_ts: Option<i64>, // Milliseconds since epoch, start of the hour
+    pub cheapest_upcoming_price: Option<f64>,
+}
+
+#[pymethods]
+impl Summary {
+    fn __repr__(&self) -> String {
+        format!(
+            "Summary(current_temp={:?}, current_price={:?}, cheapest_upcoming_ts={:?}, cheapest_upcoming_price={:?})",
+            self.current_temp, self.current_price, self.cheapest_upcoming_ts, self.cheapest_upcoming_price
+        )
+    }
+}
+
+/// Current temperature and price plus the cheapest hour still to come (the current one
+/// included) at `now`.
+pub fn summarize(weather: &OpenWeatherOneCallResponse, prices: &[SmardDataPoint], now: DateTime<Utc>) -> Summary {
+    let cheapest = cheapest_hours(prices, 1, now).into_iter().next();
+    Summary {
+        current_temp: weather.current.as_ref().map(|c| c.main.temp),
+        current_price: current_price(prices, now),
+        cheapest_upcoming_ts: cheapest.map(|(ts, _)| ts),
+        cheapest_upcoming_price: cheapest.map(|(_, price)| price),
+    }
+}
@@ -0,0 +1,101 @@
+// src/rust_data_collector/src/timestamp.rs
+
+use chrono::{DateTime, TimeZone, Utc};
+
+// --- Timestamp Normalization ---
+
+// OpenWeatherMap and Solcast count in seconds, SMARD in milliseconds. Anything compared
+// or merged across providers goes through here so the unit is never assumed.
+
+// Millisecond timestamps for any date after 1973 are above this; as seconds it would be
+// the year 5138, so nothing below it is a plausible millisecond value.
+pub const MIN_PLAUSIBLE_TIMESTAMP_MS: i64 = 100_000_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimestampUnit {
+    Seconds,
+    Milliseconds,
+}
+
+impl TimestampUnit {
+    /// Guesses the unit from the magnitude of `raw`.
+    pub fn detect(raw: i64) -> Self {
+        if raw.abs() >= MIN_PLAUSIBLE_TIMESTAMP_MS {
+            TimestampUnit::Milliseconds
+        } else {
+            TimestampUnit::Seconds
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "s" | "seconds" => Some(TimestampUnit::Seconds),
+            "ms" | "milliseconds" => Some(TimestampUnit::Milliseconds),
+            _ => None,
+        }
+    }
+}
+
+/// `raw` in the given unit (or the detected one for `None`) as a UTC time; `None` if it is
+/// out of chrono's range.
+pub fn normalize_timestamp_as(raw: i64, unit: Option<TimestampUnit>) -> Option<DateTime<Utc>> {
+    match unit.unwrap_or_else(|| TimestampUnit::detect(raw)) {
+        TimestampUnit::Seconds => Utc.timestamp_opt(raw, 0).single(),
+        TimestampUnit::Milliseconds => Utc.timestamp_millis_opt(raw).single(),
+    }
+}
+
+/// Seconds or milliseconds, told apart by magnitude, as a UTC time.
+pub fn normalize_timestamp(raw: i64) -> Option<DateTime<Utc>> {
+    normalize_timestamp_as(raw, None)
+}
+
+/// Unix seconds for a seconds or milliseconds timestamp.
+pub fn to_unix_seconds(raw: i64) -> i64 {
+    match TimestampUnit::detect(raw) {
+        TimestampUnit::Seconds => raw,
+        TimestampUnit::Milliseconds => raw.div_euclid(1000),
+    }
+}
+
+/// Unix milliseconds for a seconds or milliseconds timestamp.
+pub fn to_unix_millis(raw: i64) -> i64 {
+    match TimestampUnit::detect(raw) {
+        TimestampUnit::Seconds => raw.saturating_mul(1000),
+        TimestampUnit::Milliseconds => raw,
+    }
+}
+
+/// Start of the hour containing `raw` (seconds or milliseconds), in Unix seconds. The
+/// common key for aligning series from different providers.
+pub fn hour_start(raw: i64) -> i64 {
+    let seconds = to_unix_seconds(raw);
+    seconds - seconds.rem_euclid(3600)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECONDS: i64 = 1_717_243_200; // 2024-06-01T12:00:00Z
+    const MILLIS: i64 = SECONDS * 1000;
+
+    #[test]
+    fn seconds_and_milliseconds_normalize_identically() {
+        let expected = Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap();
+        assert_eq!(normalize_timestamp(SECONDS), Some(expected));
+        assert_eq!(normalize_timestamp(MILLIS), Some(expected));
+        assert_eq!(to_unix_seconds(MILLIS), SECONDS);
+        assert_eq!(to_unix_millis(SECONDS), MILLIS);
+        assert_eq!(hour_start(SECONDS + 1799), hour_start(MILLIS + 1_799_000));
+    }
+
+    #[test]
+    fn explicit_unit_overrides_detection() {
+        assert_eq!(TimestampUnit::detect(SECONDS), TimestampUnit::Seconds);
+        assert_eq!(TimestampUnit::detect(MILLIS), TimestampUnit::Milliseconds);
+        // Read as milliseconds, the seconds value is a day in January 1970.
+        let as_millis = normalize_timestamp_as(SECONDS, Some(TimestampUnit::Milliseconds)).unwrap();
+        assert_eq!(as_millis.timestamp(), SECONDS / 1000);
+    }
+}
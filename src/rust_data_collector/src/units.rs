@@ -0,0 +1,192 @@
+// src/rust_data_collector/src/units.rs
+
+use crate::{CollectorError, SmardApiResponse};
+
+// --- Temperature Units ---
+
+// Everything the collector stores is °C (`units=metric`), but One Call answers in Kelvin
+// with `units=standard` and in °F with `units=imperial`, so responses fetched elsewhere
+// may need converting before they're mixed with ours.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemperatureUnit {
+    Celsius,
+    Fahrenheit,
+    Kelvin,
+}
+
+impl TemperatureUnit {
+    /// Accepts "C"/"F"/"K", the full names, or the OpenWeatherMap `units` value that
+    /// implies them ("metric", "imperial", "standard"). Case-insensitive.
+    pub fn parse(unit: &str) -> Option<Self> {
+        match unit.to_ascii_lowercase().as_str() {
+            "c" | "celsius" | "metric" => Some(TemperatureUnit::Celsius),
+            "f" | "fahrenheit" | "imperial" => Some(TemperatureUnit::Fahrenheit),
+            "k" | "kelvin" | "standard" => Some(TemperatureUnit::Kelvin),
+            _ => None,
+        }
+    }
+
+    fn to_kelvin(self, value: f64) -> f64 {
+        match self {
+            TemperatureUnit::Celsius => value + 273.15,
+            TemperatureUnit::Fahrenheit => (value - 32.0) * 5.0 / 9.0 + 273.15,
+            TemperatureUnit::Kelvin => value,
+        }
+    }
+
+    fn of_kelvin(self, kelvin: f64) -> f64 {
+        match self {
+            TemperatureUnit::Celsius => kelvin - 273.15,
+            TemperatureUnit::Fahrenheit => (kelvin - 273.15) * 9.0 / 5.0 + 32.0,
+            TemperatureUnit::Kelvin => kelvin,
+        }
+    }
+}
+
+/// Converts `value` between units. Temperatures below absolute zero are rejected, since
+/// they can only come from a unit mix-up upstream.
+pub fn convert_temperature(value: f64, from: TemperatureUnit, to: TemperatureUnit) -> Result<f64, CollectorError> {
+    let kelvin = from.to_kelvin(value);
+    if kelvin < 0.0 {
+        return Err(CollectorError::InvalidInput(format!(
+            "{} {:?} is below absolute zero",
+            value, from
+        )));
+    }
+    // Skip the round trip through Kelvin, which isn't exact in floating point.
+    Ok(if from == to { value } else { to.of_kelvin(kelvin) })
+}
+
+// --- Price Units ---
+
+// Every price calculation here (statistics, ct/kWh display, costs, the aWATTar export)
+// assumes EUR/MWh, which is what SMARD's day-ahead filters publish. A filter that
+// publishes something else has to be declared, or its values come out off by 1000x (or
+// 100000x).
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceUnit {
+    EurPerMwh,
+    EurPerKwh,
+    CtPerKwh,
+}
+
+impl PriceUnit {
+    /// Accepts "EUR/MWh", "EUR/kWh" and "ct/kWh", case-insensitively.
+    pub fn parse(unit: &str) -> Option<Self> {
+        match unit.to_ascii_lowercase().as_str() {
+            "eur/mwh" => Some(PriceUnit::EurPerMwh),
+            "eur/kwh" => Some(PriceUnit::EurPerKwh),
+            "ct/kwh" => Some(PriceUnit::CtPerKwh),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PriceUnit::EurPerMwh => "EUR/MWh",
+            PriceUnit::EurPerKwh => "EUR/kWh",
+            PriceUnit::CtPerKwh => "ct/kWh",
+        }
+    }
+
+    fn eur_per_mwh_factor(self) -> f64 {
+        match self {
+            PriceUnit::EurPerMwh => 1.0,
+            PriceUnit::EurPerKwh => 1000.0,
+            PriceUnit::CtPerKwh => 10.0,
+        }
+    }
+}
+
+/// Converts a series published in `unit` to EUR/MWh in place and records `unit` as its
+/// `source_unit`. Already-converted series are left alone.
+pub fn normalize_price_unit(response: &mut SmardApiResponse, unit: PriceUnit) {
+    if unit == PriceUnit::EurPerMwh || response.source_unit.is_some() {
+        return;
+    }
+    let factor = unit.eur_per_mwh_factor();
+    for dp in &mut response.data {
+        dp.value = dp.value.map(|v| v * factor);
+    }
+    response.source_unit = Some(unit.as_str().to_string());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SmardDataPoint;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn temperature_conversions_match_reference_points() {
+        use TemperatureUnit::*;
+        assert_close(convert_temperature(100.0, Celsius, Fahrenheit).unwrap(), 212.0);
+        assert_close(convert_temperature(32.0, Fahrenheit, Celsius).unwrap(), 0.0);
+        assert_close(convert_temperature(0.0, Celsius, Kelvin).unwrap(), 273.15);
+        assert_close(convert_temperature(-40.0, Fahrenheit, Celsius).unwrap(), -40.0);
+        assert_close(convert_temperature(300.0, Kelvin, Fahrenheit).unwrap(), 80.33);
+        // Same unit returns the input untouched.
+        assert_eq!(convert_temperature(21.3, Celsius, Celsius).unwrap(), 21.3);
+    }
+
+    #[test]
+    fn temperature_below_absolute_zero_is_rejected() {
+        assert!(matches!(
+            convert_temperature(-300.0, TemperatureUnit::Celsius, TemperatureUnit::Kelvin),
+            Err(CollectorError::InvalidInput(_))
+        ));
+        assert!(convert_temperature(-1.0, TemperatureUnit::Kelvin, TemperatureUnit::Kelvin).is_err());
+    }
+
+    #[test]
+    fn unit_names_parse_case_insensitively() {
+        assert_eq!(TemperatureUnit::parse("Imperial"), Some(TemperatureUnit::Fahrenheit));
+        assert_eq!(TemperatureUnit::parse("k"), Some(TemperatureUnit::Kelvin));
+        assert_eq!(TemperatureUnit::parse("rankine"), None);
+        assert_eq!(PriceUnit::parse("CT/KWH"), Some(PriceUnit::CtPerKwh));
+        assert_eq!(PriceUnit::parse(PriceUnit::EurPerKwh.as_str()), Some(PriceUnit::EurPerKwh));
+        assert_eq!(PriceUnit::parse("EUR/GJ"), None);
+    }
+
+    fn response(values: &[Option<f64>]) -> SmardApiResponse {
+        SmardApiResponse {
+            data: values.iter().enumerate().map(|(i, &value)| SmardDataPoint { timestamp: i as i64, value }).collect(),
+            source_unit: None,
+            config_hash: None,
+        }
+    }
+
+    fn values(response: &SmardApiResponse) -> Vec<Option<f64>> {
+        response.data.iter().map(|dp| dp.value).collect()
+    }
+
+    #[test]
+    fn prices_are_normalized_to_eur_per_mwh() {
+        let mut ct = response(&[Some(8.5), None, Some(-0.3)]);
+        normalize_price_unit(&mut ct, PriceUnit::CtPerKwh);
+        assert_eq!(values(&ct), vec![Some(85.0), None, Some(-3.0)]);
+        assert_eq!(ct.source_unit.as_deref(), Some("ct/kWh"));
+
+        let mut eur = response(&[Some(0.085)]);
+        normalize_price_unit(&mut eur, PriceUnit::EurPerKwh);
+        assert_close(eur.data[0].value.unwrap(), 85.0);
+        assert_eq!(eur.source_unit.as_deref(), Some("EUR/kWh"));
+    }
+
+    #[test]
+    fn eur_per_mwh_and_converted_series_are_left_alone() {
+        let mut mwh = response(&[Some(85.0)]);
+        normalize_price_unit(&mut mwh, PriceUnit::EurPerMwh);
+        assert_eq!((values(&mwh), mwh.source_unit), (vec![Some(85.0)], None));
+
+        let mut ct = response(&[Some(8.5)]);
+        normalize_price_unit(&mut ct, PriceUnit::CtPerKwh);
+        normalize_price_unit(&mut ct, PriceUnit::CtPerKwh);
+        assert_eq!(values(&ct), vec![Some(85.0)]);
+    }
+}
@@ -0,0 +1,111 @@
+// src/rust_data_collector/src/validate.rs
+
+use serde::de::DeserializeOwned;
+use std::fs;
+use std::path::Path;
+
+use crate::rolling::RollingWindow;
+use crate::{units, CollectorError, OpenWeatherOneCallResponse, SmardApiResponse};
+
+// --- Saved File Validation ---
+
+// Older saved files can still deserialize and yet be useless: most fields default when
+// missing, so a file without `hourly` reads as a forecast with no hours, and the dashboard
+// just stays empty. Loading through here checks the structure as well and names every
+// problem at once.
+
+/// Structural checks beyond what deserializing already guarantees.
+pub trait Validate {
+    /// Every problem found, phrased to follow the file name ("is missing the hourly array").
+    fn problems(&self) -> Vec<String>;
+}
+
+// Timestamps must be strictly increasing; the first offending position is reported.
+fn check_increasing(what: &str, timestamps: impl Iterator<Item = i64>, problems: &mut Vec<String>) {
+    let mut previous: Option<i64> = None;
+    for (i, ts) in timestamps.enumerate() {
+        if previous.is_some_and(|p| ts <= p) {
+            problems.push(format!("has {} timestamps out of order or repeated at position {} ({})", what, i, ts));
+            return;
+        }
+        previous = Some(ts);
+    }
+}
+
+// Above this a "metric" temperature is really Kelvin: the file was fetched without
+// `units=metric`.
+const MAX_CELSIUS: f64 = 100.0;
+
+impl Validate for OpenWeatherOneCallResponse {
+    fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.hourly.is_empty() {
+            problems.push("is missing the hourly array (or it is empty)".to_string());
+        }
+        check_increasing("hourly", self.hourly.iter().map(|h| h.dt), &mut problems);
+        if let Some(h) = self.hourly.iter().find(|h| !h.temp.is_finite()) {
+            problems.push(format!("has a non-numeric temperature at dt={}", h.dt));
+        } else if self.hourly.iter().any(|h| h.temp > MAX_CELSIUS) {
+            problems.push("has temperatures above 100 °C, so it was likely saved in Kelvin rather than metric units".to_string());
+        }
+        problems
+    }
+}
+
+impl Validate for SmardApiResponse {
+    fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.data.is_empty() {
+            problems.push("is missing the data array (or it is empty)".to_string());
+        } else if self.data.iter().all(|dp| dp.value.is_none()) {
+            problems.push(format!("has no price values in any of its {} points", self.data.len()));
+        }
+        check_increasing("price", self.data.iter().map(|dp| dp.timestamp), &mut problems);
+        if let Some(unit) = self.source_unit.as_deref().filter(|unit| units::PriceUnit::parse(unit).is_none()) {
+            problems.push(format!("declares an unknown source unit {:?}", unit));
+        }
+        problems
+    }
+}
+
+impl Validate for RollingWindow {
+    fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.window_hours == 0 {
+            problems.push("has a window of 0 hours".to_string());
+        }
+        check_increasing("hourly", self.hourly.iter().map(|p| p.timestamp), &mut problems);
+        problems
+    }
+}
+
+/// Reads and deserializes `path`, then runs its `Validate` checks. Anything wrong, from
+/// unreadable JSON to out-of-order hours, comes back as one `InvalidInput` naming the file.
+pub fn load_and_validate<T: DeserializeOwned + Validate>(path: &Path) -> Result<T, CollectorError> {
+    let name = path.file_name().map_or_else(|| path.display().to_string(), |n| n.to_string_lossy().into_owned());
+    let body = fs::read_to_string(path)?;
+    let value: T = serde_json::from_str(&body)
+        .map_err(|e| CollectorError::InvalidInput(format!("{} does not match the expected format: {}", name, e)))?;
+    let problems = value.problems();
+    if problems.is_empty() {
+        Ok(value)
+    } else {
+        Err(CollectorError::InvalidInput(format!("{} {}", name, problems.join("; "))))
+    }
+}
+
+/// `load_and_validate` for a saved file of unknown kind, told apart by shape as in
+/// `diff`: "weather" (`hourly`, no `window_hours`), "prices" (`data`) or "rolling".
+pub fn validate_saved_file(path: &Path) -> Result<&'static str, CollectorError> {
+    let body = fs::read_to_string(path)?;
+    let json: serde_json::Value = serde_json::from_str(&body)
+        .map_err(|e| CollectorError::InvalidInput(format!("{} is not valid JSON: {}", path.display(), e)))?;
+    if json.get("window_hours").is_some() {
+        load_and_validate::<RollingWindow>(path).map(|_| "rolling")
+    } else if json.get("data").is_some() {
+        load_and_validate::<SmardApiResponse>(path).map(|_| "prices")
+    } else {
+        // Covers a weather file that lost its `hourly` array, which is the likelier drift.
+        load_and_validate::<OpenWeatherOneCallResponse>(path).map(|_| "weather")
+    }
+}
@@ -0,0 +1,309 @@
+// src/rust_data_collector/src/weather.rs
+
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::{circuit, get_openweather_data, http, secrets, CollectorError, OneCallOptions, OpenWeatherOneCallResponse};
+
+// --- Normalized Weather Data ---
+
+// Provider-agnostic hourly weather, so callers don't care who served the forecast.
+#[pyclass(get_all)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeatherHour {
+    pub timestamp: i64, // Unix timestamp (seconds), start of the hour
+    pub temp: f64,      // °C
+    pub clouds: Option<f64>, // Cloud cover, %; `None` where the provider left it out
+    pub pop: Option<f64>,    // Probability of precipitation, 0-1; likewise
+    pub ghi: Option<f64>,    // W/m², for providers that forecast irradiance
+}
+
+#[pyclass(get_all)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeatherData {
+    pub provider: String, // Which provider actually served the data
+    pub hourly: Vec<WeatherHour>,
+}
+
+pub trait WeatherProvider {
+    fn name(&self) -> &'static str;
+    /// The provider's documented horizon; `fetch_weather` is never asked for more.
+    fn max_forecast_hours(&self) -> u32;
+    /// The next `hours` hours, starting with the current one.
+    fn fetch_weather(&self, lat: f64, lon: f64, hours: u32) -> Result<WeatherData, CollectorError>;
+}
+
+// --- Forecast Horizons ---
+
+// Providers don't fail when asked past their horizon, they just return less. Checking
+// against the documented maxima up front makes the shortfall explicit.
+pub const DEFAULT_FORECAST_HOURS: u32 = 48;
+pub const OPENWEATHER_MAX_FORECAST_HOURS: u32 = 48; // One Call's `hourly` block
+pub const OPEN_METEO_MAX_FORECAST_HOURS: u32 = 16 * 24;
+
+/// `requested` if `provider` forecasts that far. Otherwise its maximum when `clamp` is set
+/// (with a logged warning), or an error naming the limit.
+pub fn limit_forecast_hours(provider: &str, requested: u32, max: u32, clamp: bool) -> Result<u32, CollectorError> {
+    if requested == 0 {
+        return Err(CollectorError::InvalidInput("forecast hours must be at least 1".to_string()));
+    }
+    if requested <= max {
+        return Ok(requested);
+    }
+    if clamp {
+        log!("WARNING (Rust): {} forecasts at most {} hours; clamping the {} requested.", provider, max, requested);
+        return Ok(max);
+    }
+    Err(CollectorError::ForecastHorizon { provider: provider.to_string(), max_hours: max, requested })
+}
+
+// --- Open-Meteo ---
+
+// Free, keyless forecast API.
+const OPEN_METEO_URL: &str = "https://api.open-meteo.com/v1/forecast";
+
+// Open-Meteo returns column arrays; any cell can be null.
+#[derive(Debug, Deserialize)]
+struct OpenMeteoHourly {
+    time: Vec<i64>,
+    temperature_2m: Vec<Option<f64>>,
+    cloud_cover: Vec<Option<f64>>,
+    precipitation_probability: Vec<Option<f64>>,
+    shortwave_radiation: Vec<Option<f64>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenMeteoResponse {
+    hourly: OpenMeteoHourly,
+}
+
+pub struct OpenMeteoProvider;
+
+impl WeatherProvider for OpenMeteoProvider {
+    fn name(&self) -> &'static str {
+        "open-meteo"
+    }
+
+    fn max_forecast_hours(&self) -> u32 {
+        OPEN_METEO_MAX_FORECAST_HOURS
+    }
+
+    fn fetch_weather(&self, lat: f64, lon: f64, hours: u32) -> Result<WeatherData, CollectorError> {
+        let url = format!(
+            "{}?latitude={}&longitude={}&hourly=temperature_2m,cloud_cover,precipitation_probability,shortwave_radiation&forecast_hours={}&timezone=UTC&timeformat=unixtime",
+            OPEN_METEO_URL, lat, lon, hours
+        );
+        log!("DEBUG (Rust): Open-Meteo API Request URL: {}", url);
+        let parsed: OpenMeteoResponse = circuit::OPEN_METEO.call(|| {
+            let response = http::client()?.get(&url).send()?;
+            let status = response.status();
+            let content_type = http::content_type(&response);
+            let body = http::read_body(response)?;
+            if !status.is_success() {
+                log!("ERROR (Rust): Open-Meteo returned non-success status {}. Full raw response: {}", status, body);
+                return Err(CollectorError::Status { status: status.as_u16(), body });
+            }
+            http::expect_json(content_type.as_deref(), &body)?;
+            serde_json::from_str(&body).map_err(|e| CollectorError::Parse { source: e, body })
+        })?;
+
+        let h = &parsed.hourly;
+        let hourly = h.time.iter().enumerate()
+            // An hour without a temperature is useless to every consumer; skip it.
+            .filter_map(|(i, &timestamp)| {
+                Some(WeatherHour {
+                    timestamp,
+                    temp: h.temperature_2m.get(i).copied().flatten()?,
+                    // Missing, not clear and dry: a null mustn't read as 0 %.
+                    clouds: h.cloud_cover.get(i).copied().flatten(),
+                    pop: h.precipitation_probability.get(i).copied().flatten().map(|p| p / 100.0),
+                    ghi: h.shortwave_radiation.get(i).copied().flatten(),
+                })
+            })
+            .collect();
+        Ok(WeatherData { provider: self.name().to_string(), hourly })
+    }
+}
+
+// --- OpenWeatherMap ---
+
+pub struct OpenWeatherProvider {
+    pub api_key: String,
+}
+
+impl From<&OpenWeatherOneCallResponse> for WeatherData {
+    fn from(response: &OpenWeatherOneCallResponse) -> Self {
+        WeatherData {
+            provider: "openweather".to_string(),
+            hourly: response.hourly.iter()
+                .map(|hour| WeatherHour {
+                    timestamp: hour.dt - hour.dt.rem_euclid(3600),
+                    temp: hour.temp,
+                    clouds: Some(hour.clouds.all as f64),
+                    pop: Some(hour.pop),
+                    ghi: None,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl WeatherProvider for OpenWeatherProvider {
+    fn name(&self) -> &'static str {
+        "openweather"
+    }
+
+    fn max_forecast_hours(&self) -> u32 {
+        OPENWEATHER_MAX_FORECAST_HOURS
+    }
+
+    fn fetch_weather(&self, lat: f64, lon: f64, hours: u32) -> Result<WeatherData, CollectorError> {
+        let response = get_openweather_data(&self.api_key, lat, lon, &OneCallOptions::default())?;
+        let mut data = WeatherData::from(&response);
+        data.hourly.truncate(hours as usize);
+        Ok(data)
+    }
+}
+
+// --- Fallback Across Providers ---
+
+fn provider_by_name(name: &str, secrets_file: Option<&str>) -> Result<Box<dyn WeatherProvider>, CollectorError> {
+    match name {
+        "open-meteo" => Ok(Box::new(OpenMeteoProvider)),
+        "openweather" => {
+            let api_key = secrets::get_secret("OPENWEATHER_API_KEY", secrets_file)?;
+            Ok(Box::new(OpenWeatherProvider { api_key }))
+        }
+        other => Err(CollectorError::InvalidInput(format!(
+            "Unknown weather provider {:?}, expected \"open-meteo\" or \"openweather\"",
+            other
+        ))),
+    }
+}
+
+// Outages, unconfigured providers and too short horizons are worth trying the next
+// provider for; a request the provider rejected as invalid would fail the same way
+// everywhere.
+fn should_fall_back(e: &CollectorError) -> bool {
+    e.is_transient()
+        || matches!(
+            e,
+            CollectorError::MissingApiKey(_) | CollectorError::BudgetExceeded { .. } | CollectorError::ForecastHorizon { .. }
+        )
+}
+
+/// Tries each named provider in order and returns the first success. Stops early on
+/// errors that another provider wouldn't fix; otherwise returns the last error. A provider
+/// that can't forecast `hours` ahead is skipped, unless `clamp_hours` accepts its maximum.
+pub fn fetch_weather_with_fallback(
+    lat: f64,
+    lon: f64,
+    providers: &[String],
+    hours: u32,
+    clamp_hours: bool,
+    secrets_file: Option<&str>,
+) -> Result<WeatherData, CollectorError> {
+    let mut last_error = CollectorError::InvalidInput("No weather providers given".to_string());
+    for name in providers {
+        let result = provider_by_name(name, secrets_file).and_then(|provider| {
+            let hours = limit_forecast_hours(provider.name(), hours, provider.max_forecast_hours(), clamp_hours)?;
+            provider.fetch_weather(lat, lon, hours)
+        });
+        match result {
+            Ok(data) => return Ok(data),
+            Err(e) if should_fall_back(&e) => {
+                log!("WARNING (Rust): Weather provider {} failed ({}), trying the next one.", name, e);
+                last_error = e;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Err(last_error)
+}
+
+// --- Ensembles ---
+
+/// Weighted average of several forecasts, hour by hour. Each hour averages over the
+/// forecasts that cover it (and for `clouds`, `pop` and `ghi`, the ones that have a value),
+/// with their weights renormalised, so a shorter or patchier forecast doesn't drag the rest
+/// towards zero.
+/// `weights` pairs up with `forecasts`; weights are relative and need not sum to 1.
+pub fn ensemble_weather(forecasts: &[WeatherData], weights: &[f64]) -> Result<WeatherData, CollectorError> {
+    if forecasts.len() != weights.len() {
+        return Err(CollectorError::InvalidInput(format!(
+            "{} forecasts but {} weights",
+            forecasts.len(), weights.len()
+        )));
+    }
+    if weights.iter().any(|w| !w.is_finite() || *w < 0.0) || !weights.iter().any(|w| *w > 0.0) {
+        return Err(CollectorError::InvalidInput(format!(
+            "weights must be non-negative with at least one above zero, got {:?}",
+            weights
+        )));
+    }
+
+    // Weighted sums per hour, with the total weight behind them. The optional fields keep
+    // their own `(weight, sum)`, since not every forecast has them for every hour.
+    #[derive(Default)]
+    struct Sums {
+        weight: f64,
+        temp: f64,
+        clouds: (f64, f64),
+        pop: (f64, f64),
+        ghi: (f64, f64),
+    }
+    fn add(sum: &mut (f64, f64), weight: f64, value: Option<f64>) {
+        if let Some(value) = value {
+            sum.0 += weight;
+            sum.1 += weight * value;
+        }
+    }
+    fn mean(sum: (f64, f64)) -> Option<f64> {
+        (sum.0 > 0.0).then(|| sum.1 / sum.0)
+    }
+    let mut hours: BTreeMap<i64, Sums> = BTreeMap::new();
+    for (forecast, &weight) in forecasts.iter().zip(weights).filter(|(_, w)| **w > 0.0) {
+        for hour in &forecast.hourly {
+            let sums = hours.entry(hour.timestamp).or_default();
+            sums.weight += weight;
+            sums.temp += weight * hour.temp;
+            add(&mut sums.clouds, weight, hour.clouds);
+            add(&mut sums.pop, weight, hour.pop);
+            add(&mut sums.ghi, weight, hour.ghi);
+        }
+    }
+
+    let providers: Vec<&str> = forecasts.iter().map(|f| f.provider.as_str()).collect();
+    Ok(WeatherData {
+        provider: format!("ensemble({})", providers.join(",")),
+        hourly: hours.into_iter()
+            .map(|(timestamp, s)| WeatherHour {
+                timestamp,
+                temp: s.temp / s.weight,
+                clouds: mean(s.clouds),
+                pop: mean(s.pop),
+                ghi: mean(s.ghi),
+            })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hour(timestamp: i64, clouds: Option<f64>, pop: Option<f64>) -> WeatherHour {
+        WeatherHour { timestamp, temp: 10.0, clouds, pop, ghi: None }
+    }
+
+    #[test]
+    fn missing_cloud_cover_is_averaged_over_the_forecasts_that_have_it() {
+        let meteo = WeatherData { provider: "open-meteo".to_string(), hourly: vec![hour(0, None, None), hour(3600, None, Some(0.2))] };
+        let owm = WeatherData { provider: "openweather".to_string(), hourly: vec![hour(0, Some(80.0), Some(0.6)), hour(3600, None, None)] };
+        let ensemble = ensemble_weather(&[meteo, owm], &[2.0, 1.0]).unwrap();
+        let fields: Vec<_> = ensemble.hourly.iter().map(|h| (h.clouds, h.pop, h.ghi)).collect();
+        assert_eq!(fields, vec![(Some(80.0), Some(0.6), None), (None, Some(0.2), None)]);
+        assert_eq!(ensemble.provider, "ensemble(open-meteo,openweather)");
+    }
+}
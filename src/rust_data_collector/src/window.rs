@@ -0,0 +1,118 @@
+// src/rust_data_collector/src/window.rs
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::CollectorError;
+
+// --- Explicit Fetch Windows ---
+
+// Which provider path serves an explicit `[start, end]` window:
+//
+// | Window                                   | Weather                        | SMARD                     |
+// |------------------------------------------|--------------------------------|---------------------------|
+// | Within now - 1h .. now + 48h             | One Call forecast              | index (recent data)       |
+// | Entirely in the past                     | timemachine, one call per hour | index, or historical      |
+// |                                          |                                | segments if older than 7d |
+// | Past and future at once, or beyond +48h  | error                          | -                         |
+//
+// SMARD itself has no horizon: day-ahead prices simply end where the last auction did.
+
+// One Call forecasts this far ahead; the first hourly entry is the current hour.
+pub const FORECAST_HORIZON: Duration = Duration::hours(48);
+const FORECAST_LOOKBACK: Duration = Duration::hours(1);
+
+// The index only carries recent data; anything starting further back is backfilled.
+pub const SMARD_INDEX_HISTORY: Duration = Duration::days(7);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeatherRoute {
+    Forecast,
+    Timemachine,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmardRoute {
+    Index,
+    Historical,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct WindowPlan {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub weather: WeatherRoute,
+    pub smard: SmardRoute,
+}
+
+pub fn parse_rfc3339(name: &str, value: &str) -> Result<DateTime<Utc>, CollectorError> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|dt| dt.with_timezone(&Utc))
+        .map_err(|e| CollectorError::InvalidTimeRange(format!(
+            "{} {:?} is not an RFC 3339 datetime (e.g. \"2024-05-01T00:00:00Z\"): {}",
+            name, value, e
+        )))
+}
+
+/// Chooses the weather and SMARD paths for `[start, end]` relative to `now`, see the table
+/// above. Windows no single weather endpoint can serve are rejected rather than half-filled.
+pub fn plan_window(start: DateTime<Utc>, end: DateTime<Utc>, now: DateTime<Utc>) -> Result<WindowPlan, CollectorError> {
+    if start >= end {
+        return Err(CollectorError::InvalidTimeRange(format!("start ({}) must be before end ({})", start, end)));
+    }
+    let weather = if end <= now {
+        WeatherRoute::Timemachine
+    } else if start >= now - FORECAST_LOOKBACK && end <= now + FORECAST_HORIZON {
+        WeatherRoute::Forecast
+    } else if end > now + FORECAST_HORIZON {
+        return Err(CollectorError::InvalidTimeRange(format!(
+            "end ({}) is beyond the {}h weather forecast horizon (until {})",
+            end, FORECAST_HORIZON.num_hours(), now + FORECAST_HORIZON
+        )));
+    } else {
+        return Err(CollectorError::InvalidTimeRange(format!(
+            "window {} .. {} spans both past (timemachine) and future (forecast) weather; split it at {}",
+            start, end, now
+        )));
+    };
+    let smard = if start < now - SMARD_INDEX_HISTORY { SmardRoute::Historical } else { SmardRoute::Index };
+    Ok(WindowPlan { start, end, weather, smard })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 6, 15, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn future_window_uses_the_forecast_and_index() {
+        let plan = plan_window(now(), now() + Duration::hours(24), now()).unwrap();
+        assert_eq!((plan.weather, plan.smard), (WeatherRoute::Forecast, SmardRoute::Index));
+    }
+
+    #[test]
+    fn past_windows_use_timemachine_and_historical_when_old() {
+        let recent = plan_window(now() - Duration::days(2), now() - Duration::days(1), now()).unwrap();
+        assert_eq!((recent.weather, recent.smard), (WeatherRoute::Timemachine, SmardRoute::Index));
+        let old = plan_window(now() - Duration::days(30), now() - Duration::days(29), now()).unwrap();
+        assert_eq!((old.weather, old.smard), (WeatherRoute::Timemachine, SmardRoute::Historical));
+    }
+
+    #[test]
+    fn unsupported_windows_are_rejected() {
+        let beyond_horizon = plan_window(now(), now() + Duration::hours(72), now());
+        assert!(matches!(beyond_horizon, Err(CollectorError::InvalidTimeRange(m)) if m.contains("horizon")));
+        let spanning = plan_window(now() - Duration::days(1), now() + Duration::hours(1), now());
+        assert!(matches!(spanning, Err(CollectorError::InvalidTimeRange(m)) if m.contains("split it")));
+        assert!(plan_window(now(), now(), now()).is_err());
+    }
+
+    #[test]
+    fn rfc3339_parsing_normalizes_offsets() {
+        assert_eq!(parse_rfc3339("start", "2024-06-15T14:00:00+02:00").unwrap(), now());
+        assert!(matches!(parse_rfc3339("start", "2024-06-15"), Err(CollectorError::InvalidTimeRange(_))));
+    }
+}
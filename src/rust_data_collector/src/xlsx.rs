@@ -0,0 +1,152 @@
+// src/rust_data_collector/src/xlsx.rs
+
+use rust_xlsxwriter::{ExcelDateTime, Format, Workbook, Worksheet, XlsxError};
+use std::path::Path;
+
+use crate::merge::HourlyPoint;
+use crate::timestamp::to_unix_seconds;
+use crate::{prices, CollectorError, OpenWeatherOneCallResponse, SmardApiResponse};
+
+// --- Excel Workbook Export ---
+
+// One sheet per dataset plus a price summary, for people who open the data in Excel
+// rather than pandas. All times are UTC and written as real Excel dates.
+
+fn xlsx_error(e: XlsxError) -> CollectorError {
+    CollectorError::Io(std::io::Error::other(e))
+}
+
+struct Formats {
+    header: Format,
+    datetime: Format,
+}
+
+fn write_header(sheet: &mut Worksheet, formats: &Formats, columns: &[&str]) -> Result<(), XlsxError> {
+    for (col, name) in columns.iter().enumerate() {
+        sheet.write_string_with_format(0, col as u16, *name, &formats.header)?;
+    }
+    sheet.set_column_width(0, 18)?;
+    sheet.set_freeze_panes(1, 0)?;
+    Ok(())
+}
+
+// Accepts seconds or milliseconds, like everything that mixes providers.
+fn write_time(sheet: &mut Worksheet, formats: &Formats, row: u32, col: u16, raw: i64) -> Result<(), XlsxError> {
+    let datetime = ExcelDateTime::from_timestamp(to_unix_seconds(raw))?;
+    sheet.write_datetime_with_format(row, col, &datetime, &formats.datetime)?;
+    Ok(())
+}
+
+// Missing values are left as empty cells.
+fn write_optional(sheet: &mut Worksheet, row: u32, col: u16, value: Option<f64>) -> Result<(), XlsxError> {
+    if let Some(v) = value {
+        sheet.write_number(row, col, v)?;
+    }
+    Ok(())
+}
+
+fn weather_sheet(sheet: &mut Worksheet, formats: &Formats, weather: &OpenWeatherOneCallResponse) -> Result<(), XlsxError> {
+    sheet.set_name("Weather")?;
+    write_header(sheet, formats, &["Time (UTC)", "Temp (°C)", "Clouds (%)", "Precip. probability", "Rain 1h (mm)", "Snow 1h (mm)", "Pressure (hPa)", "Dew point (°C)", "Visibility (m)", "Description"])?;
+    for (i, hour) in weather.hourly.iter().enumerate() {
+        let row = i as u32 + 1;
+        write_time(sheet, formats, row, 0, hour.dt)?;
+        sheet.write_number(row, 1, hour.temp)?;
+        sheet.write_number(row, 2, hour.clouds.all)?;
+        sheet.write_number(row, 3, hour.pop)?;
+        write_optional(sheet, row, 4, hour.rain_1h)?;
+        write_optional(sheet, row, 5, hour.snow_1h)?;
+        write_optional(sheet, row, 6, hour.pressure.map(f64::from))?;
+        write_optional(sheet, row, 7, hour.dew_point)?;
+        write_optional(sheet, row, 8, hour.visibility.map(f64::from))?;
+        sheet.write_string(row, 9, hour.weather.first().map(|w| w.description.as_str()).unwrap_or_default())?;
+    }
+    Ok(())
+}
+
+fn prices_sheet(sheet: &mut Worksheet, formats: &Formats, smard: &SmardApiResponse) -> Result<(), XlsxError> {
+    sheet.set_name("Prices")?;
+    write_header(sheet, formats, &["Time (UTC)", "Price (EUR/MWh)"])?;
+    for (i, dp) in smard.data.iter().enumerate() {
+        let row = i as u32 + 1;
+        write_time(sheet, formats, row, 0, dp.timestamp)?;
+        write_optional(sheet, row, 1, dp.value)?;
+    }
+    Ok(())
+}
+
+fn merged_sheet(sheet: &mut Worksheet, formats: &Formats, merged: &[HourlyPoint]) -> Result<(), XlsxError> {
+    sheet.set_name("Merged")?;
+    write_header(sheet, formats, &["Time (UTC)", "Temp (°C)", "Clouds (%)", "Precip. probability", "Rain 1h (mm)", "Snow 1h (mm)", "Pressure (hPa)", "Dew point (°C)", "Visibility (m)", "GHI (W/m²)", "Price (EUR/MWh)", "Description"])?;
+    for (i, p) in merged.iter().enumerate() {
+        let row = i as u32 + 1;
+        write_time(sheet, formats, row, 0, p.timestamp)?;
+        sheet.write_number(row, 1, p.temp)?;
+        sheet.write_number(row, 2, p.clouds)?;
+        sheet.write_number(row, 3, p.pop)?;
+        write_optional(sheet, row, 4, p.rain_1h)?;
+        write_optional(sheet, row, 5, p.snow_1h)?;
+        write_optional(sheet, row, 6, p.pressure.map(f64::from))?;
+        write_optional(sheet, row, 7, p.dew_point)?;
+        write_optional(sheet, row, 8, p.visibility.map(f64::from))?;
+        write_optional(sheet, row, 9, p.ghi)?;
+        write_optional(sheet, row, 10, p.price)?;
+        sheet.write_string(row, 11, &p.description)?;
+    }
+    Ok(())
+}
+
+// Overall price statistics, then one row per German local day.
+fn summary_sheet(sheet: &mut Worksheet, formats: &Formats, smard: &SmardApiResponse) -> Result<(), XlsxError> {
+    sheet.set_name("Summary")?;
+    let values: Vec<f64> = smard.data.iter().filter_map(|dp| dp.value).collect();
+    let mean = (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64);
+    let stats = [
+        ("Priced hours", Some(values.len() as f64)),
+        ("Mean price (EUR/MWh)", mean),
+        ("Min price (EUR/MWh)", values.iter().copied().reduce(f64::min)),
+        ("Max price (EUR/MWh)", values.iter().copied().reduce(f64::max)),
+    ];
+    sheet.set_column_width(0, 22)?;
+    for (row, (label, value)) in stats.iter().enumerate() {
+        sheet.write_string_with_format(row as u32, 0, *label, &formats.header)?;
+        write_optional(sheet, row as u32, 1, *value)?;
+    }
+
+    let first_row = stats.len() as u32 + 1;
+    for (col, name) in ["Day (Berlin)", "Hours in day", "Priced hours", "Mean", "Min", "Max"].iter().enumerate() {
+        sheet.write_string_with_format(first_row, col as u16, *name, &formats.header)?;
+    }
+    for (i, day) in prices::daily_prices(&smard.data).iter().enumerate() {
+        let row = first_row + 1 + i as u32;
+        sheet.write_string(row, 0, &day.date)?;
+        sheet.write_number(row, 1, day.hours_in_day as f64)?;
+        sheet.write_number(row, 2, day.priced_hours as f64)?;
+        write_optional(sheet, row, 3, day.mean)?;
+        write_optional(sheet, row, 4, day.min)?;
+        write_optional(sheet, row, 5, day.max)?;
+    }
+    Ok(())
+}
+
+/// Writes `weather`, `smard` and `merged` to an `.xlsx` workbook at `path`, with a
+/// summary sheet of price statistics first.
+pub fn export_xlsx(
+    path: &Path,
+    weather: &OpenWeatherOneCallResponse,
+    smard: &SmardApiResponse,
+    merged: &[HourlyPoint],
+) -> Result<(), CollectorError> {
+    let formats = Formats {
+        header: Format::new().set_bold(),
+        datetime: Format::new().set_num_format("yyyy-mm-dd hh:mm"),
+    };
+    let mut workbook = Workbook::new();
+    summary_sheet(workbook.add_worksheet(), &formats, smard).map_err(xlsx_error)?;
+    weather_sheet(workbook.add_worksheet(), &formats, weather).map_err(xlsx_error)?;
+    prices_sheet(workbook.add_worksheet(), &formats, smard).map_err(xlsx_error)?;
+    merged_sheet(workbook.add_worksheet(), &formats, merged).map_err(xlsx_error)?;
+    workbook.save(path).map_err(xlsx_error)?;
+    log!("Excel workbook saved to {:?}", path);
+    Ok(())
+}